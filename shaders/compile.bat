@@ -1,4 +1,8 @@
 C:\VulkanSDK\1.3.211.0\Bin\glslc.exe only_color.vert -o only_color_vert.spv
 C:\VulkanSDK\1.3.211.0\Bin\glslc.exe only_color.frag -o only_color_frag.spv
 C:\VulkanSDK\1.3.211.0\Bin\glslc.exe texture.vert -o texture_vert.spv
-C:\VulkanSDK\1.3.211.0\Bin\glslc.exe texture.frag -o texture_frag.spv
\ No newline at end of file
+C:\VulkanSDK\1.3.211.0\Bin\glslc.exe texture.frag -o texture_frag.spv
+C:\VulkanSDK\1.3.211.0\Bin\glslc.exe skybox.vert -o skybox_vert.spv
+C:\VulkanSDK\1.3.211.0\Bin\glslc.exe skybox.frag -o skybox_frag.spv
+C:\VulkanSDK\1.3.211.0\Bin\glslc.exe gradient.vert -o gradient_vert.spv
+C:\VulkanSDK\1.3.211.0\Bin\glslc.exe gradient.frag -o gradient_frag.spv
\ No newline at end of file
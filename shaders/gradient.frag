@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) in float fragHeight;
+
+layout(binding = 0) uniform GradientUniform {
+    vec4 top;
+    vec4 bottom;
+} gradient;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    outColor = mix(gradient.top, gradient.bottom, clamp(fragHeight, 0.0, 1.0));
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) out float fragHeight;
+
+// one triangle covering the screen, from the vertex index only
+void main() {
+    vec2 uv = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+    // depth of the far plane, behind everything
+    gl_Position = vec4(uv * 2.0 - 1.0, 1.0, 1.0);
+    fragHeight = uv.y;
+}
@@ -2,9 +2,68 @@
 
 layout(location = 0) in vec3 fragColor;
 layout(location = 1) in vec2 fragTexCoord;
+layout(location = 2) in vec3 fragNormal;
+layout(location = 3) in vec3 fragPosition;
 
 layout(location = 0) out vec4 outColor;
 
+layout(binding = 0) uniform CameraUniform {
+    mat4 view;
+    mat4 proj;
+    vec4 position;
+} camera;
+
+const int MAX_POINT_LIGHTS = 16;
+const float SHININESS = 32.0;
+const float SPECULAR_STRENGTH = 0.5;
+
+struct PointLight {
+    vec4 position; // w: range
+    vec4 color;    // w: intensity
+};
+
+layout(binding = 1) uniform LightsUniform {
+    vec4 ambient;
+    vec4 directionalDirection;
+    vec4 directionalColor; // w: intensity
+    uvec4 pointLightCount;
+    PointLight pointLights[MAX_POINT_LIGHTS];
+} lights;
+
+// Blinn-Phong contribution of a light coming from `lightDir` (towards the light).
+vec3 blinnPhong(vec3 normal, vec3 viewDir, vec3 lightDir, vec3 radiance, vec3 albedo) {
+    float diffuse = max(dot(normal, lightDir), 0.0);
+    vec3 halfway = normalize(lightDir + viewDir);
+    float specular = diffuse > 0.0 ? pow(max(dot(normal, halfway), 0.0), SHININESS) : 0.0;
+    return radiance * (diffuse * albedo + SPECULAR_STRENGTH * specular);
+}
+
+// Vertices without normal are drawn unlit.
+vec3 shade(vec3 albedo) {
+    if (dot(fragNormal, fragNormal) == 0.0) {
+        return albedo;
+    }
+    // back faces of double sided materials are lit from their side
+    vec3 normal = gl_FrontFacing ? normalize(fragNormal) : -normalize(fragNormal);
+    vec3 viewDir = normalize(camera.position.xyz - fragPosition);
+    vec3 color = lights.ambient.rgb * albedo;
+
+    color += blinnPhong(normal, viewDir, -lights.directionalDirection.xyz,
+        lights.directionalColor.rgb * lights.directionalColor.w, albedo);
+
+    uint count = min(lights.pointLightCount.x, uint(MAX_POINT_LIGHTS));
+    for (uint i = 0; i < count; i++) {
+        PointLight light = lights.pointLights[i];
+        vec3 toLight = light.position.xyz - fragPosition;
+        float distance = length(toLight);
+        float falloff = clamp(1.0 - pow(distance / light.position.w, 4.0), 0.0, 1.0);
+        float attenuation = falloff * falloff / (distance * distance + 1.0);
+        color += blinnPhong(normal, viewDir, toLight / distance,
+            light.color.rgb * light.color.w * attenuation, albedo);
+    }
+    return color;
+}
+
 void main() {
-    outColor = vec4(fragColor, 1.0);
+    outColor = vec4(shade(fragColor), 1.0);
 }
@@ -1,23 +1,40 @@
 #version 450
 
-layout(binding = 0) uniform UniformBufferObject {
+layout(binding = 0) uniform CameraUniform {
+    mat4 view;
     mat4 proj;
-} ubo;
+    vec4 position;
+} camera;
 
 layout(push_constant) uniform PushConstants {
     mat4 model;
-    mat4 view;
 } pcs;
 
 layout(location = 0) in vec3 inPosition;
 layout(location = 1) in vec3 inColor;
 layout(location = 2) in vec2 inTexCoord;
+layout(location = 3) in vec3 inNormal;
+
+// per instance
+layout(location = 4) in vec4 inInstanceTransform0;
+layout(location = 5) in vec4 inInstanceTransform1;
+layout(location = 6) in vec4 inInstanceTransform2;
+layout(location = 7) in vec4 inInstanceTransform3;
+layout(location = 8) in vec3 inInstanceTint;
 
 layout(location = 0) out vec3 fragColor;
 layout(location = 1) out vec2 fragTexCoord;
+layout(location = 2) out vec3 fragNormal;
+layout(location = 3) out vec3 fragPosition;
 
 void main() {
-    gl_Position = ubo.proj * pcs.view * pcs.model * vec4(inPosition, 1.0);
-    fragColor = inColor;
+    mat4 model = pcs.model * mat4(inInstanceTransform0, inInstanceTransform1, inInstanceTransform2, inInstanceTransform3);
+    vec4 position = model * vec4(inPosition, 1.0);
+    gl_Position = camera.proj * camera.view * position;
+    // read when drawing point lists
+    gl_PointSize = 1.0;
+    fragColor = inColor * inInstanceTint;
     fragTexCoord = inTexCoord;
+    fragNormal = mat3(transpose(inverse(model))) * inNormal;
+    fragPosition = position.xyz;
 }
@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) in vec3 fragDirection;
+layout(binding = 1) uniform textureCube skyImage;
+layout(binding = 2) uniform sampler skySampler;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    outColor = vec4(texture(samplerCube(skyImage, skySampler), fragDirection).rgb, 1.0);
+}
@@ -0,0 +1,20 @@
+#version 450
+
+layout(binding = 0) uniform CameraUniform {
+    mat4 view;
+    mat4 proj;
+    vec4 position;
+} camera;
+
+layout(location = 0) in vec3 inPosition;
+
+layout(location = 0) out vec3 fragDirection;
+
+void main() {
+    // rotation only, the sky stays around the camera
+    mat4 view = mat4(mat3(camera.view));
+    vec4 position = camera.proj * view * vec4(inPosition, 1.0);
+    // depth of the far plane, behind everything
+    gl_Position = position.xyww;
+    fragDirection = inPosition;
+}
@@ -3,6 +3,8 @@ mod renderer;
 use renderer::renderer::Renderer;
 mod tools;
 mod object;
+#[cfg(test)]
+mod tests;
 
 use chrono::{DateTime, Local, Duration};
 use winit::{
@@ -14,12 +16,23 @@ use winit::{
 use anyhow::Result;
 use nalgebra_glm as glm;
 
-use crate::{renderer::{vertex::Vertex, uniformbuffers::MatrixShaderObject}, object::{Object, mesh::Mesh, rectangle::{Rectangle}, cube::Cube}};
+use crate::{renderer::{vertex::Vertex, controller::{CameraController, OrbitController, FlyController}}, object::{Object, mesh::Mesh, rectangle::{Rectangle}, cube::Cube}};
 use tools::{loader::Loader, texture::Texture, model::Model};
 
 fn main() -> Result<()> {
     pretty_env_logger::init();
 
+    // Headless: `vulkan-rs --headless out.png` renders one frame offscreen to a PNG file.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(position) = args.iter().position(|a| a == "--headless") {
+        let path = args.get(position + 1).map(String::as_str).unwrap_or("frame.png");
+        let mut texture_loader = Loader::<Texture>::default();
+        let mut model_loader = Loader::<Model>::default();
+        let mut app = Renderer::create_headless(800, 600)?;
+        fill_app(&mut app, &mut texture_loader, &mut model_loader)?;
+        return app.render_to_png(path);
+    }
+
     // Window
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
@@ -35,12 +48,25 @@ fn main() -> Result<()> {
 
     let mut destroying = false;
     let mut minimized = false;
+    let mut added_objects = Vec::new();
+    // Tab switches between orbiting around the scene and flying through it.
+    let mut controller: Box<dyn CameraController> = Box::new(OrbitController::new());
+    let mut orbiting = true;
+    let mut last_frame = std::time::Instant::now();
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Poll;
+        match &event {
+            Event::WindowEvent { event, .. } => controller.window_event(event),
+            Event::DeviceEvent { event, .. } => controller.device_event(event),
+            _ => {}
+        }
         match event {
             // Render a frame if our Vulkan app is not being destroyed.
             Event::MainEventsCleared if !destroying && !minimized => {
                 let start: DateTime<Local> = Local::now(); 
+                let now = std::time::Instant::now();
+                controller.update(app.camera_mut(), now.duration_since(last_frame).as_secs_f32());
+                last_frame = now;
                 app.render(&window).expect("Failed to render.");
                 let since = Local::now().signed_duration_since(start);
                 let wait_time = match (Duration::milliseconds(16) - since).to_std() {
@@ -58,17 +84,25 @@ fn main() -> Result<()> {
                 }
             }            
             // Handle keyboard events.
-            Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } => {
-                if input.state == ElementState::Pressed {
-                    match input.virtual_keycode {
-                        Some(VirtualKeyCode::A) => {
-                            let mut t = object::rectangle();
-                            let texture = texture_loader.load(&"resources/texture.png".to_string()).expect("error loading texture");
-                            t.set_texture(texture);
-                            app.add_object(&t).expect("unable to add object");
-                        },
-                        _ => { }
-                    }
+            Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } if input.state == ElementState::Pressed => {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Tab) => {
+                        orbiting = !orbiting;
+                        controller = if orbiting { Box::new(OrbitController::new()) } else { Box::new(FlyController::new()) };
+                    },
+                    Some(VirtualKeyCode::Insert) => {
+                        let one = Vertex::new(glm::vec3(-0.5, -0.5, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0));
+                        let mut t = Rectangle::from_one(one, 1.0, 1.0, None);
+                        let texture = texture_loader.load("resources/texture.png").expect("error loading texture");
+                        t.set_texture(texture);
+                        added_objects.push(app.add_object(&t).expect("unable to add object"));
+                    },
+                    Some(VirtualKeyCode::Delete) => {
+                        if let Some(id) = added_objects.pop() {
+                            app.remove_object(id).expect("unable to remove object");
+                        }
+                    },
+                    _ => { }
                 }
             }
             // Destroy our Vulkan app.
@@ -99,7 +133,7 @@ fn fill_app(app: &mut Renderer, t_loader: &mut Loader<Texture>, m_loader: &mut L
         //
         4, 5, 6, 6, 7, 4
     );
-    let f = |model_index: usize, elapsed: f32, width: u32, height: u32| -> MatrixShaderObject {
+    let f = |model_index: usize, elapsed: f32| -> glm::Mat4 {
         let y = (((model_index % 2) as f32) * 2.5) - 1.25;
         let z = (((model_index / 2) as f32) * -2.0) + 1.0;
 
@@ -107,36 +141,21 @@ fn fill_app(app: &mut Renderer, t_loader: &mut Loader<Texture>, m_loader: &mut L
             &glm::identity(),
             &glm::vec3(0.0, y, z),
         );    
-        let model = glm::rotate(
+        glm::rotate(
             &model,
             elapsed * glm::radians(&glm::vec1(90.0))[0],
             &glm::vec3(0.0, 0.0, 1.0),
-        );
-        let view = glm::look_at(
-            &glm::vec3(6.0f32, 0.0, 2.0),
-            &glm::vec3(0.0, 0.0, 0.0),
-            &glm::vec3(0.0, 0.0, 1.0),
-        );
-        let mut proj = glm::perspective_rh_zo(
-            width as f32 / height as f32,
-            glm::radians(&glm::vec1(45.0))[0],
-            0.1,
-            10.0,
-        );        
-        proj[(1, 1)] *= -1.0;
-
-        MatrixShaderObject::construct(view, model, proj)
-
+        )
     };
 
     const TEXTURE_VIKING: &str = "resources/viking_room.png";
     const TEXTURE_STATUE: &str = "resources/texture.png";
     const MODEL_PATH: &str ="resources/viking_room.obj";
-    let texture_statue = t_loader.load(&TEXTURE_STATUE.to_string())?;
-    let texture_viking = t_loader.load(&TEXTURE_VIKING.to_string())?;
+    let texture_statue = t_loader.load(TEXTURE_STATUE)?;
+    let texture_viking = t_loader.load(TEXTURE_VIKING)?;
     let mut triangle = Rectangle::from_one(vertices[0], 1.0, 1.0,  Some(texture_viking.clone()));
     triangle.set_fn_update_matrix(f);
-    let model = m_loader.load(&MODEL_PATH.to_string())?;
+    let model = m_loader.load(MODEL_PATH)?;
     let mut viking_room = Mesh::new(model.clone(), Some(texture_viking.clone()));
     let mut statue_room = Mesh::new(model, Some(texture_statue.clone()));
     let one = Vertex::new(glm::vec3(-0.5, -0.5, 0.0),glm::vec3(1.0, 0.0, 0.0),glm::vec2(1.0, 0.0));
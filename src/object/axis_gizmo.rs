@@ -0,0 +1,78 @@
+use {
+    std::sync::Arc,
+    crate::{
+        tools::texture::Texture,
+        renderer::{
+            vertex::Vertex,
+            material::MaterialId,
+            rasterization::Topology,
+        }
+    },
+    super::Object,
+    nalgebra_glm as glm,
+};
+
+/// The X, Y and Z axes from the origin, drawn in red, green and blue.
+pub struct AxisGizmo {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    texture: Option<Arc<Texture>>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
+}
+
+impl AxisGizmo {
+    pub fn new(length: f32) -> Self {
+        let axes = [
+            (glm::vec3(length, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0)),
+            (glm::vec3(0.0, length, 0.0), glm::vec3(0.0, 1.0, 0.0)),
+            (glm::vec3(0.0, 0.0, length), glm::vec3(0.0, 0.0, 1.0)),
+        ];
+        let vertices = axes.iter()
+            .flat_map(|(end, color)| [
+                Vertex::new(glm::Vec3::zeros(), *color, glm::vec2(0.0, 0.0)),
+                Vertex::new(*end, *color, glm::vec2(0.0, 0.0)),
+            ])
+            .collect::<Vec<_>>();
+        let indices = (0..vertices.len() as u32).collect();
+        Self { vertices, indices, texture: None, material: None, fn_update_matrix: None }
+    }
+}
+
+impl Object for AxisGizmo {
+    fn vertices(&self) -> &[Vertex] {
+        self.vertices.as_ref()
+    }
+
+    fn indices (&self) -> &[u32] {
+        self.indices.as_ref()
+    }
+
+    fn texture (&self) -> Option<Arc<Texture>> {
+        self.texture.clone()
+    }
+
+    fn set_texture(&mut self, texture: Arc<Texture>) {
+        self.texture = Some(texture);
+    }
+
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
+        self.fn_update_matrix = Some(f);
+    }
+
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
+        self.fn_update_matrix
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::LineList
+    }
+}
\ No newline at end of file
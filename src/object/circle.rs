@@ -6,7 +6,8 @@ use {
     crate::{
         tools::texture::Texture,
         renderer::{
-            vertex::Vertex, uniformbuffers::MatrixShaderObject
+            vertex::Vertex,
+            material::MaterialId,
         }
     },
     super::{ Object, add_unique_vertex},
@@ -17,7 +18,8 @@ pub struct Circle {
     vertices: Vec<Vertex>,
     indices: Vec<u32>,    
     texture: Option<Arc<Texture>>,
-    fn_update_matrix : Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
 }
 
 impl Circle {
@@ -29,7 +31,7 @@ impl Circle {
             glm::Vec2::new(x, y)
         };
         let vertex = |pos: glm::Vec3, color: glm::Vec3, tex_coord: glm::Vec2| -> Vertex {
-            Vertex::new(pos, color, tex_coord)
+            Vertex::with_normal(pos, color, tex_coord, vec3(0.0, 0.0, 1.0))
         };  
         
         let nb = edge as f32;
@@ -66,16 +68,11 @@ impl Circle {
 			add_unique_vertex(&mut unique_vertices, &mut vertices, &mut indices, v3);
 		}		
 
-		for i in 0..indices.len() {
-			indices.push(indices[i + 2]);
-			indices.push(0);
-			indices.push(indices[i]);
-		}
-
         Self {
             vertices,
             indices,
             texture,
+            material: None,
             fn_update_matrix: None,
         }
     }
@@ -98,11 +95,23 @@ impl Object for Circle {
         self.texture = Some(texture);
     }
 
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
         self.fn_update_matrix = Some(f);
     }
 
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject> {
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
         self.fn_update_matrix
     }
+
+    fn double_sided(&self) -> bool {
+        true
+    }
 }
\ No newline at end of file
@@ -3,7 +3,8 @@ use {
     crate::{
         tools::texture::Texture,
         renderer::{
-            vertex::Vertex, uniformbuffers::MatrixShaderObject
+            vertex::Vertex,
+            material::MaterialId,
         }
     },
     super::Object,
@@ -13,12 +14,13 @@ use {
 pub struct Cube {
     vertices: [Vertex; 24],
     texture: Option<Arc<Texture>>,
-    fn_update_matrix : Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
 }
 
 impl Cube {
     pub fn new(vertices: [Vertex; 24], texture: Option<Arc<Texture>>) -> Self {
-        Self { vertices, texture, fn_update_matrix: None } 
+        Self { vertices, texture, material: None, fn_update_matrix: None } 
     }
     pub fn from_one(one: Vertex, width: f32, height: f32, depth: f32, texture: Option<Arc<Texture>>) -> Self {
         let vec3 = |x: f32, y: f32, z: f32| -> glm::Vec3 {
@@ -83,12 +85,22 @@ impl Cube {
 		d2.set_tex_coord(vec2( 1.0, 1.0 ));
 		d3.set_tex_coord(vec2( 0.0, 1.0 ));
         
-        Self {
-            vertices: 
+        let mut vertices = 
             [v0, v1, v2, v3, v4, v5, v6, v7,
              l0, l1, l2, l3, r0, r1, r2, r3,
-             u0, u1, u2, u3, d0, d1, d2, d3, ],
+             u0, u1, u2, u3, d0, d1, d2, d3, ];
+
+        //one normal per face: front, back, left, right, up, down
+        let normals = [vec3(0.0, 0.0, -1.0), vec3(0.0, 0.0, 1.0), vec3(-1.0, 0.0, 0.0),
+            vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, -1.0, 0.0)];
+        for (i, vertex) in vertices.iter_mut().enumerate() {
+            vertex.set_normal(normals[i / 4]);
+        }
+
+        Self {
+            vertices,
             texture,
+            material: None,
             fn_update_matrix: None,
         }
     }  
@@ -122,11 +134,19 @@ impl Object for Cube {
         self.texture = Some(texture);
     }
 
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
         self.fn_update_matrix = Some(f);
     }
 
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject> {
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
         self.fn_update_matrix
     }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+use {
+    std::sync::Arc,
+    crate::{
+        tools::texture::Texture,
+        renderer::{
+            vertex::Vertex,
+            material::MaterialId,
+            rasterization::Topology,
+        }
+    },
+    super::Object,
+    nalgebra_glm as glm,
+};
+
+/// Square grid of lines on the XY plane, the ground of the Z up camera, centered on the origin.
+pub struct Grid {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    texture: Option<Arc<Texture>>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
+}
+
+impl Grid {
+    /// `size` is the length of a side, split in `divisions` cells.
+    pub fn new(size: f32, divisions: u32, color: glm::Vec3) -> Self {
+        let divisions = divisions.max(1);
+        let half = size / 2.0;
+        let step = size / divisions as f32;
+        let vertex = |x: f32, y: f32| Vertex::new(glm::vec3(x, y, 0.0), color, glm::vec2(0.0, 0.0));
+        let vertices = (0..=divisions)
+            .map(|i| -half + i as f32 * step)
+            .flat_map(|t| [vertex(t, -half), vertex(t, half), vertex(-half, t), vertex(half, t)])
+            .collect::<Vec<_>>();
+        let indices = (0..vertices.len() as u32).collect();
+        Self { vertices, indices, texture: None, material: None, fn_update_matrix: None }
+    }
+}
+
+impl Object for Grid {
+    fn vertices(&self) -> &[Vertex] {
+        self.vertices.as_ref()
+    }
+
+    fn indices (&self) -> &[u32] {
+        self.indices.as_ref()
+    }
+
+    fn texture (&self) -> Option<Arc<Texture>> {
+        self.texture.clone()
+    }
+
+    fn set_texture(&mut self, texture: Arc<Texture>) {
+        self.texture = Some(texture);
+    }
+
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
+        self.fn_update_matrix = Some(f);
+    }
+
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
+        self.fn_update_matrix
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::LineList
+    }
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+use {
+    std::{sync::Arc, ops::Range},
+    crate::{
+        tools::{texture::Texture, model::Model},
+        renderer::{vertex::{Vertex, InstanceData}, material::MaterialId, rasterization::Topology},
+    },
+    super::Object,
+    nalgebra_glm as glm,
+};
+
+/// Object drawn once per instance with a single draw call, every instance has its own
+/// transform and tint. The model matrix of the wrapped object is applied after the instance transform.
+pub struct Instanced<O: Object> {
+    object: O,
+    instances: Vec<InstanceData>,
+}
+
+impl<O: Object> Instanced<O> {
+    pub fn new(object: O, instances: Vec<InstanceData>) -> Self {
+        Self { object, instances }
+    }
+    /// Untinted instances at the given transforms.
+    pub fn from_transforms(object: O, transforms: &[glm::Mat4]) -> Self {
+        Self::new(object, transforms.iter().map(|t| InstanceData::from_transform(*t)).collect())
+    }
+
+    pub fn object(&self) -> &O { &self.object }
+    pub fn object_mut(&mut self) -> &mut O { &mut self.object }
+    pub fn instances_mut(&mut self) -> &mut Vec<InstanceData> { &mut self.instances }
+    pub fn push_instance(&mut self, instance: InstanceData) { self.instances.push(instance); }
+}
+
+impl<O: Object> Object for Instanced<O> {
+    fn vertices(&self) -> &[Vertex] {
+        self.object.vertices()
+    }
+
+    fn indices (&self) -> &[u32] {
+        self.object.indices()
+    }
+
+    fn texture (&self) -> Option<Arc<Texture>> {
+        self.object.texture()
+    }
+
+    fn set_texture(&mut self, texture: Arc<Texture>) {
+        self.object.set_texture(texture);
+    }
+
+    fn material(&self) -> Option<MaterialId> {
+        self.object.material()
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.object.set_material(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
+        self.object.set_fn_update_matrix(f);
+    }
+
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
+        self.object.get_fn_update_matrix()
+    }
+
+    fn shared_model(&self) -> Option<(Arc<Model>, Range<usize>)> {
+        self.object.shared_model()
+    }
+
+    fn instances(&self) -> Option<&[InstanceData]> {
+        Some(&self.instances)
+    }
+
+    fn double_sided(&self) -> bool {
+        self.object.double_sided()
+    }
+
+    fn topology(&self) -> Topology {
+        self.object.topology()
+    }
+}
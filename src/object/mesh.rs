@@ -1,26 +1,56 @@
 use {
-    std::{sync::Arc},
+    std::{sync::Arc, ops::Range},
+    anyhow::Result,
     crate::{
-        renderer::{vertex::Vertex, uniformbuffers::MatrixShaderObject},
-        tools::{texture::Texture, model::Model},        
+        renderer::{vertex::Vertex, material::MaterialId},
+        tools::{texture::Texture, model::Model, loader::Loader},        
     },
-    super::Object,
+    super::{Object, generate_normals},
+    nalgebra_glm as glm,
 };
 
 pub struct Mesh {
     model: Arc<Model>,
+    submesh: Option<usize>,
     texture: Option<Arc<Texture>>,
-    fn_update_matrix : Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
 }
 
 impl Mesh {
     pub fn new(model: Arc<Model>, texture: Option<Arc<Texture>>) -> Self {
-        Self { model, texture, fn_update_matrix: None }
+        Self { model, submesh: None, texture, material: None, fn_update_matrix: None }
     }
-    pub fn construct(vertices: Vec<Vertex>, indices: Vec<u32>, texture :Option<Arc<Texture>>) -> Self {
+    /// Mesh drawing only one sub-mesh of the model.
+    pub fn from_submesh(model: Arc<Model>, submesh: usize, texture: Option<Arc<Texture>>) -> Self {
+        Self { model, submesh: Some(submesh), texture, material: None, fn_update_matrix: None }
+    }
+    /// One mesh per sub-mesh of the model, textured with the diffuse texture of its material,
+    /// external texture files are loaded through `texture_loader`.
+    pub fn from_materials(model: Arc<Model>, texture_loader: &mut Loader<Texture>) -> Result<Vec<Self>> {
+        (0..model.submeshes().len())
+            .map(|submesh| {
+                let material = model.submesh_material(submesh);
+                let texture = match (material.and_then(|m| m.diffuse_image()), material.and_then(|m| m.diffuse_texture())) {
+                    (Some(image), _) => Some(image),
+                    (None, Some(path)) => Some(texture_loader.load(path)?),
+                    (None, None) => None,
+                };
+                Ok(Self::from_submesh(model.clone(), submesh, texture))
+            })
+            .collect()
+    }
+    /// Normals are generated when none of the vertices has one.
+    pub fn construct(mut vertices: Vec<Vertex>, indices: Vec<u32>, texture :Option<Arc<Texture>>) -> Self {
+        if vertices.iter().all(|v| v.normal() == glm::Vec3::zeros()) {
+            generate_normals(&mut vertices, &indices);
+        }
         let model = Arc::new(Model::construct(vertices, indices));
-        Self { model, texture, fn_update_matrix: None }
+        Self { model, submesh: None, texture, material: None, fn_update_matrix: None }
     }
+
+    pub fn model(&self) -> Arc<Model> { self.model.clone() }
+    pub fn submesh(&self) -> Option<usize> { self.submesh }
 }
 
 impl Object for Mesh {
@@ -29,7 +59,10 @@ impl Object for Mesh {
     }
 
     fn indices (&self) -> &[u32] {
-        self.model.indices()
+        match self.submesh {
+            Some(submesh) => self.model.submesh_indices(submesh),
+            None => self.model.indices(),
+        }
     }
 
     fn texture (&self) -> Option<Arc<Texture>> {
@@ -40,13 +73,29 @@ impl Object for Mesh {
         self.texture = Some(texture);
     }
 
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
         self.fn_update_matrix = Some(f);
     }
 
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject> {
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
         self.fn_update_matrix
     }
+
+    fn shared_model(&self) -> Option<(Arc<Model>, Range<usize>)> {
+        let range = match self.submesh {
+            Some(submesh) => self.model.submesh_range(submesh),
+            None => 0..self.model.indices().len(),
+        };
+        Some((self.model.clone(), range))
+    }
 }
 
 
@@ -4,11 +4,17 @@ pub mod rectangle;
 pub mod cube;
 pub mod circle;
 pub mod sphere;
-use std::{sync::Arc, collections::HashMap};
+pub mod instanced;
+pub mod polyline;
+pub mod axis_gizmo;
+pub mod grid;
+pub mod point_cloud;
+use std::{sync::Arc, collections::HashMap, ops::Range};
+use nalgebra_glm as glm;
 
 use crate::{
-    renderer::{vertex::Vertex, uniformbuffers::MatrixShaderObject},
-    tools::texture::Texture
+    renderer::{vertex::{Vertex, InstanceData}, material::MaterialId, rasterization::Topology},
+    tools::{texture::Texture, model::Model},
 };
 
 pub trait Object {
@@ -16,18 +22,57 @@ pub trait Object {
     fn indices (&self) -> &[u32];
     fn texture (&self) -> Option<Arc<Texture>>;
     fn set_texture(&mut self, texture: Arc<Texture>);
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject);
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>;
+    /// Material the object is drawn with, `None` picks the textured or untextured built-in one,
+    /// double sided for flat objects.
+    fn material(&self) -> Option<MaterialId>;
+    fn set_material(&mut self, material: MaterialId);
+    /// Sets the function computing the model matrix from the model index and the elapsed time in seconds.
+    /// The model index is the order in which the object was added to the renderer and never changes.
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4);
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4>;
+    /// Model holding the geometry and the range of its indices drawn by the object.
+    /// Objects built from the same model share one GPU vertex/index buffer pair,
+    /// the others upload their own `vertices` and `indices`.
+    fn shared_model(&self) -> Option<(Arc<Model>, Range<usize>)> { None }
+    /// Copies of the object drawn in a single instanced draw, `None` draws it once.
+    fn instances(&self) -> Option<&[InstanceData]> { None }
+    /// Flat objects are seen from both sides, their back faces are not culled.
+    fn double_sided(&self) -> bool { false }
+    /// Primitives drawn from the indices, strips restart at the index `u32::MAX`.
+    fn topology(&self) -> Topology { Topology::TriangleList }
 }
 
 pub(crate) fn add_unique_vertex(hashmap: &mut HashMap<Vertex, u32>, 
     vertices: &mut Vec<Vertex>,
     indices : &mut Vec<u32>,
     vertex :Vertex) {
-        if !hashmap.contains_key(&vertex) {
-            hashmap.insert(vertex, vertices.len() as u32);
-			vertices.push(vertex);
-		}
+        let index = *hashmap.entry(vertex).or_insert_with(|| {
+            vertices.push(vertex);
+            vertices.len() as u32 - 1
+        });
 
-		indices.push(hashmap[&vertex]);
-    }
\ No newline at end of file
+		indices.push(index);
+    }
+
+/// Smooth normals averaged from the faces sharing each vertex, weighted by their area.
+/// Vertices that belong to no triangle keep their normal, null ones are drawn unlit.
+pub fn generate_normals(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut normals = vec![None; vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let (a, b, c) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+        let face = glm::cross(
+            &(vertices[b].pos() - vertices[a].pos()),
+            &(vertices[c].pos() - vertices[a].pos()),
+        );
+        for i in [a, b, c] {
+            *normals[i].get_or_insert_with(glm::Vec3::zeros) += face;
+        }
+    }
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        match normal {
+            Some(normal) if glm::length(&normal) > f32::EPSILON => vertex.set_normal(glm::normalize(&normal)),
+            Some(_) => vertex.set_normal(glm::Vec3::zeros()),
+            None => (),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use {
+    std::sync::Arc,
+    crate::{
+        tools::texture::Texture,
+        renderer::{
+            vertex::Vertex,
+            material::MaterialId,
+            rasterization::Topology,
+        }
+    },
+    super::Object,
+    nalgebra_glm as glm,
+};
+
+/// Points drawn one pixel wide, lit only if their vertices have a normal.
+pub struct PointCloud {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    texture: Option<Arc<Texture>>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
+}
+
+impl PointCloud {
+    pub fn new(vertices: Vec<Vertex>) -> Self {
+        let indices = (0..vertices.len() as u32).collect();
+        Self { vertices, indices, texture: None, material: None, fn_update_matrix: None }
+    }
+
+    /// Unlit points of a single color.
+    pub fn from_points(points: &[glm::Vec3], color: glm::Vec3) -> Self {
+        Self::new(points.iter().map(|p| Vertex::new(*p, color, glm::vec2(0.0, 0.0))).collect())
+    }
+}
+
+impl Object for PointCloud {
+    fn vertices(&self) -> &[Vertex] {
+        self.vertices.as_ref()
+    }
+
+    fn indices (&self) -> &[u32] {
+        self.indices.as_ref()
+    }
+
+    fn texture (&self) -> Option<Arc<Texture>> {
+        self.texture.clone()
+    }
+
+    fn set_texture(&mut self, texture: Arc<Texture>) {
+        self.texture = Some(texture);
+    }
+
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
+        self.fn_update_matrix = Some(f);
+    }
+
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
+        self.fn_update_matrix
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::PointList
+    }
+}
\ No newline at end of file
@@ -3,7 +3,8 @@ use {
     crate::{
         tools::texture::Texture,
         renderer::{
-            vertex::Vertex, uniformbuffers::MatrixShaderObject
+            vertex::Vertex,
+            material::MaterialId,
         }
     },
     super::Object,
@@ -11,17 +12,30 @@ use {
 };
 
 pub struct Rectangle {
-    vertices: [Vertex; 4],
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
     texture: Option<Arc<Texture>>,
-    fn_update_matrix : Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
 }
 
 impl Rectangle {
+    /// Vertices without normal get the one of the face, the back face is lit with the opposite one.
+    /// Drawn double sided unless given a material.
     pub fn new(vertices: [Vertex; 4], texture: Option<Arc<Texture>>) -> Self {
-        Self { vertices, texture, fn_update_matrix: None } 
+        let mut vertices = vertices.to_vec();
+        let face = glm::cross(&(vertices[1].pos() - vertices[0].pos()), &(vertices[2].pos() - vertices[0].pos()));
+        if glm::length(&face) > f32::EPSILON {
+            vertices.iter_mut()
+                .filter(|v| v.normal() == glm::Vec3::zeros())
+                .for_each(|v| v.set_normal(glm::normalize(&face)));
+        }
+        let indices = vec![0, 1, 2, 2, 1, 3];
+        Self { vertices, indices, texture, material: None, fn_update_matrix: None }
     }
     pub fn from_one(mut one: Vertex, width: f32, height: f32, texture: Option<Arc<Texture>>) -> Self {
         one.set_tex_coord(glm::Vec2::new(0.0, 0.0));
+        one.set_normal(glm::Vec3::zeros());
         let two = Vertex::new(
             one.pos() + glm::Vec3::new(width, 0.0, 0.0),
             one.color(),
@@ -37,11 +51,7 @@ impl Rectangle {
             one.color(),
             glm::Vec2::new(1.0, 1.0),
         );
-        Self {
-            vertices: [one, two, three, four],
-            texture,
-            fn_update_matrix: None,
-        }
+        Self::new([one, two, three, four], texture)
     }  
 }
 
@@ -51,8 +61,7 @@ impl Object for Rectangle {
     }
 
     fn indices (&self) -> &[u32] {
-        &[0, 1, 2, 2, 1, 3, 	//one face 
-          2, 1, 0, 3, 1, 2 ]    //behind
+        self.indices.as_ref()
     }
 
     fn texture (&self) -> Option<Arc<Texture>> {
@@ -63,11 +72,23 @@ impl Object for Rectangle {
         self.texture = Some(texture);
     }
 
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
         self.fn_update_matrix = Some(f);
     }
 
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject> {
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
         self.fn_update_matrix
     }
+
+    fn double_sided(&self) -> bool {
+        true
+    }
 }
\ No newline at end of file
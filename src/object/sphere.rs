@@ -6,7 +6,8 @@ use {
     crate::{
         tools::texture::Texture,
         renderer::{
-            vertex::Vertex, uniformbuffers::MatrixShaderObject
+            vertex::Vertex,
+            material::MaterialId,
         }
     },
     super::{ Object, add_unique_vertex},
@@ -17,7 +18,8 @@ pub struct Sphere {
     vertices: Vec<Vertex>,
     indices: Vec<u32>,    
     texture: Option<Arc<Texture>>,
-    fn_update_matrix : Option<fn(usize, f32, u32, u32) -> MatrixShaderObject>,
+    material: Option<MaterialId>,
+    fn_update_matrix : Option<fn(usize, f32) -> glm::Mat4>,
 }
 
 impl Sphere {
@@ -26,7 +28,7 @@ impl Sphere {
             glm::Vec2::new(x, y)
         };
         let vertex = |pos: glm::Vec3, color: glm::Vec3, tex_coord: glm::Vec2| -> Vertex {
-            Vertex::new(pos, color, tex_coord)
+            Vertex::with_normal(pos, color, tex_coord, glm::normalize(&pos))
         };  
         let spherical = |norm: f32, theta: f32, phi: f32| -> glm::Vec3 {
             glm::Vec3::new(
@@ -84,7 +86,7 @@ impl Sphere {
         }
 
         Self {
-            vertices, indices, texture, fn_update_matrix: None,
+            vertices, indices, texture, material: None, fn_update_matrix: None,
         }
     }
 }
@@ -106,11 +108,19 @@ impl Object for Sphere {
         self.texture = Some(texture);
     }
 
-    fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
+    fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    fn set_material(&mut self, material: MaterialId) {
+        self.material = Some(material);
+    }
+
+    fn set_fn_update_matrix(&mut self, f: fn(usize, f32) -> glm::Mat4) {
         self.fn_update_matrix = Some(f);
     }
 
-    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32, u32, u32) -> MatrixShaderObject> {
+    fn get_fn_update_matrix(&self) -> Option<fn(usize, f32) -> glm::Mat4> {
         self.fn_update_matrix
     }
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+use {
+    std::{collections::HashMap, ops::Range, ptr, sync::{Arc, Mutex, MutexGuard}},
+    vulkanalia::prelude::v1_0::*,
+    anyhow::{Result, anyhow},
+    log::*,
+};
+
+/// Size of the device memory blocks resources are sub-allocated from, smaller on small heaps.
+pub const DEFAULT_BLOCK_SIZE: vk::DeviceSize = 64 * 1024 * 1024;
+
+/// Tiling of the resources bound to an allocation. Linear and optimal resources never share
+/// a block, so neighbours never have to be separated by `bufferImageGranularity`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ResourceTiling {
+    /// Buffers and linear images.
+    Linear,
+    /// Optimal images.
+    Optimal,
+}
+
+//================================================
+// Allocation
+//================================================
+
+/// Range of a device memory block bound to one buffer or image.
+#[derive(Copy, Clone, Debug)]
+pub struct Allocation {
+    memory: vk::DeviceMemory,
+    offset: vk::DeviceSize,
+    size: vk::DeviceSize,
+    memory_type: u32,
+    tiling: ResourceTiling,
+    mapped: *mut u8,
+}
+
+// SAFETY: `mapped` points in the persistent mapping of the block, which lives as long as the
+// allocation. Writing through it is synchronized like any host access to device memory.
+unsafe impl Send for Allocation {}
+unsafe impl Sync for Allocation {}
+
+impl Allocation {
+    pub fn memory(&self) -> vk::DeviceMemory { self.memory }
+    pub fn offset(&self) -> vk::DeviceSize { self.offset }
+    pub fn size(&self) -> vk::DeviceSize { self.size }
+    /// Host address of the allocation, host visible blocks stay mapped for their whole life.
+    pub fn mapped(&self) -> Result<*mut u8> {
+        if self.mapped.is_null() {
+            Err(anyhow!("Allocation is not host visible."))
+        } else {
+            Ok(self.mapped)
+        }
+    }
+}
+
+impl Default for Allocation {
+    fn default() -> Self {
+        Self {
+            memory: vk::DeviceMemory::default(),
+            offset: 0,
+            size: 0,
+            memory_type: 0,
+            tiling: ResourceTiling::Linear,
+            mapped: ptr::null_mut(),
+        }
+    }
+}
+
+//================================================
+// Free list
+//================================================
+
+/// Free ranges of a memory block, sorted by offset. Released ranges are merged with their
+/// free neighbours so the space they leave can be reused by larger allocations.
+#[derive(Clone, Debug)]
+pub struct FreeList {
+    size: vk::DeviceSize,
+    free: Vec<Range<vk::DeviceSize>>,
+}
+
+impl FreeList {
+    pub fn new(size: vk::DeviceSize) -> Self {
+        Self { size, free: std::iter::once(0..size).collect() }
+    }
+
+    /// Offset of `size` free bytes aligned to `alignment`, taken from the first range they fit in.
+    pub fn allocate(&mut self, size: vk::DeviceSize, alignment: vk::DeviceSize) -> Option<vk::DeviceSize> {
+        let alignment = alignment.max(1);
+        let (index, offset) = self.free.iter().enumerate()
+            .map(|(i, range)| (i, range.start.div_ceil(alignment) * alignment))
+            .find(|(i, offset)| offset + size <= self.free[*i].end)?;
+
+        // the padding before the allocation and the space after it stay free
+        let range = self.free[index].clone();
+        let remaining = [range.start..offset, offset + size..range.end];
+        self.free.splice(index..index + 1, remaining.into_iter().filter(|r| !r.is_empty()));
+        Some(offset)
+    }
+
+    /// Gives back a range returned by `allocate`.
+    pub fn free(&mut self, offset: vk::DeviceSize, size: vk::DeviceSize) {
+        let mut index = self.free.partition_point(|range| range.start < offset);
+        self.free.insert(index, offset..offset + size);
+        if index + 1 < self.free.len() && self.free[index].end == self.free[index + 1].start {
+            self.free[index].end = self.free.remove(index + 1).end;
+        }
+        if index > 0 && self.free[index - 1].end == self.free[index].start {
+            self.free[index - 1].end = self.free.remove(index).end;
+            index -= 1;
+        }
+        debug_assert!(self.free[index].end <= self.size);
+    }
+
+    pub fn size(&self) -> vk::DeviceSize { self.size }
+    pub fn free_bytes(&self) -> vk::DeviceSize { self.free.iter().map(|r| r.end - r.start).sum() }
+    pub fn used_bytes(&self) -> vk::DeviceSize { self.size - self.free_bytes() }
+    pub fn free_ranges(&self) -> &[Range<vk::DeviceSize>] { &self.free }
+    pub fn is_empty(&self) -> bool { self.free_bytes() == self.size }
+}
+
+//================================================
+// Statistics
+//================================================
+
+/// Device memory reserved by the allocator and how much of it is bound to resources.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct MemoryStats {
+    block_count: usize,
+    allocation_count: usize,
+    reserved_bytes: vk::DeviceSize,
+    used_bytes: vk::DeviceSize,
+    free_range_count: usize,
+}
+
+impl MemoryStats {
+    /// Number of `vkAllocateMemory` allocations alive.
+    pub fn block_count(&self) -> usize { self.block_count }
+    /// Number of buffers and images sub-allocated.
+    pub fn allocation_count(&self) -> usize { self.allocation_count }
+    pub fn reserved_bytes(&self) -> vk::DeviceSize { self.reserved_bytes }
+    pub fn used_bytes(&self) -> vk::DeviceSize { self.used_bytes }
+    /// Number of holes in the blocks, a high count for few free bytes means fragmented memory.
+    pub fn free_range_count(&self) -> usize { self.free_range_count }
+}
+
+//================================================
+// Allocator
+//================================================
+
+#[derive(Debug)]
+struct MemoryBlock {
+    memory: vk::DeviceMemory,
+    mapped: *mut u8,
+    free_list: FreeList,
+    allocation_count: usize,
+    // allocated for a single large resource, freed with it
+    dedicated: bool,
+}
+
+// SAFETY: the mapping is only read and written by the owners of the allocations in the block.
+unsafe impl Send for MemoryBlock {}
+
+/// Allocates large device memory blocks per memory type and sub-allocates buffers and images
+/// from them, so the number of `vkAllocateMemory` calls stays far below `maxMemoryAllocationCount`.
+///
+/// Resources too large for a block get a dedicated one. At most one empty block is kept per
+/// memory type, ready for the next resources.
+#[derive(Debug)]
+pub struct MemoryAllocator {
+    device: Arc<Device>,
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    max_allocation_count: u32,
+    block_size: vk::DeviceSize,
+    pools: Mutex<HashMap<(u32, ResourceTiling), Vec<MemoryBlock>>>,
+}
+
+impl MemoryAllocator {
+    pub fn new(device: Arc<Device>, instance: &Instance, physical_device: vk::PhysicalDevice) -> Self {
+        Self::with_block_size(device, instance, physical_device, DEFAULT_BLOCK_SIZE)
+    }
+
+    pub fn with_block_size(device: Arc<Device>, instance: &Instance,
+        physical_device: vk::PhysicalDevice, block_size: vk::DeviceSize) -> Self
+    {
+        unsafe {
+            let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+            let limits = instance.get_physical_device_properties(physical_device).limits;
+            Self {
+                device,
+                memory_properties,
+                max_allocation_count: limits.max_memory_allocation_count,
+                block_size,
+                pools: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    /// Finds room for a resource in a memory type with `properties`, allocating a new block if needed.
+    pub fn allocate(&self, requirements: vk::MemoryRequirements,
+        properties: vk::MemoryPropertyFlags, tiling: ResourceTiling) -> Result<Allocation>
+    {
+        let memory_type = self.memory_type_index(properties, requirements)?;
+        let block_size = self.block_size(memory_type);
+        let mut pools = self.pools();
+        let blocks = pools.entry((memory_type, tiling)).or_default();
+
+        let found = blocks.iter_mut()
+            .enumerate()
+            .filter(|(_, block)| !block.dedicated)
+            .find_map(|(i, block)| block.free_list.allocate(requirements.size, requirements.alignment).map(|offset| (i, offset)));
+        let (index, offset) = match found {
+            Some(found) => found,
+            None => {
+                let dedicated = requirements.size > block_size / 2;
+                let size = if dedicated { requirements.size } else { block_size };
+                let mut block = self.allocate_block(memory_type, size, dedicated, self.count_blocks(&pools))?;
+                let offset = block.free_list.allocate(requirements.size, requirements.alignment)
+                    .ok_or_else(|| anyhow!("New memory block of {} bytes is too small.", size))?;
+                let blocks = pools.entry((memory_type, tiling)).or_default();
+                blocks.push(block);
+                (blocks.len() - 1, offset)
+            },
+        };
+
+        let block = &mut pools.get_mut(&(memory_type, tiling)).unwrap()[index];
+        block.allocation_count += 1;
+        let mapped = if block.mapped.is_null() { ptr::null_mut() } else { unsafe { block.mapped.add(offset as usize) } };
+        Ok(Allocation {
+            memory: block.memory,
+            offset,
+            size: requirements.size,
+            memory_type,
+            tiling,
+            mapped,
+        })
+    }
+
+    /// Gives the range of `allocation` back to its block. Empty dedicated blocks are freed at
+    /// once, the other ones only when their memory type already has an empty block.
+    pub fn free(&self, allocation: &Allocation) {
+        let mut pools = self.pools();
+        let blocks = match pools.get_mut(&(allocation.memory_type, allocation.tiling)) {
+            Some(blocks) => blocks,
+            None => return warn!("Freeing memory unknown to the allocator."),
+        };
+        let index = match blocks.iter().position(|block| block.memory == allocation.memory) {
+            Some(index) => index,
+            None => return warn!("Freeing memory unknown to the allocator."),
+        };
+
+        let block = &mut blocks[index];
+        block.free_list.free(allocation.offset, allocation.size);
+        block.allocation_count -= 1;
+        if block.allocation_count > 0 {
+            return;
+        }
+        let other_empty = blocks.iter().enumerate()
+            .any(|(i, other)| i != index && !other.dedicated && other.allocation_count == 0);
+        if blocks[index].dedicated || other_empty {
+            let block = blocks.remove(index);
+            unsafe { self.device.free_memory(block.memory, None); }
+        }
+    }
+
+    pub fn stats(&self) -> MemoryStats {
+        self.pools().values()
+            .flatten()
+            .fold(MemoryStats::default(), |stats, block| MemoryStats {
+                block_count: stats.block_count + 1,
+                allocation_count: stats.allocation_count + block.allocation_count,
+                reserved_bytes: stats.reserved_bytes + block.free_list.size(),
+                used_bytes: stats.used_bytes + block.free_list.used_bytes(),
+                free_range_count: stats.free_range_count + block.free_list.free_ranges().len(),
+            })
+    }
+
+    /// Frees every block, the resources bound to them must have been destroyed.
+    pub fn clean(&self) {
+        let mut pools = self.pools();
+        for block in pools.drain().flat_map(|(_, blocks)| blocks) {
+            if block.allocation_count > 0 {
+                warn!("Freeing a memory block still holding {} allocations.", block.allocation_count);
+            }
+            unsafe { self.device.free_memory(block.memory, None); }
+        }
+    }
+
+    fn allocate_block(&self, memory_type: u32, size: vk::DeviceSize, dedicated: bool, block_count: usize) -> Result<MemoryBlock> {
+        if block_count as u32 >= self.max_allocation_count {
+            return Err(anyhow!("Device memory allocation count limit ({}) reached.", self.max_allocation_count));
+        }
+        unsafe {
+            let info = vk::MemoryAllocateInfo::builder()
+                .allocation_size(size)
+                .memory_type_index(memory_type);
+            let memory = self.device.allocate_memory(&info, None)?;
+
+            let flags = self.memory_properties.memory_types[memory_type as usize].property_flags;
+            let mapped = if flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+                self.device.map_memory(memory, 0, vk::WHOLE_SIZE as u64, vk::MemoryMapFlags::empty())?.cast()
+            } else {
+                ptr::null_mut()
+            };
+            Ok(MemoryBlock { memory, mapped, free_list: FreeList::new(size), allocation_count: 0, dedicated })
+        }
+    }
+
+    /// Locks the blocks, still usable if another thread panicked while holding them.
+    fn pools(&self) -> MutexGuard<'_, HashMap<(u32, ResourceTiling), Vec<MemoryBlock>>> {
+        self.pools.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn count_blocks(&self, pools: &HashMap<(u32, ResourceTiling), Vec<MemoryBlock>>) -> usize {
+        pools.values().map(|blocks| blocks.len()).sum()
+    }
+
+    /// Block size of a memory type, an eighth of its heap at most.
+    fn block_size(&self, memory_type: u32) -> vk::DeviceSize {
+        let heap = self.memory_properties.memory_types[memory_type as usize].heap_index;
+        let heap_size = self.memory_properties.memory_heaps[heap as usize].size;
+        self.block_size.min(heap_size / 8).max(1)
+    }
+
+    fn memory_type_index(&self, properties: vk::MemoryPropertyFlags, requirements: vk::MemoryRequirements) -> Result<u32> {
+        (0..self.memory_properties.memory_type_count)
+            .find(|i| {
+                let suitable = (requirements.memory_type_bits & (1 << i)) != 0;
+                let memory_type = self.memory_properties.memory_types[*i as usize];
+                suitable && memory_type.property_flags.contains(properties)
+            })
+            .ok_or_else(|| anyhow!("Failed to find suitable memory type."))
+    }
+}
+
+impl Drop for MemoryAllocator {
+    fn drop(&mut self) {
+        self.clean();
+    }
+}
@@ -0,0 +1,53 @@
+use {
+    std::{collections::HashMap, sync::{Arc, Weak}},
+    vulkanalia::prelude::v1_0::*,
+    anyhow::Result,
+    crate::tools::model::Model,
+    super::{vertexbuffers::VertexBuffer, allocator::MemoryAllocator},
+};
+
+/// Vertex and index buffers shared by every object built from the same `Arc<Model>`.
+///
+/// The whole model is uploaded once, each object draws its own range of indices.
+/// As for the textures, the objects own the buffers and the cache keeps the model alive for its key.
+#[derive(Clone, Default)]
+pub struct BufferCache {
+    buffers: HashMap<usize, (Arc<Model>, Weak<VertexBuffer>)>,
+}
+
+impl BufferCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the GPU buffers of `model`, uploading them on first use.
+    pub fn get(&mut self, device: Arc<Device>, allocator: Arc<MemoryAllocator>,
+        command_pool: vk::CommandPool,
+        graphics_queue: vk::Queue, model: Arc<Model>) -> Result<Arc<VertexBuffer>>
+    {
+        self.release_unused();
+        let key = Arc::as_ptr(&model) as usize;
+        if let Some(buffer) = self.buffers.get(&key).and_then(|(_, weak)| weak.upgrade()) {
+            return Ok(buffer);
+        }
+
+        let buffer = Arc::new(VertexBuffer::new(device, allocator, command_pool, graphics_queue,
+            model.vertices(), model.indices())?);
+        self.buffers.insert(key, (model, Arc::downgrade(&buffer)));
+        Ok(buffer)
+    }
+
+    /// Forgets the buffers whose last user went away.
+    pub fn release_unused(&mut self) {
+        self.buffers.retain(|_, (_, weak)| weak.strong_count() > 0);
+    }
+
+    /// Number of models currently uploaded.
+    pub fn len(&self) -> usize {
+        self.buffers.values().filter(|(_, weak)| weak.strong_count() > 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,20 +1,21 @@
 use vulkanalia::{
     prelude::v1_0::*
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
+use super::allocator::{MemoryAllocator, Allocation, ResourceTiling};
 
-/**
- * Shared tools to manipulate buffers.
- */
+//================================================
+// Shared tools to manipulate buffers.
+//================================================
 
+/// Creates a buffer bound to memory sub-allocated by `allocator`.
 pub unsafe fn create_buffer(
-    instance: &Instance,
     device: &Device,
-    physical_device: vk::PhysicalDevice,
+    allocator: &MemoryAllocator,
     size: vk::DeviceSize,
     usage: vk::BufferUsageFlags,
     properties: vk::MemoryPropertyFlags,
-) -> Result<(vk::Buffer, vk::DeviceMemory)> {
+) -> Result<(vk::Buffer, Allocation)> {
 
     // Buffer
     let buffer_info = vk::BufferCreateInfo::builder()
@@ -27,15 +28,22 @@ pub unsafe fn create_buffer(
     // Memory
     let requirements = device.get_buffer_memory_requirements(buffer);
 
-    let memory_info = vk::MemoryAllocateInfo::builder()
-        .allocation_size(requirements.size)
-        .memory_type_index(get_memory_type_index(instance, physical_device, properties, requirements)?);
+    let allocation = match allocator.allocate(requirements, properties, ResourceTiling::Linear) {
+        Ok(allocation) => allocation,
+        Err(e) => {
+            device.destroy_buffer(buffer, None);
+            return Err(e);
+        },
+    };
 
-    let buffer_memory = device.allocate_memory(&memory_info, None)?;
+    device.bind_buffer_memory(buffer, allocation.memory(), allocation.offset())?;
 
-    device.bind_buffer_memory(buffer, buffer_memory, 0)?;
+    Ok((buffer, allocation))
+}
 
-    Ok((buffer, buffer_memory))
+pub unsafe fn destroy_buffer(device: &Device, allocator: &MemoryAllocator, buffer: vk::Buffer, allocation: &Allocation) {
+    device.destroy_buffer(buffer, None);
+    allocator.free(allocation);
 }
 
 pub unsafe fn copy_buffer(
@@ -56,22 +64,6 @@ pub unsafe fn copy_buffer(
     Ok(())
 }
 
-pub unsafe fn get_memory_type_index(
-    instance: &Instance,
-    physical_device: vk::PhysicalDevice,
-    properties: vk::MemoryPropertyFlags,
-    requirements: vk::MemoryRequirements,
-) -> Result<u32> {
-    let memory = instance.get_physical_device_memory_properties(physical_device);
-    (0..memory.memory_type_count)
-        .find(|i| {
-            let suitable = (requirements.memory_type_bits & (1 << i)) != 0;
-            let memory_type = memory.memory_types[*i as usize];
-            suitable && memory_type.property_flags.contains(properties)
-        })
-        .ok_or_else(|| anyhow!("Failed to find suitable memory type."))
-}
-
 pub unsafe fn begin_single_time_commands(
     device: &Device,
     command_pool: vk::CommandPool,
@@ -0,0 +1,79 @@
+use nalgebra_glm as glm;
+
+//================================================
+// Camera
+//================================================
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Vertical field of view in radians.
+    Perspective { fov: f32 },
+    /// Visible height in world units, the width follows the aspect ratio.
+    Orthographic { height: f32 },
+}
+
+/// Point of view of the renderer, shared by every object.
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    position: glm::Vec3,
+    target: glm::Vec3,
+    up: glm::Vec3,
+    projection: Projection,
+    near: f32,
+    far: f32,
+}
+
+impl Camera {
+    pub fn perspective(position: glm::Vec3, target: glm::Vec3, up: glm::Vec3, fov: f32, near: f32, far: f32) -> Self {
+        Self { position, target, up, projection: Projection::Perspective { fov }, near, far }
+    }
+
+    pub fn orthographic(position: glm::Vec3, target: glm::Vec3, up: glm::Vec3, height: f32, near: f32, far: f32) -> Self {
+        Self { position, target, up, projection: Projection::Orthographic { height }, near, far }
+    }
+
+    pub fn view(&self) -> glm::Mat4 {
+        glm::look_at(&self.position, &self.target, &self.up)
+    }
+
+    /// Projection matrix for a target of `width` x `height`, with the Y axis flipped for Vulkan.
+    pub fn proj(&self, width: u32, height: u32) -> glm::Mat4 {
+        let aspect = width as f32 / height.max(1) as f32;
+        let mut proj = match self.projection {
+            Projection::Perspective { fov } => glm::perspective_rh_zo(aspect, fov, self.near, self.far),
+            Projection::Orthographic { height } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect;
+                glm::ortho_rh_zo(-half_width, half_width, -half_height, half_height, self.near, self.far)
+            },
+        };
+        proj[(1, 1)] *= -1.0;
+        proj
+    }
+
+    pub fn position(&self) -> glm::Vec3 { self.position }
+    pub fn target(&self) -> glm::Vec3 { self.target }
+    pub fn up(&self) -> glm::Vec3 { self.up }
+    pub fn projection(&self) -> Projection { self.projection }
+    pub fn near(&self) -> f32 { self.near }
+    pub fn far(&self) -> f32 { self.far }
+    pub fn set_position(&mut self, position: glm::Vec3) { self.position = position; }
+    pub fn set_target(&mut self, target: glm::Vec3) { self.target = target; }
+    pub fn set_up(&mut self, up: glm::Vec3) { self.up = up; }
+    pub fn set_projection(&mut self, projection: Projection) { self.projection = projection; }
+    pub fn set_near(&mut self, near: f32) { self.near = near; }
+    pub fn set_far(&mut self, far: f32) { self.far = far; }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::perspective(
+            glm::vec3(6.0, 0.0, 2.0),
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(0.0, 0.0, 1.0),
+            glm::radians(&glm::vec1(45.0))[0],
+            0.1,
+            10.0,
+        )
+    }
+}
@@ -1,11 +1,19 @@
 use vulkanalia::{
     prelude::v1_0::*
 };
-use std::time::Instant;
-use anyhow::Result;
+use std::{time::Instant, mem::size_of};
+use nalgebra_glm as glm;
+use anyhow::{Result, anyhow};
 use crate::renderer::{
     core::Core,
     queue_family::QueueFamilyIndices,
+    camera::Camera,
+    light::Lights,
+    uniformbuffers::{CameraUniform, PushConstantObject},
+    material::draw_order,
+    debug_draw::DebugDraw,
+    rasterization::Topology,
+    render_settings::RenderSettings,
 };
 
 //================================================
@@ -15,7 +23,7 @@ use crate::renderer::{
 pub unsafe fn create_command_pools(
     instance: &Instance, 
     device: &Device, 
-    swapchain_images: &Vec<vk::Image>,
+    swapchain_images: &[vk::Image],
     surface: vk::SurfaceKHR,
     physical_device: vk::PhysicalDevice)
 -> Result<(vk::CommandPool, Vec<vk::CommandPool> )> {
@@ -52,13 +60,13 @@ unsafe fn create_command_pool(
 
 pub unsafe fn create_command_buffers(
     device: &Device, 
-    swapchain_images: &Vec<vk::Image>, 
-    command_pools: &Vec<vk::CommandPool>) -> Result<Vec<vk::CommandBuffer>> {
+    swapchain_images: &[vk::Image], 
+    command_pools: &[vk::CommandPool]) -> Result<Vec<vk::CommandBuffer>> {
     let num_images = swapchain_images.len();
     let mut command_buffers : Vec<vk::CommandBuffer> = Vec::with_capacity(num_images);
-    for image_index in 0..num_images {
+    for command_pool in command_pools.iter().take(num_images) {
         let allocate_info = vk::CommandBufferAllocateInfo::builder()
-            .command_pool(command_pools[image_index])
+            .command_pool(*command_pool)
             .level(vk::CommandBufferLevel::PRIMARY)
             .command_buffer_count(1);
 
@@ -68,9 +76,21 @@ pub unsafe fn create_command_buffers(
     Ok(command_buffers)
 }
 
+// the frame state is owned by Renderer, borrowed next to the mutable Core
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn update_command_buffer(device: &Device, core: &mut Core, 
-    image_index: usize, start: &Instant) -> Result<()> 
+    image_index: usize, start: &Instant, camera: &Camera, lights: &Lights, debug_draw: &DebugDraw,
+    settings: &RenderSettings) -> Result<()> 
 {
+    // Camera and lights, shared by every object
+    let extent = core.swapchain_extent();
+    let camera_uniform = CameraUniform::construct(camera.view(), camera.proj(extent.width, extent.height), camera.position());
+    core.camera_buffer().update(image_index, &camera_uniform)?;
+    core.lights_buffer().update(image_index, &lights.uniform())?;
+    core.update_background(settings)?;
+    core.update_material_uniforms(image_index)?;
+    core.upload_debug_draw(image_index, debug_draw)?;
+
     // Reset
     let command_pool = core.command_pools()[image_index];
     device.reset_command_pool(command_pool, vk::CommandPoolResetFlags::empty())?;
@@ -87,16 +107,7 @@ pub unsafe fn update_command_buffer(device: &Device, core: &mut Core,
         .offset(vk::Offset2D::default())
         .extent(core.swapchain_extent());
 
-    let color_clear_value = vk::ClearValue {
-        color: vk::ClearColorValue {
-            float32: [0.0, 0.1, 0.1, 1.0],
-        },
-    };
-    let depth_clear_value = vk::ClearValue {
-        depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 },
-    };
-
-    let clear_values = &[color_clear_value, depth_clear_value];
+    let clear_values = &settings.clear_values();
     let info = vk::RenderPassBeginInfo::builder()
         .render_pass(core.render_pass())
         .framebuffer(core.framebuffers()[image_index])
@@ -105,12 +116,38 @@ pub unsafe fn update_command_buffer(device: &Device, core: &mut Core,
 
     device.cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::SECONDARY_COMMAND_BUFFERS);
 
+    // Background first, without depth test, every object covers it
+    if settings.gradient().is_some() && core.gradient().is_some() {
+        let index = core.models().len() + 2;
+        let gradient_command_buffer = update_gradient_command_buffer(device, core, core.command_pools()[image_index], image_index, index)?;
+        device.cmd_execute_commands(command_buffer, &[gradient_command_buffer]);
+    }
+    if core.skybox().is_some() {
+        let index = core.models().len() + 1;
+        let skybox_command_buffer = update_skybox_command_buffer(device, core, core.command_pools()[image_index], image_index, index)?;
+        device.cmd_execute_commands(command_buffer, &[skybox_command_buffer]);
+    }
     if !core.models().is_empty() {
-        let secondary_command_buffers = (0..core.models().len())
-            .map(|i| update_secondary_command_buffer(device, core, core.command_pools()[image_index], image_index, i, start))
+        // Opaque objects first, then the transparent ones from back to front
+        let elapsed_time = start.elapsed().as_secs_f32();
+        let objects = (0..core.models().len())
+            .map(|i| {
+                let model = core.at_model(i);
+                let distance = glm::distance(&model.world_center(elapsed_time), &camera.position());
+                (model.shader().borrow().material().blend_mode(), distance)
+            })
+            .collect::<Vec<_>>();
+        let secondary_command_buffers = draw_order(&objects).into_iter()
+            .map(|i| update_secondary_command_buffer(device, core, core.command_pools()[image_index], image_index, i, elapsed_time))
             .collect::<Result<Vec<_>, _>>()?;
         device.cmd_execute_commands(command_buffer, &secondary_command_buffers[..]);
     }
+    // Debug primitives over the scene, recorded after the model ones
+    if core.debug_draw().is_some_and(|d| !d.calls(image_index).is_empty()) {
+        let index = core.models().len();
+        let debug_command_buffer = update_debug_command_buffer(device, core, core.command_pools()[image_index], image_index, index)?;
+        device.cmd_execute_commands(command_buffer, &[debug_command_buffer]);
+    }
     device.cmd_end_render_pass(command_buffer);
 
     device.end_command_buffer(command_buffer)?;
@@ -124,21 +161,9 @@ unsafe fn update_secondary_command_buffer(
     command_pool: vk::CommandPool,
     image_index: usize,
     model_index: usize,
-    start: &Instant,
+    elapsed_time: f32,
 ) -> Result<vk::CommandBuffer> {
-    let secondary_command_buffers = core.secondary_command_buffers_mut();
-    let secondary_command_buffers = &mut secondary_command_buffers[image_index];
-    while model_index >= secondary_command_buffers.len() {
-        let allocate_info = vk::CommandBufferAllocateInfo::builder()
-            .command_pool(command_pool)
-            .level(vk::CommandBufferLevel::SECONDARY)
-            .command_buffer_count(1);
-
-        let command_buffer = device.allocate_command_buffers(&allocate_info)?[0];
-        secondary_command_buffers.push(command_buffer);
-    }
-
-    let command_buffer = secondary_command_buffers[model_index];
+    let command_buffer = secondary_command_buffer(device, core, command_pool, image_index, model_index)?;
 
     //model who will be draw
     let model = core.at_model(model_index);
@@ -148,36 +173,18 @@ unsafe fn update_secondary_command_buffer(
     let shader = shader_ptr.borrow();
 
     //push constant data
-    let elapsed_time = start.elapsed().as_secs_f32();
-    let push_constant_object= model.uniform_buffer().update_matrix(
-        device, 
-        core.swapchain_extent(),
-        image_index,
-        model_index, 
-        elapsed_time)?;
-
-    let mat_model = push_constant_object.model();
-    let view = push_constant_object.view();
-    let model_slice = mat_model.as_slice();
-    let view_slice = view.as_slice();
-    let mut vec_push_constant = Vec::from(model_slice);
-    vec_push_constant.append(&mut Vec::from(view_slice));
-    let (_, push_constant_data, _) = vec_push_constant.as_slice().align_to::<u8>();
-
-    //info command buffer
-    let inheritance_info = vk::CommandBufferInheritanceInfo::builder()
-        .render_pass(core.render_pass())
-        .subpass(0)
-        .framebuffer(core.framebuffers()[image_index]);
-
-    let info = vk::CommandBufferBeginInfo::builder()
-        .flags(vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE)
-        .inheritance_info(&inheritance_info);
-    
-    device.begin_command_buffer(command_buffer, &info)?;
-
-    device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, shader.pipeline());
-    device.cmd_bind_vertex_buffers(command_buffer, 0, &[model_buffer.vertex_buffer()], &[0]);
+    let push_constant = PushConstantObject::construct(
+        model.model_matrix(elapsed_time),
+        shader.material().blend_mode().alpha_cutoff());
+    let push_constant_data = std::slice::from_raw_parts(
+        &push_constant as *const PushConstantObject as *const u8,
+        size_of::<PushConstantObject>());
+
+    begin_secondary_command_buffer(device, core, command_buffer, image_index)?;
+
+    device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, shader.pipeline(model.topology())?);
+    set_viewport(device, command_buffer, core.swapchain_extent());
+    device.cmd_bind_vertex_buffers(command_buffer, 0, &[model_buffer.vertex_buffer(), model.instances().buffer()], &[0, 0]);
     device.cmd_bind_index_buffer(command_buffer, model_buffer.index_buffer(), 0, vk::IndexType::UINT32);
 
     device.cmd_bind_descriptor_sets(
@@ -191,12 +198,182 @@ unsafe fn update_secondary_command_buffer(
     device.cmd_push_constants(
         command_buffer,
         shader.pipeline_layout(),
-        vk::ShaderStageFlags::VERTEX,
+        shader.push_constant_stages(),
         0,
         push_constant_data,
     );
-    device.cmd_draw_indexed(command_buffer, model_buffer.indices_len() as u32, 1, 0, 0, 0);
+    device.cmd_draw_indexed(command_buffer, model.index_count(), model.instances().instance_count() as u32, model.first_index(), 0, 0);
 
     device.end_command_buffer(command_buffer)?;
     Ok(command_buffer)
 }
+
+/// Draws the debug primitives uploaded for the swapchain image, the depth tested ones first.
+unsafe fn update_debug_command_buffer(
+    device : &Device,
+    core: &mut Core,
+    command_pool: vk::CommandPool,
+    image_index: usize,
+    index: usize,
+) -> Result<vk::CommandBuffer> {
+    let command_buffer = secondary_command_buffer(device, core, command_pool, image_index, index)?;
+    let debug_draw = core.debug_draw()
+        .ok_or_else(|| anyhow!("Debug drawing is not available."))?;
+    let buffer = debug_draw.buffer(image_index)
+        .ok_or_else(|| anyhow!("No debug primitives were uploaded for image {}.", image_index))?;
+    let push_constant = PushConstantObject::identity();
+    let push_constant_data = std::slice::from_raw_parts(
+        &push_constant as *const PushConstantObject as *const u8,
+        size_of::<PushConstantObject>());
+
+    begin_secondary_command_buffer(device, core, command_buffer, image_index)?;
+    set_viewport(device, command_buffer, core.swapchain_extent());
+    device.cmd_bind_vertex_buffers(command_buffer, 0, &[buffer, buffer], &[debug_draw.vertex_offset(), 0]);
+
+    for call in debug_draw.calls(image_index) {
+        let (shader, descriptor) = debug_draw.shader(call.depth_test);
+        let shader = shader.borrow();
+        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, shader.pipeline(call.topology)?);
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            shader.pipeline_layout(),
+            0,
+            &[descriptor.descriptor_sets()[image_index]],
+            &[],
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            shader.pipeline_layout(),
+            shader.push_constant_stages(),
+            0,
+            push_constant_data,
+        );
+        device.cmd_draw(command_buffer, call.vertex_count, 1, call.first_vertex, 0);
+    }
+
+    device.end_command_buffer(command_buffer)?;
+    Ok(command_buffer)
+}
+
+/// Draws the cube of the skybox around the camera.
+unsafe fn update_skybox_command_buffer(
+    device : &Device,
+    core: &mut Core,
+    command_pool: vk::CommandPool,
+    image_index: usize,
+    index: usize,
+) -> Result<vk::CommandBuffer> {
+    let command_buffer = secondary_command_buffer(device, core, command_pool, image_index, index)?;
+    let skybox = core.skybox()
+        .ok_or_else(|| anyhow!("No skybox is set."))?;
+    let shader_ptr = skybox.shader();
+    let shader = shader_ptr.borrow();
+    let cube = skybox.cube();
+
+    begin_secondary_command_buffer(device, core, command_buffer, image_index)?;
+
+    device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, shader.pipeline(Topology::TriangleList)?);
+    set_viewport(device, command_buffer, core.swapchain_extent());
+    device.cmd_bind_vertex_buffers(command_buffer, 0, &[cube.vertex_buffer()], &[0]);
+    device.cmd_bind_index_buffer(command_buffer, cube.index_buffer(), 0, vk::IndexType::UINT32);
+    device.cmd_bind_descriptor_sets(
+        command_buffer,
+        vk::PipelineBindPoint::GRAPHICS,
+        shader.pipeline_layout(),
+        0,
+        &[skybox.descriptor().descriptor_sets()[image_index]],
+        &[],
+    );
+    device.cmd_draw_indexed(command_buffer, cube.indices_len() as u32, 1, 0, 0, 0);
+
+    device.end_command_buffer(command_buffer)?;
+    Ok(command_buffer)
+}
+
+/// Draws the full-screen triangle of the gradient background.
+unsafe fn update_gradient_command_buffer(
+    device : &Device,
+    core: &mut Core,
+    command_pool: vk::CommandPool,
+    image_index: usize,
+    index: usize,
+) -> Result<vk::CommandBuffer> {
+    let command_buffer = secondary_command_buffer(device, core, command_pool, image_index, index)?;
+    let gradient = core.gradient()
+        .ok_or_else(|| anyhow!("No gradient background is set."))?;
+    let shader_ptr = gradient.shader();
+    let shader = shader_ptr.borrow();
+
+    begin_secondary_command_buffer(device, core, command_buffer, image_index)?;
+
+    device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, shader.pipeline(Topology::TriangleList)?);
+    set_viewport(device, command_buffer, core.swapchain_extent());
+    device.cmd_bind_descriptor_sets(
+        command_buffer,
+        vk::PipelineBindPoint::GRAPHICS,
+        shader.pipeline_layout(),
+        0,
+        &[gradient.descriptor().descriptor_sets()[image_index]],
+        &[],
+    );
+    // the vertices come from their index
+    device.cmd_draw(command_buffer, 3, 1, 0, 0);
+
+    device.end_command_buffer(command_buffer)?;
+    Ok(command_buffer)
+}
+
+/// Secondary command buffer at `index` for the swapchain image, allocated on first use.
+unsafe fn secondary_command_buffer(
+    device : &Device,
+    core: &mut Core,
+    command_pool: vk::CommandPool,
+    image_index: usize,
+    index: usize,
+) -> Result<vk::CommandBuffer> {
+    let secondary_command_buffers = core.secondary_command_buffers_mut();
+    let secondary_command_buffers = &mut secondary_command_buffers[image_index];
+    while index >= secondary_command_buffers.len() {
+        let allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .command_pool(command_pool)
+            .level(vk::CommandBufferLevel::SECONDARY)
+            .command_buffer_count(1);
+
+        let command_buffer = device.allocate_command_buffers(&allocate_info)?[0];
+        secondary_command_buffers.push(command_buffer);
+    }
+    Ok(secondary_command_buffers[index])
+}
+
+unsafe fn begin_secondary_command_buffer(device: &Device, core: &Core, command_buffer: vk::CommandBuffer, image_index: usize) -> Result<()> {
+    let inheritance_info = vk::CommandBufferInheritanceInfo::builder()
+        .render_pass(core.render_pass())
+        .subpass(0)
+        .framebuffer(core.framebuffers()[image_index]);
+
+    let info = vk::CommandBufferBeginInfo::builder()
+        .flags(vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE)
+        .inheritance_info(&inheritance_info);
+
+    device.begin_command_buffer(command_buffer, &info)?;
+    Ok(())
+}
+
+/// Covers the whole render target, the pipelines take their viewport and scissor from the command buffer.
+unsafe fn set_viewport(device: &Device, command_buffer: vk::CommandBuffer, extent: vk::Extent2D) {
+    let viewport = vk::Viewport::builder()
+        .x(0.0)
+        .y(0.0)
+        .width(extent.width as f32)
+        .height(extent.height as f32)
+        .min_depth(0.0)
+        .max_depth(1.0);
+
+    let scissor = vk::Rect2D::builder()
+        .offset(vk::Offset2D { x: 0, y: 0 })
+        .extent(extent);
+
+    device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+    device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+}
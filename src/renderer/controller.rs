@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use nalgebra_glm as glm;
+use winit::event::{
+    DeviceEvent, ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+use crate::renderer::camera::Camera;
+
+/// Smallest angle kept between the view direction and the up axis, avoids a degenerate look_at.
+const MIN_POLE_ANGLE: f32 = 0.01;
+/// Pixels of a `PixelDelta` scroll counted as one wheel line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Drives a `Camera` from winit input.
+///
+/// Events are accumulated as they arrive and applied once per frame by `update`,
+/// `delta` being the time elapsed since the previous frame in seconds.
+pub trait CameraController {
+    fn window_event(&mut self, event: &WindowEvent);
+    fn device_event(&mut self, event: &DeviceEvent);
+    fn update(&mut self, camera: &mut Camera, delta: f32);
+}
+
+//================================================
+// Orbit
+//================================================
+
+/// Arcball around the camera target: drag with the left button to rotate,
+/// scroll to zoom, arrow keys rotate at a constant angular speed.
+#[derive(Clone, Debug)]
+pub struct OrbitController {
+    /// Radians per pixel of mouse motion.
+    rotate_speed: f32,
+    /// Radians per second for the arrow keys.
+    key_speed: f32,
+    /// Fraction of the distance removed per wheel line.
+    zoom_speed: f32,
+    min_distance: f32,
+    max_distance: f32,
+    dragging: bool,
+    mouse_delta: glm::Vec2,
+    scroll: f32,
+    keys: HashSet<VirtualKeyCode>,
+}
+
+impl OrbitController {
+    pub fn new() -> Self {
+        Self {
+            rotate_speed: 0.005,
+            key_speed: glm::half_pi(),
+            zoom_speed: 0.1,
+            min_distance: 0.1,
+            max_distance: 100.0,
+            dragging: false,
+            mouse_delta: glm::vec2(0.0, 0.0),
+            scroll: 0.0,
+            keys: HashSet::new(),
+        }
+    }
+
+    pub fn set_rotate_speed(&mut self, rotate_speed: f32) { self.rotate_speed = rotate_speed; }
+    pub fn set_key_speed(&mut self, key_speed: f32) { self.key_speed = key_speed; }
+    pub fn set_zoom_speed(&mut self, zoom_speed: f32) { self.zoom_speed = zoom_speed; }
+    pub fn set_distance_range(&mut self, min_distance: f32, max_distance: f32) {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance;
+    }
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController for OrbitController {
+    fn window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                self.dragging = *state == ElementState::Pressed;
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+            },
+            WindowEvent::KeyboardInput { input, .. } => track_key(&mut self.keys, input.virtual_keycode, input.state),
+            WindowEvent::Focused(false) => {
+                self.dragging = false;
+                self.keys.clear();
+            },
+            _ => {}
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            if self.dragging {
+                self.mouse_delta += glm::vec2(*x as f32, *y as f32);
+            }
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, delta: f32) {
+        let key_axis = |positive, negative| axis(&self.keys, positive, negative) * self.key_speed * delta;
+        let yaw = -self.mouse_delta.x * self.rotate_speed + key_axis(VirtualKeyCode::Left, VirtualKeyCode::Right);
+        let elevation = self.mouse_delta.y * self.rotate_speed + key_axis(VirtualKeyCode::Up, VirtualKeyCode::Down);
+
+        let offset = camera.position() - camera.target();
+        let distance = glm::length(&offset);
+        if distance > 0.0 {
+            let direction = rotate_direction(&(offset / distance), &camera.up(), yaw, elevation);
+            let distance = (distance * (1.0 - self.zoom_speed).powf(self.scroll))
+                .clamp(self.min_distance, self.max_distance);
+            camera.set_position(camera.target() + direction * distance);
+        }
+
+        self.mouse_delta = glm::vec2(0.0, 0.0);
+        self.scroll = 0.0;
+    }
+}
+
+//================================================
+// Fly
+//================================================
+
+/// Free flight: WASD to move, Space / left Shift to go up and down,
+/// hold the right button and move the mouse to look around, left Control to go faster.
+#[derive(Clone, Debug)]
+pub struct FlyController {
+    /// World units per second.
+    move_speed: f32,
+    /// Multiplier applied to `move_speed` while left Control is held.
+    boost: f32,
+    /// Radians per pixel of mouse motion.
+    look_speed: f32,
+    looking: bool,
+    mouse_delta: glm::Vec2,
+    keys: HashSet<VirtualKeyCode>,
+}
+
+impl FlyController {
+    pub fn new() -> Self {
+        Self {
+            move_speed: 2.0,
+            boost: 4.0,
+            look_speed: 0.003,
+            looking: false,
+            mouse_delta: glm::vec2(0.0, 0.0),
+            keys: HashSet::new(),
+        }
+    }
+
+    pub fn set_move_speed(&mut self, move_speed: f32) { self.move_speed = move_speed; }
+    pub fn set_boost(&mut self, boost: f32) { self.boost = boost; }
+    pub fn set_look_speed(&mut self, look_speed: f32) { self.look_speed = look_speed; }
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController for FlyController {
+    fn window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::MouseInput { state, button: MouseButton::Right, .. } => {
+                self.looking = *state == ElementState::Pressed;
+            },
+            WindowEvent::KeyboardInput { input, .. } => track_key(&mut self.keys, input.virtual_keycode, input.state),
+            WindowEvent::Focused(false) => {
+                self.looking = false;
+                self.keys.clear();
+            },
+            _ => {}
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            if self.looking {
+                self.mouse_delta += glm::vec2(*x as f32, *y as f32);
+            }
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, delta: f32) {
+        let up = glm::normalize(&camera.up());
+        let offset = camera.target() - camera.position();
+        // Keep the target at the same distance so orbiting afterwards stays natural.
+        let distance = glm::length(&offset).max(f32::EPSILON);
+        let forward = rotate_direction(
+            &(offset / distance),
+            &up,
+            -self.mouse_delta.x * self.look_speed,
+            -self.mouse_delta.y * self.look_speed,
+        );
+        let right = glm::normalize(&glm::cross(&forward, &up));
+
+        let mut speed = self.move_speed * delta;
+        if self.keys.contains(&VirtualKeyCode::LControl) {
+            speed *= self.boost;
+        }
+        let movement = forward * axis(&self.keys, VirtualKeyCode::W, VirtualKeyCode::S)
+            + right * axis(&self.keys, VirtualKeyCode::D, VirtualKeyCode::A)
+            + up * axis(&self.keys, VirtualKeyCode::Space, VirtualKeyCode::LShift);
+        let position = camera.position() + movement * speed;
+
+        camera.set_position(position);
+        camera.set_target(position + forward * distance);
+        self.mouse_delta = glm::vec2(0.0, 0.0);
+    }
+}
+
+//================================================
+// Helpers
+//================================================
+
+fn track_key(keys: &mut HashSet<VirtualKeyCode>, key: Option<VirtualKeyCode>, state: ElementState) {
+    if let Some(key) = key {
+        match state {
+            ElementState::Pressed => keys.insert(key),
+            ElementState::Released => keys.remove(&key),
+        };
+    }
+}
+
+/// 1.0, -1.0 or 0.0 depending on which of the two keys is held.
+fn axis(keys: &HashSet<VirtualKeyCode>, positive: VirtualKeyCode, negative: VirtualKeyCode) -> f32 {
+    keys.contains(&positive) as i32 as f32 - keys.contains(&negative) as i32 as f32
+}
+
+/// Turns a unit `direction` by `yaw` around `up` then by `pitch` towards `up`,
+/// never letting it reach the poles.
+pub(crate) fn rotate_direction(direction: &glm::Vec3, up: &glm::Vec3, yaw: f32, pitch: f32) -> glm::Vec3 {
+    let up = glm::normalize(up);
+    let direction = glm::rotate_vec3(direction, yaw, &up);
+
+    let angle = glm::angle(&direction, &up);
+    let pitch = pitch.clamp(angle - glm::pi::<f32>() + MIN_POLE_ANGLE, angle - MIN_POLE_ANGLE);
+    let right = glm::cross(&direction, &up);
+    if glm::length(&right) <= f32::EPSILON {
+        return direction;
+    }
+    glm::normalize(&glm::rotate_vec3(&direction, pitch, &glm::normalize(&right)))
+}
@@ -9,7 +9,11 @@ use {
     std::{
         sync::Arc,
         cell::RefCell,
+        rc::Rc,
+        mem::size_of,
+        path::Path,
     },
+    log::*,
     winit::window::Window,
     super::{
         instance::{create_instance, VALIDATION_ENABLED},
@@ -21,17 +25,34 @@ use {
         depthbuffers::create_depth_objects, 
         framebuffers::create_framebuffers, 
         sync::create_sync_objects,
-        vulkan_model::VulkanModel,
+        offscreen::{create_offscreen_images, OFFSCREEN_FORMAT},
+        vulkan_model::{VulkanModel, ObjectId},
+        renderer::MAX_FRAMES_IN_FLIGHT,
         vulkan_shader::ShaderContainer,
+        uniformbuffers::{UniformBuffer, CameraUniform},
+        light::LightsUniform,
+        texture_cache::TextureCache,
+        buffer_cache::BufferCache,
+        allocator::{MemoryAllocator, MemoryStats, Allocation},
+        image::destroy_image,
+        pipeline_cache::{PipelineCache, PIPELINE_CACHE_PATH},
+        material::{Material, MaterialId},
+        vulkan_debug_draw::VulkanDebugDraw,
+        debug_draw::DebugDraw,
+        skybox::Skybox,
+        vulkan_gradient::VulkanGradient,
+        render_settings::RenderSettings,
     },
-    crate::object::Object,
-    anyhow::{Result},
+    crate::{object::Object, tools::cubemap::Cubemap},
+    anyhow::{anyhow, Result},
 };
 /// The Vulkan handles and associated properties used by our Vulkan app.
 #[derive(Clone)]
 pub struct Core {
     instance: Instance,
     device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
+    pipeline_cache: PipelineCache,
     surface: vk::SurfaceKHR,
     messenger: vk::DebugUtilsMessengerEXT,
 
@@ -42,11 +63,13 @@ pub struct Core {
     present_queue: vk::Queue,
 
 //swapchain
+    headless: bool,
     swapchain: vk::SwapchainKHR,
     swapchain_format: vk::Format,
     swapchain_extent: vk::Extent2D,
     swapchain_images: Vec<vk::Image>,
     swapchain_image_views: Vec<vk::ImageView>,
+    offscreen_images_allocation: Vec<Allocation>,
 
 //pipeline
     render_pass: vk::RenderPass,
@@ -66,53 +89,84 @@ pub struct Core {
 
 //depth buffer
     depth_image: vk::Image,
-    depth_image_memory: vk::DeviceMemory,
+    depth_image_allocation: Allocation,
     depth_image_view: vk::ImageView,
 
 //multisampling
     color_image: vk::Image,
-    color_image_memory: vk::DeviceMemory,
+    color_image_allocation: Allocation,
     color_image_view: vk::ImageView,
 
-    models: Vec<VulkanModel>,
-    shaders: Arc<RefCell<ShaderContainer>>,
+//shared uniforms
+    camera_buffer: UniformBuffer,
+    lights_buffer: UniformBuffer,
+    textures: TextureCache,
+    buffers: BufferCache,
+
+    models: Vec<(ObjectId, VulkanModel)>,
+    next_object_id: u64,
+    //removed models with the frame they were removed at, freed once no frame in flight uses them
+    retired_models: Vec<(u64, VulkanModel)>,
+    frame_count: u64,
+    shaders: Rc<RefCell<ShaderContainer>>,
+    //created with the first debug primitives
+    debug_draw: Option<VulkanDebugDraw>,
+    skybox: Option<Skybox>,
+    //created when a gradient background is first set
+    gradient: Option<VulkanGradient>,
     is_allocated: bool,
 }
 
 impl Core {
     pub fn new(window: &Window, entry: &Entry) -> Result<Self> {
+        Self::create(entry, Some(window), vk::Extent2D::default())
+    }
+
+    /// Creates a core without window nor surface, rendering into offscreen images of the given extent.
+    pub fn new_headless(entry: &Entry, extent: vk::Extent2D) -> Result<Self> {
+        Self::create(entry, None, extent)
+    }
+
+    fn create(entry: &Entry, window: Option<&Window>, extent: vk::Extent2D) -> Result<Self> {
         unsafe {
             let (instance, messenger) = create_instance(window, entry)?;
-            let surface = vk_window::create_surface(&instance, window)?;
+            let surface = match window {
+                Some(window) => vk_window::create_surface(&instance, window)?,
+                None => vk::SurfaceKHR::null(),
+            };
 
             let (physical_device, msaa_samples) = pick_physical_device(&instance, surface)?;
             let (device_,
                 graphics_queue,
-                present_queue) = create_logical_device(&instance, surface, physical_device)?;
+                present_queue,
+                rasterization_features) = create_logical_device(&instance, surface, physical_device)?;
             let device = Arc::new(device_);
+            let allocator = Arc::new(MemoryAllocator::new(device.clone(), &instance, physical_device));
+            let pipeline_cache = PipelineCache::new(device.clone(), &instance, physical_device, Path::new(PIPELINE_CACHE_PATH))?;
 
             let (swapchain,
                 swapchain_format,
                 swapchain_extent,
                 swapchain_images,
-            ) = create_swapchain(window, &instance, &device, surface, physical_device)?;
+                offscreen_images_allocation,
+            ) = create_render_targets(window, &instance, &device, &allocator, surface, physical_device, extent)?;
             let swapchain_image_views = create_swapchain_image_views(&device, &swapchain_images, swapchain_format)?;
 
-            let render_pass = create_render_pass(&instance, &device, physical_device, swapchain_format, msaa_samples)?;
+            let render_pass = create_render_pass(&instance, &device, physical_device, swapchain_format, msaa_samples, final_layout(window.is_none()))?;
                     
             let (command_pool,
                 command_pools
             ) = create_command_pools(&instance, &device, &swapchain_images, surface, physical_device)?;
 
             let (color_image, 
-                color_image_memory, 
+                color_image_allocation, 
                 color_image_view,
-            ) = create_color_objects(&instance, &device, physical_device, swapchain_extent, msaa_samples, swapchain_format)?;
+            ) = create_color_objects(&device, &allocator, swapchain_extent, msaa_samples, swapchain_format)?;
             
             let (depth_image, 
-                depth_image_memory, 
+                depth_image_allocation, 
                 depth_image_view,
-            ) = create_depth_objects(&instance, &device, physical_device, swapchain_extent, msaa_samples)?;
+            ) = create_depth_objects(&instance, &device, &allocator, physical_device, swapchain_extent, msaa_samples)?;
             
             let framebuffers = create_framebuffers(&device, &swapchain_image_views, 
                 swapchain_extent, render_pass, 
@@ -128,20 +182,26 @@ impl Core {
                 images_in_flight,
                 ) = create_sync_objects(&device, &swapchain_images)?;
 
+            let camera_buffer = UniformBuffer::new(device.clone(), allocator.clone(), &swapchain_images, size_of::<CameraUniform>() as u64)?;
+            let lights_buffer = UniformBuffer::new(device.clone(), allocator.clone(), &swapchain_images, size_of::<LightsUniform>() as u64)?;
+
             let core = Core {
                 instance,
                 device: device.clone(),
+                allocator: allocator.clone(),
                 surface,
                 messenger,
                 msaa_samples,
                 physical_device,
                 graphics_queue,
                 present_queue,
+                headless: window.is_none(),
                 swapchain,
                 swapchain_format,
                 swapchain_extent,
                 swapchain_images,
                 swapchain_image_views,
+                offscreen_images_allocation,
                 render_pass,
                 framebuffers,
                 command_pool,
@@ -153,13 +213,24 @@ impl Core {
                 in_flight_fences,
                 images_in_flight,
                 depth_image,
-                depth_image_memory,
+                depth_image_allocation,
                 depth_image_view,
                 color_image,
-                color_image_memory,
+                color_image_allocation,
                 color_image_view,
+                camera_buffer,
+                lights_buffer,
+                textures: TextureCache::new(),
+                buffers: BufferCache::new(),
                 models: vec![],
-                shaders: Arc::new(RefCell::new(ShaderContainer::new(device.clone()))),
+                next_object_id: 0,
+                retired_models: vec![],
+                frame_count: 0,
+                shaders: Rc::new(RefCell::new(ShaderContainer::new(device.clone(), allocator.clone(), pipeline_cache.cache(), rasterization_features))),
+                pipeline_cache,
+                debug_draw: None,
+                skybox: None,
+                gradient: None,
                 is_allocated: true,
             };
 
@@ -176,14 +247,25 @@ impl Core {
 
                 //swapchain
                 self.destroy_swapchain();
-
-                for i in 0..self.models.len() {
-                    let model = &mut self.models[i];
-                    model.clean();
-                }
+                self.device.destroy_render_pass(self.render_pass, None);
+
+                //models own the textures and buffers, drop them while the device is alive
+                self.models.clear();
+                self.retired_models.clear();
+                self.textures.release_unused();
+                self.buffers.release_unused();
+                self.debug_draw = None;
+                self.skybox = None;
+                self.gradient = None;
+                self.camera_buffer.clean();
+                self.lights_buffer.clean();
                 if let Some(shaders) = self.shaders.as_ptr().as_mut() {
                     shaders.clean();
                 }
+                if let Err(e) = self.pipeline_cache.save() {
+                    warn!("Unable to save the pipeline cache to `{}`: {}", self.pipeline_cache.path().display(), e);
+                }
+                self.pipeline_cache.clean();
 
                 self.command_pools.iter()
                     .for_each(|p| self.device.destroy_command_pool(*p, None));
@@ -197,8 +279,11 @@ impl Core {
                     .for_each(|s| self.device.destroy_semaphore(*s, None));
 
                 self.device.destroy_command_pool(self.command_pool, None);                
+                self.allocator.clean();
                 self.device.destroy_device(None);
-                self.instance.destroy_surface_khr(self.surface, None);
+                if !self.headless {
+                    self.instance.destroy_surface_khr(self.surface, None);
+                }
 
                 if VALIDATION_ENABLED {
                     self.instance.destroy_debug_utils_messenger_ext(self.messenger, None);
@@ -213,26 +298,27 @@ impl Core {
     unsafe fn destroy_swapchain(&mut self) {
         //multisampling buffer
         self.device.destroy_image_view(self.color_image_view, None);
-        self.device.free_memory(self.color_image_memory, None);
-        self.device.destroy_image(self.color_image, None);
+        destroy_image(&self.device, &self.allocator, self.color_image, &self.color_image_allocation);
 
         //depth buffer
         self.device.destroy_image_view(self.depth_image_view, None);
-        self.device.free_memory(self.depth_image_memory, None);
-        self.device.destroy_image(self.depth_image, None);
+        destroy_image(&self.device, &self.allocator, self.depth_image, &self.depth_image_allocation);
 
         //framebuffers
         self.framebuffers.iter()
             .for_each(|f| self.device.destroy_framebuffer(*f, None));
 
-        //pipeline
-        self.device.destroy_render_pass(self.render_pass, None);
-
         //swapchain
         self.swapchain_image_views.iter()
             .for_each(|v| self.device.destroy_image_view(*v, None));
 
-        self.device.destroy_swapchain_khr(self.swapchain, None);
+        if self.headless {
+            self.swapchain_images.iter()
+                .zip(self.offscreen_images_allocation.iter())
+                .for_each(|(i, a)| destroy_image(&self.device, &self.allocator, *i, a));
+        } else {
+            self.device.destroy_swapchain_khr(self.swapchain, None);
+        }
     }
 
     pub unsafe fn recreate_swapchain(&mut self, window: &Window) -> Result<()> {
@@ -240,70 +326,87 @@ impl Core {
         self.destroy_swapchain();
         let instance = &self.instance;
         let device = &self.device;
-        let surface = &self.surface;
-        let physical_device = &self.physical_device;
-        let msaa_samples = &self.msaa_samples;
+        let surface = self.surface;
+        let physical_device = self.physical_device;
+        let msaa_samples = self.msaa_samples;
 
         let (swapchain,
             swapchain_format,
             swapchain_extent,
             swapchain_images,
-        ) = create_swapchain(window, instance, &device, *surface, *physical_device)?;
-        let swapchain_image_views = create_swapchain_image_views(&device, &swapchain_images, swapchain_format)?;
+        ) = create_swapchain(window, instance, device, surface, physical_device)?;
+        let swapchain_image_views = create_swapchain_image_views(device, &swapchain_images, swapchain_format)?;
 
-        let render_pass = create_render_pass(&instance, &device, *physical_device, swapchain_format, *msaa_samples)?;
-                
         let (color_image, 
-            color_image_memory, 
+            color_image_allocation, 
             color_image_view,
-        ) = create_color_objects(instance, &device, *physical_device, swapchain_extent, *msaa_samples, swapchain_format)?;
+        ) = create_color_objects(device, &self.allocator, swapchain_extent, msaa_samples, swapchain_format)?;
         
         let (depth_image, 
-            depth_image_memory, 
+            depth_image_allocation, 
             depth_image_view,
-        ) = create_depth_objects(instance, &device,* physical_device, swapchain_extent, *msaa_samples)?;
+        ) = create_depth_objects(instance, device, &self.allocator, physical_device, swapchain_extent, msaa_samples)?;
         
-        let framebuffers = create_framebuffers(&device, &swapchain_image_views, 
-            swapchain_extent, render_pass, 
+        // the pipelines stay valid as long as the render pass keeps the same attachment formats
+        if swapchain_format != self.swapchain_format {
+            self.device.destroy_render_pass(self.render_pass, None);
+            self.render_pass = create_render_pass(instance, device, physical_device, swapchain_format, msaa_samples, final_layout(false))?;
+            if let Some(shaders) = self.shaders.as_ptr().as_mut() {
+                shaders.reload_render_pass(self.msaa_samples, self.render_pass)?;
+            }
+        }
+
+        let framebuffers = create_framebuffers(device, &swapchain_image_views, 
+            swapchain_extent, self.render_pass, 
             depth_image_view, color_image_view)?;
 
-        let command_buffers = create_command_buffers(&device, &swapchain_images, &self.command_pools)?;
+        let command_buffers = create_command_buffers(device, &swapchain_images, &self.command_pools)?;
 
         self.swapchain = swapchain;
         self.swapchain_format = swapchain_format;
         self.swapchain_extent = swapchain_extent;
         self.swapchain_images = swapchain_images;
         self.swapchain_image_views = swapchain_image_views;
-        self.render_pass = render_pass;
         self.framebuffers = framebuffers;
         self.color_image = color_image;
-        self.color_image_memory = color_image_memory;
+        self.color_image_allocation = color_image_allocation;
         self.color_image_view = color_image_view;
         self.depth_image = depth_image;
-        self.depth_image_memory = depth_image_memory;
+        self.depth_image_allocation = depth_image_allocation;
         self.depth_image_view = depth_image_view;
         self.command_buffers = command_buffers;
         self.secondary_command_buffers = vec![vec![]; self.swapchain_images.len()];
 
+        //the device is idle, nothing can still use the retired models
+        self.retired_models.clear();
+
+        self.camera_buffer.reload_swapchain(&self.swapchain_images)?;
+        self.lights_buffer.reload_swapchain(&self.swapchain_images)?;
         if let Some(shaders) = self.shaders.as_ptr().as_mut() {
-            shaders.reload_swapchain(self.swapchain_extent, self.msaa_samples, self.render_pass)?;
+            shaders.reload_swapchain(&self.swapchain_images)?;
         }
-
-        for i in 0..self.models.len() {
-            let model = &mut self.models[i];
+        for (_, model) in self.models.iter_mut() {
             model.reload_swapchain(
-                &self.instance,
-                self.physical_device,
-                &self.swapchain_images)?;
+                &self.swapchain_images,
+                &self.camera_buffer,
+                &self.lights_buffer)?;
+        }
+        if let Some(debug_draw) = self.debug_draw.as_mut() {
+            debug_draw.reload_swapchain(&self.swapchain_images, &self.camera_buffer, &self.lights_buffer)?;
+        }
+        if let Some(skybox) = self.skybox.as_mut() {
+            skybox.reload_swapchain(&self.swapchain_images, &self.camera_buffer, &self.lights_buffer)?;
+        }
+        if let Some(gradient) = self.gradient.as_mut() {
+            gradient.reload_swapchain(&self.swapchain_images, &self.camera_buffer, &self.lights_buffer)?;
         }
 
 
         Ok(())
     }
 
-    pub fn push_model(&mut self, model: VulkanModel) { self.models.push(model); }
-    pub fn models(&self) -> &[VulkanModel] { self.models.as_ref() }
-    pub fn at_model(&self, index: usize) -> &VulkanModel { &self.models[index] }
+    pub fn models(&self) -> &[(ObjectId, VulkanModel)] { self.models.as_ref() }
+    pub fn at_model(&self, index: usize) -> &VulkanModel { &self.models[index].1 }
 
     //getters
     pub fn device(&self) -> Arc<Device> { self.device.clone() }
@@ -326,26 +429,197 @@ impl Core {
     pub fn images_in_flight_mut(&mut self) -> &mut Vec<vk::Fence> { &mut self.images_in_flight }
     pub fn command_buffers_mut(&mut self) -> &mut Vec<vk::CommandBuffer> { &mut self.command_buffers }
     pub fn msaa_samples(&self) -> vk::SampleCountFlags { self.msaa_samples }
+    pub fn headless(&self) -> bool { self.headless }
+    pub fn camera_buffer(&self) -> &UniformBuffer { &self.camera_buffer }
+    pub fn lights_buffer(&self) -> &UniformBuffer { &self.lights_buffer }
+    pub fn textures(&self) -> &TextureCache { &self.textures }
+    pub fn buffers(&self) -> &BufferCache { &self.buffers }
+    pub fn allocator(&self) -> &Arc<MemoryAllocator> { &self.allocator }
+    pub fn memory_stats(&self) -> MemoryStats { self.allocator.stats() }
+
+    pub(crate) unsafe fn add_object(&mut self, obj: &dyn Object) -> Result<ObjectId> {
+        let id = ObjectId(self.next_object_id);
+        let model = self.create_model(id, obj)?;
+        self.next_object_id += 1;
+        self.models.push((id, model));
+        Ok(id)
+    }
 
-    pub(crate) unsafe fn add_object(&mut self, obj: &dyn Object) -> Result<()> {
-        if let Some(shaders) = self.shaders.as_ptr().as_mut() {
-            let model = VulkanModel::from_obj(
-                self.device(),
+    pub(crate) fn remove_object(&mut self, id: ObjectId) -> Result<()> {
+        let index = self.model_index(id)?;
+        let (_, model) = self.models.remove(index);
+        self.retired_models.push((self.frame_count, model));
+        Ok(())
+    }
+
+    pub(crate) unsafe fn replace_object(&mut self, id: ObjectId, obj: &dyn Object) -> Result<()> {
+        let index = self.model_index(id)?;
+        let model = self.create_model(id, obj)?;
+        let old_model = std::mem::replace(&mut self.models[index].1, model);
+        self.retired_models.push((self.frame_count, old_model));
+        Ok(())
+    }
+
+    /// The id of the object is its model index, so that removing an object does not move the others.
+    unsafe fn create_model(&mut self, id: ObjectId, obj: &dyn Object) -> Result<VulkanModel> {
+        let shaders = self.shaders.as_ptr().as_mut()
+            .ok_or_else(|| anyhow!("Shader container is not available."))?;
+        VulkanModel::from_obj(
+            self.device.clone(),
+            self.allocator.clone(),
+            shaders,
+            &mut self.textures,
+            &mut self.buffers,
+            &self.instance,
+            self.physical_device,
+            self.command_pool,
+            self.graphics_queue,
+            &self.swapchain_images,
+            self.msaa_samples,
+            self.render_pass,
+            &self.camera_buffer,
+            &self.lights_buffer,
+            id.0 as usize,
+            obj)
+    }
+
+    pub(crate) unsafe fn register_material(&mut self, material: Material) -> Result<MaterialId> {
+        let shaders = self.shaders.as_ptr().as_mut()
+            .ok_or_else(|| anyhow!("Shader container is not available."))?;
+        shaders.register(material, &self.swapchain_images, self.msaa_samples, self.render_pass)
+    }
+
+    pub(crate) fn set_material_uniform(&mut self, material: MaterialId, binding: u32, data: &[u8]) -> Result<()> {
+        self.shaders.borrow_mut().set_uniform(material, binding, data)
+    }
+
+    /// Copies the material uniforms in the buffers of the swapchain image about to be drawn.
+    pub fn update_material_uniforms(&self, image_index: usize) -> Result<()> {
+        self.shaders.borrow().update_uniforms(image_index)
+    }
+
+    fn model_index(&self, id: ObjectId) -> Result<usize> {
+        self.models.iter()
+            .position(|(model_id, _)| *model_id == id)
+            .ok_or_else(|| anyhow!("No object with id {:?}.", id))
+    }
+
+    /// Frees the retired models once every frame submitted before their removal has completed.
+    /// Must be called after waiting for the fence of the frame about to be recorded.
+    pub fn release_retired_models(&mut self) {
+        let frame_count = self.frame_count;
+        self.retired_models.retain(|(removed_at, _)| frame_count < removed_at + MAX_FRAMES_IN_FLIGHT as u64);
+        self.textures.release_unused();
+        self.buffers.release_unused();
+    }
+
+    /// Writes the debug primitives of the frame in the buffer of the swapchain image about to be drawn.
+    pub(crate) unsafe fn upload_debug_draw(&mut self, image_index: usize, debug_draw: &DebugDraw) -> Result<()> {
+        if self.debug_draw.is_none() {
+            if debug_draw.is_empty() {
+                return Ok(());
+            }
+            let shaders = self.shaders.as_ptr().as_mut()
+                .ok_or_else(|| anyhow!("Shader container is not available."))?;
+            self.debug_draw = Some(VulkanDebugDraw::new(
+                self.device.clone(),
+                self.allocator.clone(),
                 shaders,
-                self.instance(),
-                self.physical_device(),
-                self.command_pool(),
-                self.graphics_queue(),
-                self.swapchain_images(),
-                self.swapchain_extent(),
-                self.msaa_samples(),
-                self.render_pass(),
-                obj)?;
-            self.push_model(model);
+                &self.swapchain_images,
+                self.msaa_samples,
+                self.render_pass,
+                &self.camera_buffer,
+                &self.lights_buffer)?);
+        }
+        match self.debug_draw.as_mut() {
+            Some(vulkan_debug_draw) => vulkan_debug_draw.upload(image_index, debug_draw),
+            None => Ok(()),
         }
+    }
+
+    pub fn debug_draw(&self) -> Option<&VulkanDebugDraw> { self.debug_draw.as_ref() }
+
+    /// Uploads the cubemap drawn behind the scene, replacing the previous one.
+    pub(crate) unsafe fn set_skybox(&mut self, cubemap: &Cubemap) -> Result<()> {
+        let shaders = self.shaders.as_ptr().as_mut()
+            .ok_or_else(|| anyhow!("Shader container is not available."))?;
+        let skybox = Skybox::new(
+            self.device.clone(),
+            self.allocator.clone(),
+            shaders,
+            &self.instance,
+            self.physical_device,
+            self.command_pool,
+            self.graphics_queue,
+            &self.swapchain_images,
+            self.msaa_samples,
+            self.render_pass,
+            &self.camera_buffer,
+            &self.lights_buffer,
+            cubemap)?;
+        //the frames in flight may still sample the previous cubemap
+        self.device.device_wait_idle()?;
+        self.skybox = Some(skybox);
         Ok(())
     }
 
+    pub(crate) unsafe fn remove_skybox(&mut self) -> Result<()> {
+        if self.skybox.is_some() {
+            self.device.device_wait_idle()?;
+            self.skybox = None;
+        }
+        Ok(())
+    }
+
+    pub fn skybox(&self) -> Option<&Skybox> { self.skybox.as_ref() }
+
+    /// Writes the colors of the gradient background, before the material uniforms are updated.
+    pub(crate) unsafe fn update_background(&mut self, settings: &RenderSettings) -> Result<()> {
+        let Some(gradient) = settings.gradient() else {
+            return Ok(());
+        };
+        if self.gradient.is_none() {
+            let shaders = self.shaders.as_ptr().as_mut()
+                .ok_or_else(|| anyhow!("Shader container is not available."))?;
+            self.gradient = Some(VulkanGradient::new(
+                self.device.clone(),
+                shaders,
+                &self.swapchain_images,
+                self.msaa_samples,
+                self.render_pass,
+                &self.camera_buffer,
+                &self.lights_buffer)?);
+        }
+        match self.gradient.as_ref() {
+            Some(vulkan_gradient) => vulkan_gradient.set_colors(&gradient),
+            None => Ok(()),
+        }
+    }
+
+    pub fn gradient(&self) -> Option<&VulkanGradient> { self.gradient.as_ref() }
+
+    pub(crate) unsafe fn set_wireframe(&mut self, wireframe: bool) -> Result<()> {
+        let shaders = self.shaders.as_ptr().as_mut()
+            .ok_or_else(|| anyhow!("Shader container is not available."))?;
+        shaders.set_wireframe(wireframe)
+    }
+
+    pub fn wireframe(&self) -> bool {
+        self.shaders.borrow().wireframe()
+    }
+
+    /// Rebuilds the pipelines of the shader files edited since the last call.
+    pub fn reload_changed_shaders(&mut self) -> Result<()> {
+        unsafe {
+            if let Some(shaders) = self.shaders.as_ptr().as_mut() {
+                shaders.reload_changed()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn end_frame(&mut self) { self.frame_count += 1; }
+
     pub fn secondary_command_buffers_mut(&mut self) -> &mut Vec<Vec<vk::CommandBuffer>> {
         &mut self.secondary_command_buffers
     }
@@ -355,4 +629,44 @@ impl Drop for Core {
     fn drop(&mut self) {
         self.clean();
     }
-}
\ No newline at end of file
+}
+
+//================================================
+// Render targets
+//================================================
+
+/// Swapchain, format, extent, images and the allocations of the offscreen images.
+type RenderTargets = (vk::SwapchainKHR, vk::Format, vk::Extent2D, Vec<vk::Image>, Vec<Allocation>);
+
+/// Creates the images the render pass resolves into: the swapchain images when a window
+/// is given, offscreen images of `extent` otherwise (the swapchain handle is then null).
+unsafe fn create_render_targets(
+    window: Option<&Window>,
+    instance: &Instance,
+    device: &Device,
+    allocator: &MemoryAllocator,
+    surface: vk::SurfaceKHR,
+    physical_device: vk::PhysicalDevice,
+    extent: vk::Extent2D)
+-> Result<RenderTargets> {
+    if let Some(window) = window {
+        let (swapchain,
+            swapchain_format,
+            swapchain_extent,
+            swapchain_images,
+        ) = create_swapchain(window, instance, device, surface, physical_device)?;
+        Ok((swapchain, swapchain_format, swapchain_extent, swapchain_images, vec![]))
+    } else {
+        let (images, images_allocation) = create_offscreen_images(device, allocator, extent, 1)?;
+        Ok((vk::SwapchainKHR::null(), OFFSCREEN_FORMAT, extent, images, images_allocation))
+    }
+}
+
+/// Layout of the resolved image at the end of the render pass.
+fn final_layout(headless: bool) -> vk::ImageLayout {
+    if headless {
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL
+    } else {
+        vk::ImageLayout::PRESENT_SRC_KHR
+    }
+}
@@ -0,0 +1,157 @@
+use {
+    nalgebra_glm as glm,
+    super::{vertex::Vertex, rasterization::Topology},
+};
+
+/// Segments of the circles of `DebugDraw::sphere`.
+pub const SPHERE_SEGMENTS: usize = 24;
+
+/// Lines and triangles drawn with the same depth mode, two vertices per line and three per triangle.
+#[derive(Clone, Debug, Default)]
+pub struct DebugBatch {
+    lines: Vec<Vertex>,
+    triangles: Vec<Vertex>,
+}
+
+impl DebugBatch {
+    pub fn lines(&self) -> &[Vertex] { &self.lines }
+    pub fn triangles(&self) -> &[Vertex] { &self.triangles }
+    pub fn is_empty(&self) -> bool { self.lines.is_empty() && self.triangles.is_empty() }
+}
+
+/// Range of the vertices of `DebugDraw::draw_calls` drawn with one pipeline.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DebugDrawCall {
+    pub depth_test: bool,
+    pub topology: Topology,
+    pub first_vertex: u32,
+    pub vertex_count: u32,
+}
+
+/// Unlit primitives drawn for a single frame, cleared once the frame is rendered.
+/// Primitives are depth tested with the scene unless added after `set_depth_test(false)`,
+/// these ones are drawn on top of everything.
+#[derive(Clone, Debug)]
+pub struct DebugDraw {
+    depth_tested: DebugBatch,
+    overlay: DebugBatch,
+    depth_test: bool,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self { depth_tested: DebugBatch::default(), overlay: DebugBatch::default(), depth_test: true }
+    }
+}
+
+impl DebugDraw {
+    pub fn line(&mut self, from: glm::Vec3, to: glm::Vec3, color: glm::Vec3) {
+        let batch = self.batch_mut();
+        batch.lines.push(vertex(from, color));
+        batch.lines.push(vertex(to, color));
+    }
+
+    /// Filled triangle, visible from both sides.
+    pub fn triangle(&mut self, a: glm::Vec3, b: glm::Vec3, c: glm::Vec3, color: glm::Vec3) {
+        self.batch_mut().triangles.extend([vertex(a, color), vertex(b, color), vertex(c, color)]);
+    }
+
+    /// Edges of the axis aligned box between `min` and `max`.
+    pub fn aabb(&mut self, min: glm::Vec3, max: glm::Vec3, color: glm::Vec3) {
+        let corner = |i: usize| glm::vec3(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z });
+        // corners differing by a single coordinate
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// Circles of the sphere in the XY, YZ and XZ planes.
+    pub fn sphere(&mut self, center: glm::Vec3, radius: f32, color: glm::Vec3) {
+        let point = |angle: f32, plane: usize| {
+            let (sin, cos) = (radius * angle.sin(), radius * angle.cos());
+            center + match plane {
+                0 => glm::vec3(cos, sin, 0.0),
+                1 => glm::vec3(0.0, cos, sin),
+                _ => glm::vec3(cos, 0.0, sin),
+            }
+        };
+        let step = std::f32::consts::TAU / SPHERE_SEGMENTS as f32;
+        for plane in 0..3 {
+            for i in 0..SPHERE_SEGMENTS {
+                self.line(point(i as f32 * step, plane), point((i + 1) as f32 * step, plane), color);
+            }
+        }
+    }
+
+    /// Line from `from` to `to` with a head of four lines, a fifth of its length.
+    pub fn arrow(&mut self, from: glm::Vec3, to: glm::Vec3, color: glm::Vec3) {
+        self.line(from, to, color);
+        let direction = to - from;
+        let length = glm::length(&direction);
+        if length <= f32::EPSILON {
+            return;
+        }
+        let direction = direction / length;
+        // any vector not parallel to the arrow
+        let other = if direction.x.abs() < 0.9 { glm::vec3(1.0, 0.0, 0.0) } else { glm::vec3(0.0, 1.0, 0.0) };
+        let side = glm::normalize(&glm::cross(&direction, &other));
+        let up = glm::cross(&direction, &side);
+        let head = length * 0.2;
+        let base = to - direction * head;
+        for offset in [side, -side, up, -up] {
+            self.line(to, base + offset * head * 0.5, color);
+        }
+    }
+
+    /// Removes every primitive, done by the renderer after each frame.
+    pub fn clear(&mut self) {
+        self.depth_tested = DebugBatch::default();
+        self.overlay = DebugBatch::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth_tested.is_empty() && self.overlay.is_empty()
+    }
+
+    /// Vertices of every primitive and the draws covering them, the overlay ones last.
+    pub fn draw_calls(&self) -> (Vec<Vertex>, Vec<DebugDrawCall>) {
+        let mut vertices = vec![];
+        let mut calls = vec![];
+        for (depth_test, batch) in [(true, &self.depth_tested), (false, &self.overlay)] {
+            for (topology, primitives) in [(Topology::LineList, &batch.lines), (Topology::TriangleList, &batch.triangles)] {
+                if !primitives.is_empty() {
+                    calls.push(DebugDrawCall {
+                        depth_test,
+                        topology,
+                        first_vertex: vertices.len() as u32,
+                        vertex_count: primitives.len() as u32,
+                    });
+                    vertices.extend_from_slice(primitives);
+                }
+            }
+        }
+        (vertices, calls)
+    }
+
+    pub fn depth_tested(&self) -> &DebugBatch { &self.depth_tested }
+    pub fn overlay(&self) -> &DebugBatch { &self.overlay }
+    pub fn depth_test(&self) -> bool { self.depth_test }
+    /// Whether the next primitives are hidden by the scene.
+    pub fn set_depth_test(&mut self, depth_test: bool) { self.depth_test = depth_test; }
+
+    fn batch_mut(&mut self) -> &mut DebugBatch {
+        if self.depth_test { &mut self.depth_tested } else { &mut self.overlay }
+    }
+}
+
+/// Without normal the built-in shaders draw the vertex color as is.
+fn vertex(pos: glm::Vec3, color: glm::Vec3) -> Vertex {
+    Vertex::new(pos, color, glm::vec2(0.0, 0.0))
+}
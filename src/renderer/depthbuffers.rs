@@ -4,6 +4,7 @@ use vulkanalia::{
 use anyhow::{Result, anyhow};
 use crate::renderer::{
     image::*,
+    allocator::{MemoryAllocator, Allocation},
 };
 
 //================================================
@@ -13,17 +14,17 @@ use crate::renderer::{
 pub unsafe fn create_depth_objects(
     instance: &Instance,
     device: &Device, 
+    allocator: &MemoryAllocator,
     physical_device: vk::PhysicalDevice,
     swapchain_extent: vk::Extent2D,
     msaa_samples: vk::SampleCountFlags)
--> Result<(vk::Image, vk::DeviceMemory, vk::ImageView)> {
+-> Result<(vk::Image, Allocation, vk::ImageView)> {
     // Image + Image Memory
     let format = get_depth_format(instance, physical_device)?;
 
-    let (depth_image, depth_image_memory) = create_image(
-        instance,
+    let (depth_image, depth_image_allocation) = create_image(
         device,
-        physical_device,
+        allocator,
         swapchain_extent.width,
         swapchain_extent.height,
         1,
@@ -43,7 +44,7 @@ pub unsafe fn create_depth_objects(
         1,
     )?;
 
-    Ok((depth_image, depth_image_memory, depth_image_view))
+    Ok((depth_image, depth_image_allocation, depth_image_view))
 }
 
 pub unsafe fn get_depth_format(instance: &Instance, physical_device: vk::PhysicalDevice,
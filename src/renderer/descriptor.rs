@@ -1,16 +1,11 @@
 use {
-    std::{
-        mem::size_of,
-        sync::Arc,
-    },
+    std::sync::Arc,
     vulkanalia::prelude::v1_0::*,
     anyhow::Result,
     super::{
-        uniformbuffers::{
-            UniformBuffer, 
-            UniformBufferObject
-        },
-        vulkan_shader::ShaderType,
+        uniformbuffers::UniformBuffer,
+        material::{MaterialBinding, MaterialResource},
+        reflection::ReflectedBinding,
         vulkan_texture::VulkanTexture,
     },
 };
@@ -26,18 +21,21 @@ pub struct Descriptor {
 impl Descriptor {
     pub fn new(
         device: Arc<Device>,
-        swapchain_images: &Vec<vk::Image>, 
+        swapchain_images: &[vk::Image], 
         descriptor_set_layout: vk::DescriptorSetLayout, 
-        uniform_buffers: &UniformBuffer,
-        texture: &Option<VulkanTexture>) -> Result<Self> 
+        descriptor_counts: &[(vk::DescriptorType, u32)],
+        bindings: &[MaterialBinding],
+        uniform_buffers: &[(u32, &UniformBuffer)],
+        texture: Option<&VulkanTexture>) -> Result<Self> 
     {
         unsafe {
-            let descriptor_pool = create_descriptor_pool(&device, swapchain_images)?;
+            let descriptor_pool = create_descriptor_pool(&device, swapchain_images, descriptor_counts)?;
             let descriptor_sets = create_descriptor_sets(
                 &device, 
                 swapchain_images, 
                 descriptor_set_layout, 
-                uniform_buffers.uniform_buffers(), 
+                bindings,
+                uniform_buffers, 
                 descriptor_pool, 
                 texture)?;
 
@@ -51,20 +49,23 @@ impl Descriptor {
     }
 
     pub fn reload_swapchain(&mut self,
-        swapchain_images: &Vec<vk::Image>,
+        swapchain_images: &[vk::Image],
         descriptor_set_layout: vk::DescriptorSetLayout,
-        uniform_buffer: &UniformBuffer,
-        texture: &Option<VulkanTexture>,
+        descriptor_counts: &[(vk::DescriptorType, u32)],
+        bindings: &[MaterialBinding],
+        uniform_buffers: &[(u32, &UniformBuffer)],
+        texture: Option<&VulkanTexture>,
     ) -> Result<()> {
         unsafe {
             self.device.destroy_descriptor_pool(self.descriptor_pool, None);
-            self.descriptor_pool = create_descriptor_pool(&self.device, swapchain_images)?;
+            self.descriptor_pool = create_descriptor_pool(&self.device, swapchain_images, descriptor_counts)?;
 
             self.descriptor_sets = create_descriptor_sets(
                 &self.device, 
                 swapchain_images, 
                 descriptor_set_layout, 
-                uniform_buffer.uniform_buffers(), 
+                bindings,
+                uniform_buffers, 
                 self.descriptor_pool, 
                 texture)?;
             Ok(())
@@ -96,68 +97,56 @@ impl Drop for Descriptor {
 // descriptor set layout
 //================================================
 
-pub fn create_descriptor_set_layout(device: &Device, shader_type: ShaderType)  -> Result<vk::DescriptorSetLayout> {
+/// Binding of the camera uniform in the built-in materials.
+pub const CAMERA_BINDING: u32 = 0;
+/// Binding of the lights uniform in the built-in materials.
+pub const LIGHTS_BINDING: u32 = 1;
+/// Binding of the texture image in the textured material.
+pub const TEXTURE_BINDING: u32 = 2;
+/// Binding of the texture sampler in the textured material.
+pub const SAMPLER_BINDING: u32 = 3;
+/// Binding of the camera uniform in the skybox material.
+pub const SKYBOX_CAMERA_BINDING: u32 = 0;
+/// Binding of the cubemap image in the skybox material.
+pub const SKYBOX_TEXTURE_BINDING: u32 = 1;
+/// Binding of the cubemap sampler in the skybox material.
+pub const SKYBOX_SAMPLER_BINDING: u32 = 2;
+/// Binding of the colors in the gradient material.
+pub const GRADIENT_BINDING: u32 = 0;
+
+/// Layout of the bindings declared by the shaders, see `reflection::reflect`.
+pub fn create_descriptor_set_layout(device: &Device, bindings: &[ReflectedBinding]) -> Result<vk::DescriptorSetLayout> {
+    let bindings = bindings.iter()
+        .map(|binding| vk::DescriptorSetLayoutBinding::builder()
+            .binding(binding.binding())
+            .descriptor_type(binding.descriptor_type())
+            .descriptor_count(binding.count())
+            .stage_flags(binding.stages())
+            .build())
+        .collect::<Vec<_>>();
+    let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings);
+
     unsafe {
-        match shader_type {
-            ShaderType::Textured => descriptor_set_layout_textured(device),
-            ShaderType::Untextured => descriptor_set_layout_untextured(device),
-        }
+        Ok(device.create_descriptor_set_layout(&info, None)?)
     }
 }
 
-pub unsafe fn descriptor_set_layout_textured(device: &Device) -> Result<vk::DescriptorSetLayout> {
-    let ubo_binding = vk::DescriptorSetLayoutBinding::builder()
-        .binding(0)
-        .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-        .descriptor_count(1)
-        .stage_flags(vk::ShaderStageFlags::VERTEX);
-
-    let sampler_binding = vk::DescriptorSetLayoutBinding::builder()
-        .binding(1)
-        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-        .descriptor_count(1)
-        .stage_flags(vk::ShaderStageFlags::FRAGMENT);
-
-    let bindings = &[ubo_binding, sampler_binding];
-    let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
-
-    let descriptor_set_layout = device.create_descriptor_set_layout(&info, None)?;
-
-    Ok(descriptor_set_layout)
-}
-
-pub unsafe fn descriptor_set_layout_untextured(device: &Device) -> Result<vk::DescriptorSetLayout> {
-    let ubo_binding = vk::DescriptorSetLayoutBinding::builder()
-        .binding(0)
-        .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-        .descriptor_count(1)
-        .stage_flags(vk::ShaderStageFlags::VERTEX);
-
-    let bindings = &[ubo_binding];
-    let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
-
-    let descriptor_set_layout = device.create_descriptor_set_layout(&info, None)?;
-
-    Ok(descriptor_set_layout)
-}
-
 //================================================
 // descriptor pool
 //================================================
 
-pub unsafe fn create_descriptor_pool(device: &Device, swapchain_images: &Vec<vk::Image>) -> Result<vk::DescriptorPool> {
+/// Pool of one set per swapchain image, `descriptor_counts` being the descriptors of each type in a set.
+pub unsafe fn create_descriptor_pool(device: &Device, swapchain_images: &[vk::Image], descriptor_counts: &[(vk::DescriptorType, u32)]) -> Result<vk::DescriptorPool> {
     let swapchain_len = swapchain_images.len() as u32;
-    let ubo_size = vk::DescriptorPoolSize::builder()
-        .type_(vk::DescriptorType::UNIFORM_BUFFER)
-        .descriptor_count(swapchain_len);
-
-    let sampler_size = vk::DescriptorPoolSize::builder()
-        .type_(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-        .descriptor_count(swapchain_len);
+    let pool_sizes = descriptor_counts.iter()
+        .map(|(descriptor_type, count)| vk::DescriptorPoolSize::builder()
+            .type_(*descriptor_type)
+            .descriptor_count(swapchain_len * count)
+            .build())
+        .collect::<Vec<_>>();
 
-    let pool_sizes = &[ubo_size, sampler_size];
     let info = vk::DescriptorPoolCreateInfo::builder()
-        .pool_sizes(pool_sizes)
+        .pool_sizes(&pool_sizes)
         .max_sets(swapchain_len);
 
     Ok(device.create_descriptor_pool(&info, None)?)
@@ -168,112 +157,74 @@ pub unsafe fn create_descriptor_pool(device: &Device, swapchain_images: &Vec<vk:
 // descriptor sets
 //================================================
 
+/// Allocates one set per swapchain image, `uniform_buffers` gives the buffer of each uniform
+/// binding and the texture, if any, is written at the `Texture` and `Sampler` bindings.
 pub fn create_descriptor_sets(device: &Device, 
-    swapchain_images: &Vec<vk::Image>,
+    swapchain_images: &[vk::Image],
     descriptor_set_layout: vk::DescriptorSetLayout,
-    uniform_buffers: &Vec<vk::Buffer>,
+    bindings: &[MaterialBinding],
+    uniform_buffers: &[(u32, &UniformBuffer)],
     descriptor_pool: vk::DescriptorPool,
-    texture: &Option<VulkanTexture>) -> Result<Vec<vk::DescriptorSet>> 
+    texture: Option<&VulkanTexture>) -> Result<Vec<vk::DescriptorSet>> 
 {
     unsafe {
-        if let Some(texture) = &texture {
-            create_descriptor_sets_texture(device,
-                swapchain_images,descriptor_set_layout,
-                uniform_buffers,descriptor_pool, 
-                texture.texture_image_view(), 
-                texture.texture_sampler())
-        }
-        else { 
-            create_descriptor_sets_not_texture(device,
-                swapchain_images, 
-                descriptor_set_layout, 
-                uniform_buffers, 
-                descriptor_pool)
-        }
-    }
-}
-
-pub unsafe fn create_descriptor_sets_texture(
-        device: &Device, 
-        swapchain_images: &Vec<vk::Image>,
-        descriptor_set_layout: vk::DescriptorSetLayout,
-        uniform_buffers: &Vec<vk::Buffer>,
-        descriptor_pool: vk::DescriptorPool,
-        image_view : vk::ImageView,
-        sampler: vk::Sampler) -> Result<Vec<vk::DescriptorSet>> 
-{
-    let layouts = vec![descriptor_set_layout; swapchain_images.len()];
-    let info = vk::DescriptorSetAllocateInfo::builder()
-        .descriptor_pool(descriptor_pool)
-        .set_layouts(&layouts);
-
-    let descriptor_sets = device.allocate_descriptor_sets(&info)?;
-    
-    // Update
-    for i in 0..swapchain_images.len() {
-        let info = vk::DescriptorBufferInfo::builder()
-            .buffer(uniform_buffers[i])
-            .offset(0)
-            .range(size_of::<UniformBufferObject>() as u64);
-
-        let buffer_info = &[info];
-        let ubo_write = vk::WriteDescriptorSet::builder()
-            .dst_set(descriptor_sets[i])
-            .dst_binding(0)
-            .dst_array_element(0)
-            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-            .buffer_info(buffer_info);
-
-        let info = vk::DescriptorImageInfo::builder()
-            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
-            .image_view(image_view)
-            .sampler(sampler);
+        let layouts = vec![descriptor_set_layout; swapchain_images.len()];
+        let info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(descriptor_pool)
+            .set_layouts(&layouts);
+
+        let descriptor_sets = device.allocate_descriptor_sets(&info)?;
+
+        // Update
+        for (i, &descriptor_set) in descriptor_sets.iter().enumerate() {
+            let buffer_infos = uniform_buffers.iter()
+                .map(|(binding, uniform_buffer)| (*binding, [vk::DescriptorBufferInfo::builder()
+                    .buffer(uniform_buffer.uniform_buffers()[i])
+                    .offset(0)
+                    .range(uniform_buffer.size())
+                    .build()]))
+                .collect::<Vec<_>>();
+
+            let mut writes = buffer_infos.iter()
+                .map(|(binding, buffer_info)| vk::WriteDescriptorSet::builder()
+                    .dst_set(descriptor_set)
+                    .dst_binding(*binding)
+                    .dst_array_element(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                    .buffer_info(buffer_info)
+                    .build())
+                .collect::<Vec<_>>();
+
+            let image_info;
+            let sampler_info;
+            if let Some(texture) = texture {
+                image_info = [vk::DescriptorImageInfo::builder()
+                    .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .image_view(texture.texture_image_view())
+                    .build()];
+                sampler_info = [vk::DescriptorImageInfo::builder()
+                    .sampler(texture.texture_sampler())
+                    .build()];
+
+                for binding in bindings {
+                    let info = match binding.resource() {
+                        MaterialResource::Texture => &image_info,
+                        MaterialResource::Sampler => &sampler_info,
+                        _ => continue,
+                    };
+                    writes.push(vk::WriteDescriptorSet::builder()
+                        .dst_set(descriptor_set)
+                        .dst_binding(binding.binding())
+                        .dst_array_element(0)
+                        .descriptor_type(binding.resource().descriptor_type())
+                        .image_info(info)
+                        .build());
+                }
+            }
 
-        let image_info = &[info];
-        let sampler_write = vk::WriteDescriptorSet::builder()
-            .dst_set(descriptor_sets[i])
-            .dst_binding(1)
-            .dst_array_element(0)
-            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-            .image_info(image_info);
+            device.update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+        }
 
-        device.update_descriptor_sets(&[ubo_write, sampler_write], &[] as &[vk::CopyDescriptorSet]);
+        Ok(descriptor_sets)
     }
-    
-    Ok(descriptor_sets)
 }
-
-pub unsafe fn create_descriptor_sets_not_texture(
-        device: &Device, 
-        swapchain_images: &Vec<vk::Image>,
-        descriptor_set_layout: vk::DescriptorSetLayout,
-        uniform_buffers: &Vec<vk::Buffer>,
-        descriptor_pool: vk::DescriptorPool) -> Result<Vec<vk::DescriptorSet>> 
-{
-    let layouts = vec![descriptor_set_layout; swapchain_images.len()];
-    let info = vk::DescriptorSetAllocateInfo::builder()
-        .descriptor_pool(descriptor_pool)
-        .set_layouts(&layouts);
-
-    let descriptor_sets = device.allocate_descriptor_sets(&info)?;
-    
-    // Update
-    for i in 0..swapchain_images.len() {
-        let info = vk::DescriptorBufferInfo::builder()
-            .buffer(uniform_buffers[i])
-            .offset(0)
-            .range(size_of::<UniformBufferObject>() as u64);
-
-        let buffer_info = &[info];
-        let ubo_write = vk::WriteDescriptorSet::builder()
-            .dst_set(descriptor_sets[i])
-            .dst_binding(0)
-            .dst_array_element(0)
-            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-            .buffer_info(buffer_info);
-
-        device.update_descriptor_sets(&[ubo_write], &[] as &[vk::CopyDescriptorSet]);
-    }
-    
-    Ok(descriptor_sets)
-}
\ No newline at end of file
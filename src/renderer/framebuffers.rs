@@ -10,7 +10,7 @@ use anyhow::Result;
 
 pub unsafe fn create_framebuffers(
     device: &Device,
-    swapchain_image_views: &Vec<vk::ImageView>,
+    swapchain_image_views: &[vk::ImageView],
     swapchain_extent: vk::Extent2D,
     render_pass: vk::RenderPass,
     depth_image_view: vk::ImageView,
@@ -8,7 +8,8 @@ use vulkanalia::{
 use anyhow::{Result, anyhow};
 use crate::{renderer::{
     buffers_tools::*,
-}, tools::texture::Texture};
+    allocator::{MemoryAllocator, Allocation, ResourceTiling},
+}, tools::{texture::{Texture, TextureFormat}, cubemap::Cubemap}};
 
 //================================================
 // texture image
@@ -17,46 +18,52 @@ use crate::{renderer::{
 pub unsafe fn create_texture_image(
     instance: &Instance,
     device: &Device, 
+    allocator: &MemoryAllocator,
     physical_device: vk::PhysicalDevice,
     command_pool: vk::CommandPool,
     graphics_queue: vk::Queue,
     texture: Arc<Texture>)
- -> Result<(vk::Image, vk::DeviceMemory, u32)> {
+ -> Result<(vk::Image, Allocation, u32)> {
     
     let data = texture.data();
+    let format = texture_format(texture.format());
     let size = texture.buffer_size() as u64;
     let width = texture.width();
     let height = texture.height();
+    let expected_size = width as u64 * height as u64 * texture.format().bytes_per_pixel() as u64;
+    if width == 0 || height == 0 || data.len() as u64 != expected_size {
+        return Err(anyhow!("Texture of {}x{} {:?} pixels must hold {} bytes, not {}.",
+            width, height, texture.format(), expected_size, data.len()));
+    }
 
-    let mip_levels = (width.max(height) as f32).log2().floor() as u32 + 1;
+    // Formats that can not be filtered linearly (often the float ones) go without mipmaps
+    let mip_levels = if supports_linear_filter(instance, physical_device, format) {
+        (width.max(height) as f32).log2().floor() as u32 + 1
+    } else {
+        1
+    };
 
     // Create (staging)
-    let (staging_buffer, staging_buffer_memory) = create_buffer(
-        instance,
+    let (staging_buffer, staging_buffer_allocation) = create_buffer(
         device,
-        physical_device,
+        allocator,
         size,
         vk::BufferUsageFlags::TRANSFER_SRC,
         vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
     )?;
 
     // Copy (staging)
-    let memory = device.map_memory(staging_buffer_memory, 0, size, vk::MemoryMapFlags::empty())?;
-
-    memcpy(data.as_ptr(), memory.cast(), data.len());
-
-    device.unmap_memory(staging_buffer_memory);
+    memcpy(data.as_ptr(), staging_buffer_allocation.mapped()?, data.len());
 
     // Create (image)
-    let (texture_image, texture_image_memory) = create_image(
-        instance,
+    let (texture_image, texture_image_allocation) = create_image(
         device,
-        physical_device,
+        allocator,
         width,
         height,
         mip_levels,
         vk::SampleCountFlags::_1,
-        vk::Format::R8G8B8A8_SRGB,
+        format,
         vk::ImageTiling::OPTIMAL,
         vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::TRANSFER_SRC,
         vk::MemoryPropertyFlags::DEVICE_LOCAL,
@@ -68,10 +75,11 @@ pub unsafe fn create_texture_image(
         command_pool, 
         graphics_queue,
         texture_image,
-        vk::Format::R8G8B8A8_SRGB,
+        format,
         vk::ImageLayout::UNDEFINED,
         vk::ImageLayout::TRANSFER_DST_OPTIMAL,
         mip_levels,
+        1,
     )?;
 
     copy_buffer_to_image(
@@ -82,11 +90,11 @@ pub unsafe fn create_texture_image(
         texture_image,
         width,
         height,
+        1,
     )?;
 
     // Cleanup
-    device.destroy_buffer(staging_buffer, None);
-    device.free_memory(staging_buffer_memory, None);
+    destroy_buffer(device, allocator, staging_buffer, &staging_buffer_allocation);
 
     // Mipmaps
     generate_mipmaps(
@@ -96,38 +104,179 @@ pub unsafe fn create_texture_image(
         command_pool, 
         graphics_queue,
         texture_image,
-        vk::Format::R8G8B8A8_SRGB,
+        format,
         width,
         height,
         mip_levels,
     )?;
 
-    Ok((texture_image, texture_image_memory, mip_levels))
+    Ok((texture_image, texture_image_allocation, mip_levels))
+}
+
+
+/// GPU format a texture is uploaded in.
+pub fn texture_format(format: TextureFormat) -> vk::Format {
+    match format {
+        TextureFormat::Rgba8Srgb => vk::Format::R8G8B8A8_SRGB,
+        TextureFormat::Rgba32Float => vk::Format::R32G32B32A32_SFLOAT,
+    }
+}
+
+/// Whether the format can be blitted and sampled with linear filters.
+pub unsafe fn supports_linear_filter(instance: &Instance, physical_device: vk::PhysicalDevice, format: vk::Format) -> bool {
+    instance
+        .get_physical_device_format_properties(physical_device, format)
+        .optimal_tiling_features
+        .contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
+}
+
+//================================================
+// cubemap image
+//================================================
+
+/// Uploads the six faces of the cubemap to the layers of a cube compatible image, without mipmaps.
+pub unsafe fn create_cubemap_image(
+    device: &Device,
+    allocator: &MemoryAllocator,
+    command_pool: vk::CommandPool,
+    graphics_queue: vk::Queue,
+    cubemap: &Cubemap,
+) -> Result<(vk::Image, Allocation)> {
+    let data = cubemap.data();
+    let format = texture_format(cubemap.format());
+    let size = cubemap.size();
+
+    // Create (staging)
+    let (staging_buffer, staging_buffer_allocation) = create_buffer(
+        device,
+        allocator,
+        data.len() as u64,
+        vk::BufferUsageFlags::TRANSFER_SRC,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )?;
+
+    // Copy (staging)
+    memcpy(data.as_ptr(), staging_buffer_allocation.mapped()?, data.len());
+
+    // Create (image)
+    let (cubemap_image, cubemap_image_allocation) = create_layered_image(
+        device,
+        allocator,
+        size,
+        size,
+        1,
+        6,
+        vk::ImageCreateFlags::CUBE_COMPATIBLE,
+        vk::SampleCountFlags::_1,
+        format,
+        vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+
+    // Transition + Copy + Transition (image)
+    transition_image_layout(
+        device,
+        command_pool,
+        graphics_queue,
+        cubemap_image,
+        format,
+        vk::ImageLayout::UNDEFINED,
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        1,
+        6,
+    )?;
+
+    copy_buffer_to_image(
+        device,
+        command_pool,
+        graphics_queue,
+        staging_buffer,
+        cubemap_image,
+        size,
+        size,
+        6,
+    )?;
+
+    transition_image_layout(
+        device,
+        command_pool,
+        graphics_queue,
+        cubemap_image,
+        format,
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        1,
+        6,
+    )?;
+
+    // Cleanup
+    destroy_buffer(device, allocator, staging_buffer, &staging_buffer_allocation);
+
+    Ok((cubemap_image, cubemap_image_allocation))
+}
+
+pub unsafe fn create_cubemap_image_view(device: &Device, cubemap_image: vk::Image, format: TextureFormat) -> Result<vk::ImageView> {
+    create_layered_image_view(
+        device,
+        cubemap_image,
+        texture_format(format),
+        vk::ImageAspectFlags::COLOR,
+        1,
+        vk::ImageViewType::CUBE,
+        6,
+    )
 }
 
+/// Clamps to the edges so that no seam shows between the faces, with nearest filters when the
+/// format does not support linear ones.
+pub unsafe fn create_cubemap_sampler(device: &Device, linear: bool) -> Result<vk::Sampler> {
+    let (filter, mipmap_mode) = sampler_filters(linear);
+    let info = vk::SamplerCreateInfo::builder()
+        .mag_filter(filter)
+        .min_filter(filter)
+        .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+        .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+        .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+        .anisotropy_enable(false)
+        .max_anisotropy(1.0)
+        .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+        .unnormalized_coordinates(false)
+        .compare_enable(false)
+        .compare_op(vk::CompareOp::ALWAYS)
+        .mipmap_mode(mipmap_mode)
+        .min_lod(0.0)
+        .max_lod(0.0)
+        .mip_lod_bias(0.0);
+
+    let sampler = device.create_sampler(&info, None)?;
+    Ok(sampler)
+}
 
 //================================================
 // texture image view
 //================================================
 
-pub unsafe fn create_texture_image_view(device: &Device, texture_image: vk::Image, mip_levels: u32) -> Result<vk::ImageView> {
-    Ok(create_image_view(
+pub unsafe fn create_texture_image_view(device: &Device, texture_image: vk::Image, format: TextureFormat, mip_levels: u32) -> Result<vk::ImageView> {
+    create_image_view(
         device,
         texture_image,
-        vk::Format::R8G8B8A8_SRGB,
+        texture_format(format),
         vk::ImageAspectFlags::COLOR,
         mip_levels,
-    )?)
+    )
 }
 
 //================================================
 // texture sampler
 //================================================
 
-pub unsafe fn create_texture_sampler(device: &Device, mip_levels: u32) -> Result<vk::Sampler> {
+/// Samples with nearest filters when the format does not support linear ones (often the float ones).
+pub unsafe fn create_texture_sampler(device: &Device, mip_levels: u32, linear: bool) -> Result<vk::Sampler> {
+    let (filter, mipmap_mode) = sampler_filters(linear);
     let info = vk::SamplerCreateInfo::builder()
-        .mag_filter(vk::Filter::LINEAR)
-        .min_filter(vk::Filter::LINEAR)
+        .mag_filter(filter)
+        .min_filter(filter)
         .address_mode_u(vk::SamplerAddressMode::REPEAT)
         .address_mode_v(vk::SamplerAddressMode::REPEAT)
         .address_mode_w(vk::SamplerAddressMode::REPEAT)
@@ -137,7 +286,7 @@ pub unsafe fn create_texture_sampler(device: &Device, mip_levels: u32) -> Result
         .unnormalized_coordinates(false)
         .compare_enable(false)
         .compare_op(vk::CompareOp::ALWAYS)
-        .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+        .mipmap_mode(mipmap_mode)
         .min_lod(0.0)       // Optional.
         .max_lod(mip_levels as f32)
         .mip_lod_bias(0.0); // Optional.
@@ -146,10 +295,20 @@ pub unsafe fn create_texture_sampler(device: &Device, mip_levels: u32) -> Result
     Ok(sampler)
 }
 
+fn sampler_filters(linear: bool) -> (vk::Filter, vk::SamplerMipmapMode) {
+    if linear {
+        (vk::Filter::LINEAR, vk::SamplerMipmapMode::LINEAR)
+    } else {
+        (vk::Filter::NEAREST, vk::SamplerMipmapMode::NEAREST)
+    }
+}
+
 //================================================
 // mimaps generation
 //================================================
 
+// blitting needs the physical device to check the format on top of the image description
+#[allow(clippy::too_many_arguments)]
 unsafe fn generate_mipmaps(
     instance: &Instance,
     device: &Device,
@@ -163,11 +322,7 @@ unsafe fn generate_mipmaps(
     mip_levels: u32,
 ) -> Result<()> {
     // Support
-    if !instance
-        .get_physical_device_format_properties(physical_device, format)
-        .optimal_tiling_features
-        .contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
-    {
+    if mip_levels > 1 && !supports_linear_filter(instance, physical_device, format) {
         return Err(anyhow!("Texture image format does not support linear blitting!"));
     }
 
@@ -303,27 +458,42 @@ pub unsafe fn create_image_view(
     format: vk::Format,
     aspects: vk::ImageAspectFlags,
     mip_levels: u32,
+) -> Result<vk::ImageView> {
+    create_layered_image_view(device, image, format, aspects, mip_levels, vk::ImageViewType::_2D, 1)
+}
+
+/// View over the first `layer_count` layers of the image, as a cube for the six layers of a cubemap.
+pub unsafe fn create_layered_image_view(
+    device: &Device,
+    image: vk::Image,
+    format: vk::Format,
+    aspects: vk::ImageAspectFlags,
+    mip_levels: u32,
+    view_type: vk::ImageViewType,
+    layer_count: u32,
 ) -> Result<vk::ImageView> {
     let subresource_range = vk::ImageSubresourceRange::builder()
         .aspect_mask(aspects)
         .base_mip_level(0)
         .level_count(mip_levels)
         .base_array_layer(0)
-        .layer_count(1);
+        .layer_count(layer_count);
 
     let info = vk::ImageViewCreateInfo::builder()
         .image(image)
-        .view_type(vk::ImageViewType::_2D)
+        .view_type(view_type)
         .format(format)
         .subresource_range(subresource_range);
 
     Ok(device.create_image_view(&info, None)?)
 }
 
+/// Creates an image bound to memory sub-allocated by `allocator`.
+// single layer shorthand of `create_layered_image`, taking the same image description
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn create_image(
-    instance: &Instance,
     device: &Device,
-    physical_device: vk::PhysicalDevice,
+    allocator: &MemoryAllocator,
     width: u32,
     height: u32,
     mip_levels: u32,
@@ -332,9 +502,31 @@ pub unsafe fn create_image(
     tiling: vk::ImageTiling,
     usage: vk::ImageUsageFlags,
     properties: vk::MemoryPropertyFlags,
-) -> Result<(vk::Image, vk::DeviceMemory)> {
+) -> Result<(vk::Image, Allocation)> {
+    create_layered_image(device, allocator, width, height, mip_levels, 1, vk::ImageCreateFlags::empty(),
+        samples, format, tiling, usage, properties)
+}
+
+/// Creates an image of `array_layers` layers, `CUBE_COMPATIBLE` for cubemaps.
+// one argument per field of `vk::ImageCreateInfo` the renderer varies, plus the memory properties
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn create_layered_image(
+    device: &Device,
+    allocator: &MemoryAllocator,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+    array_layers: u32,
+    flags: vk::ImageCreateFlags,
+    samples: vk::SampleCountFlags,
+    format: vk::Format,
+    tiling: vk::ImageTiling,
+    usage: vk::ImageUsageFlags,
+    properties: vk::MemoryPropertyFlags,
+) -> Result<(vk::Image, Allocation)> {
     // Image
     let info = vk::ImageCreateInfo::builder()
+        .flags(flags)
         .image_type(vk::ImageType::_2D)
         .extent(vk::Extent3D {
             width,
@@ -342,7 +534,7 @@ pub unsafe fn create_image(
             depth: 1,
         })
         .mip_levels(mip_levels)
-        .array_layers(1)
+        .array_layers(array_layers)
         .format(format)
         .tiling(tiling)
         .initial_layout(vk::ImageLayout::UNDEFINED)
@@ -355,17 +547,27 @@ pub unsafe fn create_image(
     // Memory
     let requirements = device.get_image_memory_requirements(image);
 
-    let info = vk::MemoryAllocateInfo::builder()
-        .allocation_size(requirements.size)
-        .memory_type_index(get_memory_type_index(instance, physical_device, properties, requirements)?);
+    let resource_tiling = if tiling == vk::ImageTiling::LINEAR { ResourceTiling::Linear } else { ResourceTiling::Optimal };
+    let allocation = match allocator.allocate(requirements, properties, resource_tiling) {
+        Ok(allocation) => allocation,
+        Err(e) => {
+            device.destroy_image(image, None);
+            return Err(e);
+        },
+    };
 
-    let image_memory = device.allocate_memory(&info, None)?;
+    device.bind_image_memory(image, allocation.memory(), allocation.offset())?;
 
-    device.bind_image_memory(image, image_memory, 0)?;
+    Ok((image, allocation))
+}
 
-    Ok((image, image_memory))
+pub unsafe fn destroy_image(device: &Device, allocator: &MemoryAllocator, image: vk::Image, allocation: &Allocation) {
+    device.destroy_image(image, None);
+    allocator.free(allocation);
 }
 
+// recorded in a single time command, the pool and queue come on top of the copy region
+#[allow(clippy::too_many_arguments)]
 unsafe fn copy_buffer_to_image(
     device: &Device,
     command_pool: vk::CommandPool,
@@ -374,14 +576,16 @@ unsafe fn copy_buffer_to_image(
     image: vk::Image,
     width: u32,
     height: u32,
+    layer_count: u32,
 ) -> Result<()> {
     let command_buffer = begin_single_time_commands(device, command_pool)?;
 
+    // Layers follow each other in the buffer
     let subresource = vk::ImageSubresourceLayers::builder()
         .aspect_mask(vk::ImageAspectFlags::COLOR)
         .mip_level(0)
         .base_array_layer(0)
-        .layer_count(1);
+        .layer_count(layer_count);
 
     let region = vk::BufferImageCopy::builder()
         .buffer_offset(0)
@@ -408,6 +612,8 @@ unsafe fn copy_buffer_to_image(
     Ok(())
 }
 
+// recorded in a single time command, the pool and queue come on top of the barrier
+#[allow(clippy::too_many_arguments)]
 unsafe fn transition_image_layout(
     device: &Device,
     command_pool: vk::CommandPool,
@@ -417,6 +623,7 @@ unsafe fn transition_image_layout(
     old_layout: vk::ImageLayout,
     new_layout: vk::ImageLayout,
     mip_levels: u32,
+    layer_count: u32,
 ) -> Result<()> {
     let (src_access_mask, dst_access_mask, src_stage_mask, dst_stage_mask) = 
     match (old_layout, new_layout) {
@@ -442,7 +649,7 @@ unsafe fn transition_image_layout(
         .base_mip_level(0)
         .level_count(mip_levels)
         .base_array_layer(0)
-        .layer_count(1);
+        .layer_count(layer_count);
 
     let barrier = vk::ImageMemoryBarrier::builder()
         .old_layout(old_layout)
@@ -474,17 +681,15 @@ unsafe fn transition_image_layout(
 //================================================
 
 pub unsafe fn create_color_objects(
-    instance: &Instance,
     device: &Device,
-    physical_device: vk::PhysicalDevice,
+    allocator: &MemoryAllocator,
     swapchain_extent: vk::Extent2D,
     msaa_samples: vk::SampleCountFlags,
     swapchain_format: vk::Format,
-) -> Result<(vk::Image, vk::DeviceMemory,vk::ImageView)> {
-    let (color_image, color_image_memory) = create_image(
-        instance,
+) -> Result<(vk::Image, Allocation, vk::ImageView)> {
+    let (color_image, color_image_allocation) = create_image(
         device,
-        physical_device,
+        allocator,
         swapchain_extent.width,
         swapchain_extent.height,
         1,
@@ -504,5 +709,5 @@ pub unsafe fn create_color_objects(
         1,
     )?;
 
-    Ok((color_image, color_image_memory, color_image_view))
+    Ok((color_image, color_image_allocation, color_image_view))
 }
\ No newline at end of file
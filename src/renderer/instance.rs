@@ -24,7 +24,7 @@ pub const VALIDATION_LAYER: vk::ExtensionName =
 // Instance
 //================================================
 
-pub unsafe fn create_instance(window: &Window, entry: &Entry) -> Result<(Instance, vk::DebugUtilsMessengerEXT)> {
+pub unsafe fn create_instance(window: Option<&Window>, entry: &Entry) -> Result<(Instance, vk::DebugUtilsMessengerEXT)> {
     let application_info = vk::ApplicationInfo::builder()
         .application_name(b"Vulkan Tutorial\0")
         .application_version(vk::make_version(1, 0, 0))
@@ -47,10 +47,14 @@ pub unsafe fn create_instance(window: &Window, entry: &Entry) -> Result<(Instanc
         Vec::new()
     };    
 
-    let mut extensions = vk_window::get_required_instance_extensions(window)
-    .iter()
-    .map(|e| e.as_ptr())
-    .collect::<Vec<_>>();
+    // A headless instance does not present, so it needs no surface extensions.
+    let mut extensions = match window {
+        Some(window) => vk_window::get_required_instance_extensions(window)
+            .iter()
+            .map(|e| e.as_ptr())
+            .collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
 
     if VALIDATION_ENABLED {
         extensions.push(vk::EXT_DEBUG_UTILS_EXTENSION.name.as_ptr());
@@ -0,0 +1,141 @@
+use anyhow::{Result, anyhow};
+use nalgebra_glm as glm;
+
+/// Point lights a scene can hold, size of the array in the lights uniform of the shaders.
+pub const MAX_POINT_LIGHTS: usize = 16;
+
+//================================================
+// Lights
+//================================================
+
+/// Light coming from infinitely far away along `direction`, like the sun.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionalLight {
+    direction: glm::Vec3,
+    color: glm::Vec3,
+    intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: glm::Vec3, color: glm::Vec3, intensity: f32) -> Self {
+        Self { direction, color, intensity }
+    }
+
+    pub fn direction(&self) -> glm::Vec3 { self.direction }
+    pub fn color(&self) -> glm::Vec3 { self.color }
+    pub fn intensity(&self) -> f32 { self.intensity }
+    pub fn set_direction(&mut self, direction: glm::Vec3) { self.direction = direction; }
+    pub fn set_color(&mut self, color: glm::Vec3) { self.color = color; }
+    pub fn set_intensity(&mut self, intensity: f32) { self.intensity = intensity; }
+}
+
+/// Light emitted in every direction from `position`, fading out to nothing at `range`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointLight {
+    position: glm::Vec3,
+    color: glm::Vec3,
+    intensity: f32,
+    range: f32,
+}
+
+impl PointLight {
+    pub fn new(position: glm::Vec3, color: glm::Vec3, intensity: f32, range: f32) -> Self {
+        Self { position, color, intensity, range }
+    }
+
+    pub fn position(&self) -> glm::Vec3 { self.position }
+    pub fn color(&self) -> glm::Vec3 { self.color }
+    pub fn intensity(&self) -> f32 { self.intensity }
+    pub fn range(&self) -> f32 { self.range }
+    pub fn set_position(&mut self, position: glm::Vec3) { self.position = position; }
+    pub fn set_color(&mut self, color: glm::Vec3) { self.color = color; }
+    pub fn set_intensity(&mut self, intensity: f32) { self.intensity = intensity; }
+    pub fn set_range(&mut self, range: f32) { self.range = range; }
+}
+
+/// Lights of the scene, shared by every object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lights {
+    ambient: glm::Vec3,
+    directional: Option<DirectionalLight>,
+    point_lights: Vec<PointLight>,
+}
+
+impl Lights {
+    pub fn new(ambient: glm::Vec3, directional: Option<DirectionalLight>) -> Self {
+        Self { ambient, directional, point_lights: Vec::new() }
+    }
+
+    /// Adds a point light and returns its index, fails past `MAX_POINT_LIGHTS`.
+    pub fn add_point_light(&mut self, light: PointLight) -> Result<usize> {
+        if self.point_lights.len() >= MAX_POINT_LIGHTS {
+            return Err(anyhow!("Too many point lights, at most {} are supported.", MAX_POINT_LIGHTS));
+        }
+        self.point_lights.push(light);
+        Ok(self.point_lights.len() - 1)
+    }
+
+    pub fn remove_point_light(&mut self, index: usize) -> PointLight {
+        self.point_lights.remove(index)
+    }
+
+    pub fn clear_point_lights(&mut self) { self.point_lights.clear(); }
+    pub fn ambient(&self) -> glm::Vec3 { self.ambient }
+    pub fn directional(&self) -> Option<DirectionalLight> { self.directional }
+    pub fn point_lights(&self) -> &[PointLight] { self.point_lights.as_ref() }
+    pub fn point_lights_mut(&mut self) -> &mut [PointLight] { self.point_lights.as_mut() }
+    pub fn set_ambient(&mut self, ambient: glm::Vec3) { self.ambient = ambient; }
+    pub fn set_directional(&mut self, directional: Option<DirectionalLight>) { self.directional = directional; }
+
+    pub fn uniform(&self) -> LightsUniform {
+        let mut uniform = LightsUniform {
+            ambient: glm::vec4(self.ambient.x, self.ambient.y, self.ambient.z, 0.0),
+            directional_direction: glm::Vec4::zeros(),
+            directional_color: glm::Vec4::zeros(),
+            point_light_count: [self.point_lights.len() as u32, 0, 0, 0],
+            point_lights: [PointLightUniform::default(); MAX_POINT_LIGHTS],
+        };
+        if let Some(light) = &self.directional {
+            let direction = glm::normalize(&light.direction);
+            uniform.directional_direction = glm::vec4(direction.x, direction.y, direction.z, 0.0);
+            uniform.directional_color = glm::vec4(light.color.x, light.color.y, light.color.z, light.intensity);
+        }
+        for (data, light) in uniform.point_lights.iter_mut().zip(&self.point_lights) {
+            data.position = glm::vec4(light.position.x, light.position.y, light.position.z, light.range);
+            data.color = glm::vec4(light.color.x, light.color.y, light.color.z, light.intensity);
+        }
+        uniform
+    }
+}
+
+impl Default for Lights {
+    /// Soft ambient and a white light from above, so that scenes are lit out of the box.
+    fn default() -> Self {
+        Self::new(
+            glm::vec3(0.15, 0.15, 0.15),
+            Some(DirectionalLight::new(glm::vec3(-0.4, -0.3, -1.0), glm::vec3(1.0, 1.0, 1.0), 1.0)),
+        )
+    }
+}
+
+//================================================
+// Uniform
+//================================================
+
+/// std140 layout of the lights uniform, intensities and ranges are packed in the `w` components.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct LightsUniform {
+    ambient: glm::Vec4,
+    directional_direction: glm::Vec4,
+    directional_color: glm::Vec4,
+    point_light_count: [u32; 4],
+    point_lights: [PointLightUniform; MAX_POINT_LIGHTS],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+struct PointLightUniform {
+    position: glm::Vec4,
+    color: glm::Vec4,
+}
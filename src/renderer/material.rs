@@ -0,0 +1,320 @@
+use {
+    std::{collections::HashSet, mem::size_of},
+    vulkanalia::prelude::v1_0::*,
+    anyhow::{Result, anyhow},
+    super::{
+        shader_source::ShaderSource,
+        descriptor::{CAMERA_BINDING, LIGHTS_BINDING, TEXTURE_BINDING, SAMPLER_BINDING,
+            SKYBOX_CAMERA_BINDING, SKYBOX_TEXTURE_BINDING, SKYBOX_SAMPLER_BINDING, GRADIENT_BINDING},
+        reflection::ShaderReflection,
+        rasterization::Rasterization,
+        uniformbuffers::{CameraUniform, ALPHA_CUTOFF_END},
+        light::LightsUniform,
+        render_settings::GradientUniform,
+    },
+};
+
+/// Handle of a material registered in the renderer.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MaterialId(pub(crate) usize);
+
+impl MaterialId {
+    /// Lit material sampling the texture of the object.
+    pub const TEXTURED: MaterialId = MaterialId(0);
+    /// Lit material using the vertex colors only.
+    pub const UNTEXTURED: MaterialId = MaterialId(1);
+    /// `TEXTURED` without culling, for flat objects.
+    pub const TEXTURED_DOUBLE_SIDED: MaterialId = MaterialId(2);
+    /// `UNTEXTURED` without culling, for flat objects.
+    pub const UNTEXTURED_DOUBLE_SIDED: MaterialId = MaterialId(3);
+    /// Debug drawing, depth tested with the scene.
+    pub(crate) const DEBUG: MaterialId = MaterialId(4);
+    /// Debug drawing, on top of the scene.
+    pub(crate) const DEBUG_OVERLAY: MaterialId = MaterialId(5);
+    /// Cubemap drawn behind the scene.
+    pub(crate) const SKYBOX: MaterialId = MaterialId(6);
+    /// Gradient background of `RenderSettings`.
+    pub(crate) const GRADIENT: MaterialId = MaterialId(7);
+}
+
+/// What the renderer binds at a binding of a material.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MaterialResource {
+    /// The camera uniform, see `CameraUniform`.
+    Camera,
+    /// The lights uniform, see `LightsUniform`.
+    Lights,
+    /// Uniform block of the given size in bytes, shared by the objects of the material
+    /// and written with `Renderer::set_material_uniform`.
+    Uniform(u64),
+    /// Image of the object texture, a `texture2D` in GLSL, or a `textureCube` for the skybox.
+    Texture,
+    /// Sampler of the object texture, a `sampler` in GLSL.
+    Sampler,
+}
+
+impl MaterialResource {
+    pub fn descriptor_type(&self) -> vk::DescriptorType {
+        match self {
+            MaterialResource::Camera | MaterialResource::Lights | MaterialResource::Uniform(_) => vk::DescriptorType::UNIFORM_BUFFER,
+            MaterialResource::Texture => vk::DescriptorType::SAMPLED_IMAGE,
+            MaterialResource::Sampler => vk::DescriptorType::SAMPLER,
+        }
+    }
+}
+
+/// Resource bound at a binding of the descriptor set, the stages using it come from the shaders.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MaterialBinding {
+    binding: u32,
+    resource: MaterialResource,
+}
+
+impl MaterialBinding {
+    pub fn new(binding: u32, resource: MaterialResource) -> Self {
+        Self { binding, resource }
+    }
+
+    pub fn binding(&self) -> u32 { self.binding }
+    pub fn resource(&self) -> MaterialResource { self.resource }
+}
+
+//================================================
+// Blending
+//================================================
+
+/// Alpha under which the fragments of `BlendMode::Cutout` materials are discarded.
+pub const ALPHA_CUTOFF: f32 = 0.5;
+
+/// How the fragments of a material are combined with the render target.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BlendMode {
+    /// Replaces the target.
+    #[default]
+    Opaque,
+    /// Mixes the color with the target by its alpha.
+    Alpha,
+    /// Adds the color weighted by its alpha to the target.
+    Additive,
+    /// Like `Alpha`, for colors already multiplied by their alpha.
+    Premultiplied,
+    /// Opaque, fragments with an alpha under `ALPHA_CUTOFF` are discarded. The fragment shader
+    /// must read the cutoff pushed after the model matrix, like `texture.frag`.
+    Cutout,
+}
+
+impl BlendMode {
+    /// Transparent objects do not write depth and are drawn after the opaque ones.
+    pub fn is_transparent(&self) -> bool {
+        matches!(self, BlendMode::Alpha | BlendMode::Additive | BlendMode::Premultiplied)
+    }
+
+    /// Alpha cutoff pushed to the fragment shader.
+    pub fn alpha_cutoff(&self) -> f32 {
+        match self {
+            BlendMode::Cutout => ALPHA_CUTOFF,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Order to draw objects given their blend mode and distance to the camera: the opaque ones
+/// in their order, then the transparent ones from the farthest to the nearest.
+pub fn draw_order(objects: &[(BlendMode, f32)]) -> Vec<usize> {
+    let mut order = (0..objects.len())
+        .filter(|&i| !objects[i].0.is_transparent())
+        .collect::<Vec<_>>();
+    let mut transparent = (0..objects.len())
+        .filter(|&i| objects[i].0.is_transparent())
+        .collect::<Vec<_>>();
+    transparent.sort_by(|&a, &b| objects[b].1.total_cmp(&objects[a].1));
+    order.extend(transparent);
+    order
+}
+
+//================================================
+// Material
+//================================================
+
+/// A vertex and fragment shader pair with the resources of its descriptor set.
+/// The vertex shader reads the `Vertex` and `InstanceData` attributes and the model
+/// matrix from the push constants, like the built-in shaders.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Material {
+    name: String,
+    vertex: ShaderSource,
+    fragment: ShaderSource,
+    bindings: Vec<MaterialBinding>,
+    blend_mode: BlendMode,
+    rasterization: Rasterization,
+    depth_test: bool,
+}
+
+impl Material {
+    /// Opaque material drawing the front faces, see `set_blend_mode` and `set_rasterization`.
+    pub fn new(name: &str, vertex: ShaderSource, fragment: ShaderSource, bindings: Vec<MaterialBinding>) -> Self {
+        Self {
+            name: name.to_string(),
+            vertex,
+            fragment,
+            bindings,
+            blend_mode: BlendMode::Opaque,
+            rasterization: Rasterization::default(),
+            depth_test: true,
+        }
+    }
+
+    pub fn textured() -> Self {
+        Self::new("textured",
+            ShaderSource::file_or_embedded("shaders/texture.vert", include_bytes!("../../shaders/texture_vert.spv")),
+            ShaderSource::file_or_embedded("shaders/texture.frag", include_bytes!("../../shaders/texture_frag.spv")),
+            vec![
+                MaterialBinding::new(CAMERA_BINDING, MaterialResource::Camera),
+                MaterialBinding::new(LIGHTS_BINDING, MaterialResource::Lights),
+                MaterialBinding::new(TEXTURE_BINDING, MaterialResource::Texture),
+                MaterialBinding::new(SAMPLER_BINDING, MaterialResource::Sampler),
+            ])
+    }
+
+    pub fn untextured() -> Self {
+        Self::new("untextured",
+            ShaderSource::file_or_embedded("shaders/only_color.vert", include_bytes!("../../shaders/only_color_vert.spv")),
+            ShaderSource::file_or_embedded("shaders/only_color.frag", include_bytes!("../../shaders/only_color_frag.spv")),
+            vec![
+                MaterialBinding::new(CAMERA_BINDING, MaterialResource::Camera),
+                MaterialBinding::new(LIGHTS_BINDING, MaterialResource::Lights),
+            ])
+    }
+
+    pub fn textured_double_sided() -> Self {
+        let mut material = Self::textured();
+        material.name = "textured_double_sided".to_string();
+        material.rasterization = Rasterization::double_sided();
+        material
+    }
+
+    pub fn untextured_double_sided() -> Self {
+        let mut material = Self::untextured();
+        material.name = "untextured_double_sided".to_string();
+        material.rasterization = Rasterization::double_sided();
+        material
+    }
+
+    /// Unlit lines and triangles of `DebugDraw`, hidden by the scene only if `depth_test` is set.
+    pub fn debug(depth_test: bool) -> Self {
+        let mut material = Self::untextured_double_sided();
+        material.name = if depth_test { "debug" } else { "debug_overlay" }.to_string();
+        material.depth_test = depth_test;
+        material
+    }
+
+    /// Unlit cubemap sampled in the direction of the vertices, drawn first at the far plane.
+    pub fn skybox() -> Self {
+        let mut material = Self::new("skybox",
+            ShaderSource::file_or_embedded("shaders/skybox.vert", include_bytes!("../../shaders/skybox_vert.spv")),
+            ShaderSource::file_or_embedded("shaders/skybox.frag", include_bytes!("../../shaders/skybox_frag.spv")),
+            vec![
+                MaterialBinding::new(SKYBOX_CAMERA_BINDING, MaterialResource::Camera),
+                MaterialBinding::new(SKYBOX_TEXTURE_BINDING, MaterialResource::Texture),
+                MaterialBinding::new(SKYBOX_SAMPLER_BINDING, MaterialResource::Sampler),
+            ]);
+        material.rasterization = Rasterization::double_sided();
+        material.depth_test = false;
+        material
+    }
+
+    /// Full-screen triangle of the gradient background, drawn first at the far plane.
+    pub fn gradient() -> Self {
+        let mut material = Self::new("gradient",
+            ShaderSource::file_or_embedded("shaders/gradient.vert", include_bytes!("../../shaders/gradient_vert.spv")),
+            ShaderSource::file_or_embedded("shaders/gradient.frag", include_bytes!("../../shaders/gradient_frag.spv")),
+            vec![
+                MaterialBinding::new(GRADIENT_BINDING, MaterialResource::Uniform(size_of::<GradientUniform>() as u64)),
+            ]);
+        material.rasterization = Rasterization::double_sided();
+        material.depth_test = false;
+        material
+    }
+
+    /// Checks the bindings are unique, uniforms are not empty and the texture comes with its sampler.
+    pub fn validate(&self) -> Result<()> {
+        let mut bindings = HashSet::new();
+        for binding in self.bindings.iter() {
+            if !bindings.insert(binding.binding) {
+                return Err(anyhow!("Material `{}` uses binding {} twice.", self.name, binding.binding));
+            }
+            if binding.resource == MaterialResource::Uniform(0) {
+                return Err(anyhow!("Uniform at binding {} of material `{}` is empty.", binding.binding, self.name));
+            }
+        }
+        let count = |resource| self.bindings.iter().filter(|b| b.resource == resource).count();
+        if count(MaterialResource::Texture) != count(MaterialResource::Sampler) || count(MaterialResource::Texture) > 1 {
+            return Err(anyhow!("Material `{}` must bind the object texture with one image and one sampler.", self.name));
+        }
+        Ok(())
+    }
+
+    /// Checks the bindings match the descriptors declared by the shaders of the material,
+    /// and that the fragment shader of a cutout material reads the alpha cutoff.
+    pub fn check(&self, reflection: &ShaderReflection) -> Result<()> {
+        if self.blend_mode == BlendMode::Cutout && !reflection.fragment_push_constants_reach(ALPHA_CUTOFF_END) {
+            return Err(anyhow!("Material `{}` uses the cutout blend mode, its fragment shader does not read the alpha cutoff.",
+                self.name));
+        }
+        for reflected in reflection.bindings() {
+            if reflected.set() != 0 {
+                return Err(anyhow!("Descriptor `{}` of material `{}` is in set {}, materials only use set 0.",
+                    reflected.name(), self.name, reflected.set()));
+            }
+            let binding = self.bindings.iter()
+                .find(|b| b.binding == reflected.binding())
+                .ok_or_else(|| anyhow!("Descriptor `{}` at binding {} of material `{}` has no resource.",
+                    reflected.name(), reflected.binding(), self.name))?;
+            if binding.resource.descriptor_type() != reflected.descriptor_type() || reflected.count() != 1 {
+                return Err(anyhow!("Descriptor `{}` at binding {} of material `{}` is a {:?}, the material binds a {:?}.",
+                    reflected.name(), reflected.binding(), self.name, reflected.descriptor_type(), binding.resource));
+            }
+            let available = match binding.resource {
+                MaterialResource::Camera => size_of::<CameraUniform>() as u64,
+                MaterialResource::Lights => size_of::<LightsUniform>() as u64,
+                MaterialResource::Uniform(size) => size,
+                MaterialResource::Texture | MaterialResource::Sampler => continue,
+            };
+            if reflected.block_size().is_some_and(|size| size > available) {
+                return Err(anyhow!("Uniform `{}` at binding {} of material `{}` reads {} bytes, only {} are bound.",
+                    reflected.name(), reflected.binding(), self.name, reflected.block_size().unwrap_or_default(), available));
+            }
+        }
+        if let Some(unused) = self.bindings.iter().find(|b| !reflection.bindings().iter().any(|r| r.binding() == b.binding)) {
+            return Err(anyhow!("Binding {} of material `{}` is not declared by its shaders.", unused.binding, self.name));
+        }
+        Ok(())
+    }
+
+    /// Whether the objects drawn with the material need a texture.
+    pub fn uses_texture(&self) -> bool {
+        self.bindings.iter().any(|b| b.resource == MaterialResource::Texture)
+    }
+
+    /// Size of the uniform block at `binding`, `None` if it is not a material uniform.
+    pub fn uniform_size(&self, binding: u32) -> Option<u64> {
+        self.bindings.iter()
+            .find(|b| b.binding == binding)
+            .and_then(|b| match b.resource {
+                MaterialResource::Uniform(size) => Some(size),
+                _ => None,
+            })
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn vertex(&self) -> &ShaderSource { &self.vertex }
+    pub fn fragment(&self) -> &ShaderSource { &self.fragment }
+    pub fn bindings(&self) -> &[MaterialBinding] { &self.bindings }
+    pub fn blend_mode(&self) -> BlendMode { self.blend_mode }
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) { self.blend_mode = blend_mode; }
+    pub fn rasterization(&self) -> Rasterization { self.rasterization }
+    pub fn set_rasterization(&mut self, rasterization: Rasterization) { self.rasterization = rasterization; }
+    pub fn depth_test(&self) -> bool { self.depth_test }
+    /// Without depth test the material is drawn over what is already drawn and does not write the depth.
+    pub fn set_depth_test(&mut self, depth_test: bool) { self.depth_test = depth_test; }
+}
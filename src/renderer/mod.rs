@@ -1,10 +1,15 @@
 #[allow(dead_code)]
+// `renderer::renderer::Renderer` is the public path of the renderer
+#[allow(clippy::module_inception)]
 pub mod renderer;
 pub mod vulkan_texture;
 pub mod vertexbuffers;
 pub mod vulkan_model;
 pub mod uniformbuffers;
 pub mod vertex;
+pub mod camera;
+pub mod controller;
+pub mod light;
 mod core;
 mod instance;
 mod descriptor;
@@ -17,4 +22,19 @@ mod pipeline;
 mod framebuffers;
 mod commandbuffers;
 mod sync;
-mod vulkan_shader;
\ No newline at end of file
+mod offscreen;
+mod vulkan_shader;
+mod texture_cache;
+mod buffer_cache;
+mod vulkan_debug_draw;
+mod skybox;
+mod vulkan_gradient;
+pub mod allocator;
+pub mod pipeline_cache;
+
+pub mod shader_source;
+pub mod material;
+pub mod reflection;
+pub mod rasterization;
+pub mod debug_draw;
+pub mod render_settings;
\ No newline at end of file
@@ -0,0 +1,137 @@
+use std::ptr::copy_nonoverlapping as memcpy;
+use vulkanalia::{
+    prelude::v1_0::*
+};
+use anyhow::Result;
+use crate::renderer::{
+    image::create_image,
+    buffers_tools::*,
+    allocator::{MemoryAllocator, Allocation},
+};
+
+/// Format of the offscreen color target, read back as RGBA bytes.
+pub const OFFSCREEN_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+
+//================================================
+// Offscreen images
+//================================================
+
+/// Creates the images a headless renderer resolves into instead of the swapchain images.
+pub unsafe fn create_offscreen_images(
+    device: &Device,
+    allocator: &MemoryAllocator,
+    extent: vk::Extent2D,
+    count: usize)
+-> Result<(Vec<vk::Image>, Vec<Allocation>)> {
+    let mut images = Vec::<vk::Image>::default();
+    let mut images_allocation = Vec::<Allocation>::default();
+    for _ in 0..count {
+        let (image, image_allocation) = create_image(
+            device,
+            allocator,
+            extent.width,
+            extent.height,
+            1,
+            vk::SampleCountFlags::_1,
+            OFFSCREEN_FORMAT,
+            vk::ImageTiling::OPTIMAL,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        images.push(image);
+        images_allocation.push(image_allocation);
+    }
+
+    Ok((images, images_allocation))
+}
+
+//================================================
+// Readback
+//================================================
+
+/// Copies a rendered offscreen image (in `TRANSFER_SRC_OPTIMAL` layout) into tightly packed RGBA bytes.
+pub unsafe fn read_offscreen_image(
+    device: &Device,
+    allocator: &MemoryAllocator,
+    command_pool: vk::CommandPool,
+    graphics_queue: vk::Queue,
+    image: vk::Image,
+    extent: vk::Extent2D,
+) -> Result<Vec<u8>> {
+    let size = (extent.width * extent.height * 4) as u64;
+
+    let (staging_buffer, staging_buffer_allocation) = create_buffer(
+        device,
+        allocator,
+        size,
+        vk::BufferUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )?;
+
+    let command_buffer = begin_single_time_commands(device, command_pool)?;
+
+    // Make the render pass writes visible to the transfer.
+    let subresource_range = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(0)
+        .level_count(1)
+        .base_array_layer(0)
+        .layer_count(1);
+
+    let barrier = vk::ImageMemoryBarrier::builder()
+        .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+        .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(subresource_range)
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_access_mask(vk::AccessFlags::TRANSFER_READ);
+
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[barrier],
+    );
+
+    let subresource = vk::ImageSubresourceLayers::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .mip_level(0)
+        .base_array_layer(0)
+        .layer_count(1);
+
+    let region = vk::BufferImageCopy::builder()
+        .buffer_offset(0)
+        .buffer_row_length(0)
+        .buffer_image_height(0)
+        .image_subresource(subresource)
+        .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+        .image_extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        });
+
+    device.cmd_copy_image_to_buffer(
+        command_buffer,
+        image,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        staging_buffer,
+        &[region],
+    );
+
+    end_single_time_commands(device, command_buffer, command_pool, graphics_queue)?;
+
+    // Copy (staging)
+    let mut data = vec![0u8; size as usize];
+    memcpy(staging_buffer_allocation.mapped()?, data.as_mut_ptr(), data.len());
+
+    // Cleanup
+    destroy_buffer(device, allocator, staging_buffer, &staging_buffer_allocation);
+
+    Ok(data)
+}
@@ -1,54 +1,58 @@
 use crate::renderer::{
-    vertex::Vertex, 
+    vertex::{Vertex, InstanceData, vertex_attribute_descriptions},
     depthbuffers::get_depth_format,
+    material::BlendMode,
+    rasterization::{Rasterization, Topology},
 };
 use vulkanalia::prelude::v1_0::*;
 
-use anyhow::{Result, anyhow};
-
-use super::vulkan_shader::ShaderType;
+use anyhow::Result;
 
 //================================================
 // Pipeline
 //================================================
 
-pub fn create_pipeline_type(device: &Device,
-    shader_type: ShaderType,
-    swapchain_extent: vk::Extent2D,
-    msaa_samples: vk::SampleCountFlags,
+/// Layout shared by the pipelines of a material, with the push constants of `push_constant_range`.
+pub unsafe fn create_pipeline_layout(
+    device: &Device,
     descriptor_set_layout: vk::DescriptorSetLayout,
-    render_pass: vk::RenderPass) -> Result<(vk::Pipeline, vk::PipelineLayout)> 
-{
-    match shader_type {
-        ShaderType::Textured =>{
-            let vert = include_bytes!("../../shaders/texture_vert.spv");
-            let frag = include_bytes!("../../shaders/texture_frag.spv");
-            unsafe {
-                create_pipeline(device, vert, frag, swapchain_extent, msaa_samples, descriptor_set_layout, render_pass)
-            }
-        },
-        ShaderType::Untextured => {
-            let vert = include_bytes!("../../shaders/only_color_vert.spv");
-            let frag = include_bytes!("../../shaders/only_color_frag.spv");
-            unsafe {
-                create_pipeline(device, vert, frag, swapchain_extent, msaa_samples, descriptor_set_layout, render_pass)
-            }
-        },
-    }
+    push_constant_range: vk::PushConstantRange)
+-> Result<vk::PipelineLayout> {
+    let set_layouts = &[descriptor_set_layout];
+    let push_constant_ranges = &[push_constant_range];
+    let layout_info = vk::PipelineLayoutCreateInfo::builder()
+        .set_layouts(set_layouts)
+        .push_constant_ranges(push_constant_ranges);
+
+    Ok(device.create_pipeline_layout(&layout_info, None)?)
 }
 
+/// Creates a pipeline drawing `Vertex` and `InstanceData` as `topology` primitives.
+/// Transparent blend modes test the depth without writing it.
+// every fixed function state a material can change is an argument
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn create_pipeline(
     device: &Device, 
-    vert: &[u8],
-    frag: &[u8],
-    swapchain_extent: vk::Extent2D,
+    pipeline_cache: vk::PipelineCache,
+    vert: &[u32],
+    frag: &[u32],
     msaa_samples: vk::SampleCountFlags,
-    descriptor_set_layout: vk::DescriptorSetLayout,
+    pipeline_layout: vk::PipelineLayout,
+    blend_mode: BlendMode,
+    rasterization: Rasterization,
+    depth_test: bool,
+    topology: Topology,
     render_pass: vk::RenderPass)
--> Result<(vk::Pipeline, vk::PipelineLayout)> {
+-> Result<vk::Pipeline> {
     
-    let vert_shader_module = create_shader_module(device, &vert[..])?;
-    let frag_shader_module = create_shader_module(device, &frag[..])?;
+    let vert_shader_module = create_shader_module(device, vert)?;
+    let frag_shader_module = match create_shader_module(device, frag) {
+        Ok(module) => module,
+        Err(e) => {
+            device.destroy_shader_module(vert_shader_module, None);
+            return Err(e);
+        }
+    };
 
     let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
         .stage(vk::ShaderStageFlags::VERTEX)
@@ -61,45 +65,39 @@ pub unsafe fn create_pipeline(
         .name(b"main\0");
 
     // Vertex Input State
-    let binding_descriptions = &[Vertex::binding_description()];
-    let attribute_descriptions = Vertex::attribute_descriptions();
+    let binding_descriptions = &[Vertex::binding_description(), InstanceData::binding_description()];
+    let attribute_descriptions = vertex_attribute_descriptions();
     let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
         .vertex_binding_descriptions(binding_descriptions)
         .vertex_attribute_descriptions(&attribute_descriptions);
 
     // Input Assembly State
     let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
-        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
-        .primitive_restart_enable(false);
-
-    // Viewport State
-    let viewport = vk::Viewport::builder()
-        .x(0.0)
-        .y(0.0)
-        .width(swapchain_extent.width as f32)
-        .height(swapchain_extent.height as f32)
-        .min_depth(0.0)
-        .max_depth(1.0);
-
-    let scissor = vk::Rect2D::builder()
-        .offset(vk::Offset2D { x: 0, y: 0 })
-        .extent(swapchain_extent);
-
-    let viewports = &[viewport];
-    let scissors = &[scissor];
+        .topology(topology.vk_topology())
+        .primitive_restart_enable(topology.is_strip());
+
+    // Viewport State, set when recording so resizing keeps the pipelines
     let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
-        .viewports(viewports)
-        .scissors(scissors);
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(dynamic_states);
 
     // Rasterization State
+    let depth_bias = rasterization.depth_bias().unwrap_or_default();
     let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
         .depth_clamp_enable(false)
         .rasterizer_discard_enable(false)
-        .polygon_mode(vk::PolygonMode::FILL)
+        .polygon_mode(rasterization.vk_polygon_mode())
         .line_width(1.0)
-        .cull_mode(vk::CullModeFlags::BACK)
-        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
-        .depth_bias_enable(false);
+        .cull_mode(rasterization.vk_cull_mode())
+        .front_face(rasterization.vk_front_face())
+        .depth_bias_enable(rasterization.depth_bias().is_some())
+        .depth_bias_constant_factor(depth_bias.constant_factor)
+        .depth_bias_slope_factor(depth_bias.slope_factor)
+        .depth_bias_clamp(depth_bias.clamp);
 
     // Multisample State
     let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
@@ -110,16 +108,14 @@ pub unsafe fn create_pipeline(
 
     // Depth Stencil State
     let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
-        .depth_test_enable(true)
-        .depth_write_enable(true)
+        .depth_test_enable(depth_test)
+        .depth_write_enable(depth_test && !blend_mode.is_transparent())
         .depth_compare_op(vk::CompareOp::LESS)
         .depth_bounds_test_enable(false)
         .stencil_test_enable(false);
 
     // Color Blend State
-    let attachment = vk::PipelineColorBlendAttachmentState::builder()
-        .color_write_mask(vk::ColorComponentFlags::all())
-        .blend_enable(false);
+    let attachment = color_blend_attachment(blend_mode);
 
     let attachments = &[attachment];
     let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
@@ -128,21 +124,6 @@ pub unsafe fn create_pipeline(
         .attachments(attachments)
         .blend_constants([0.0, 0.0, 0.0, 0.0]);
 
-    //push constant
-    let vert_push_constant_range = vk::PushConstantRange::builder()
-        .stage_flags(vk::ShaderStageFlags::VERTEX)
-        .offset(0)
-        .size(128 /* 2 glm::mat4 */);
-
-    // Layout
-    let set_layouts = &[descriptor_set_layout];
-    let push_constant_ranges = &[vert_push_constant_range];
-    let layout_info = vk::PipelineLayoutCreateInfo::builder()
-        .set_layouts(set_layouts)
-        .push_constant_ranges(push_constant_ranges);
-
-    let pipeline_layout = device.create_pipeline_layout(&layout_info, None)?;
-
     // Create
     let stages = &[vert_stage, frag_stage];
     let info = vk::GraphicsPipelineCreateInfo::builder()
@@ -154,35 +135,48 @@ pub unsafe fn create_pipeline(
         .multisample_state(&multisample_state)
         .depth_stencil_state(&depth_stencil_state)
         .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
         .layout(pipeline_layout)
         .render_pass(render_pass)
         .subpass(0);
 
-    let pipeline = device
-        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
-        .0;
+    let pipeline = device.create_graphics_pipelines(pipeline_cache, &[info], None);
 
     // Cleanup
     device.destroy_shader_module(vert_shader_module, None);
     device.destroy_shader_module(frag_shader_module, None);
 
-    Ok((pipeline, pipeline_layout))
+    Ok(pipeline?.0)
 }
 
-unsafe fn create_shader_module(device: &Device, bytecode: &[u8]) -> Result<vk::ShaderModule> {
-    let bytecode = Vec::<u8>::from(bytecode);
-    let (prefix, code, suffix) = bytecode.align_to::<u32>();
-    if !prefix.is_empty() || !suffix.is_empty() {
-        return Err(anyhow!("Shader bytecode is not properly aligned."));
-    }
-
+unsafe fn create_shader_module(device: &Device, code: &[u32]) -> Result<vk::ShaderModule> {
     let info = vk::ShaderModuleCreateInfo::builder()
-        .code_size(bytecode.len())
+        .code_size(std::mem::size_of_val(code))
         .code(code);
 
     Ok(device.create_shader_module(&info, None)?)
 }
 
+/// Color blending of a blend mode, the alpha of the target accumulates like its color.
+fn color_blend_attachment(blend_mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
+    let (src, dst) = match blend_mode {
+        BlendMode::Opaque | BlendMode::Cutout => (vk::BlendFactor::ONE, vk::BlendFactor::ZERO),
+        BlendMode::Alpha => (vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+        BlendMode::Additive => (vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE),
+        BlendMode::Premultiplied => (vk::BlendFactor::ONE, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+    };
+    vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(blend_mode.is_transparent())
+        .src_color_blend_factor(src)
+        .dst_color_blend_factor(dst)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD)
+        .build()
+}
+
 //================================================
 // Render_pass
 //================================================
@@ -192,7 +186,8 @@ pub unsafe fn create_render_pass(
     device: &Device, 
     physical_device: vk::PhysicalDevice,
     swapchain_format: vk::Format,
-    msaa_samples: vk::SampleCountFlags
+    msaa_samples: vk::SampleCountFlags,
+    final_layout: vk::ImageLayout,
 ) -> Result<vk::RenderPass> {
     // Attachments
     let color_attachment = vk::AttachmentDescription::builder()
@@ -223,7 +218,7 @@ pub unsafe fn create_render_pass(
         .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
         .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
         .initial_layout(vk::ImageLayout::UNDEFINED)
-        .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+        .final_layout(final_layout);
 
     // Subpasses
     let color_attachment_ref = vk::AttachmentReference::builder()
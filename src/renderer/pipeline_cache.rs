@@ -0,0 +1,133 @@
+use {
+    std::{fs, path::{Path, PathBuf}, sync::Arc},
+    vulkanalia::prelude::v1_0::*,
+    anyhow::Result,
+    log::*,
+};
+
+/// File the pipeline cache is kept in between runs.
+pub const PIPELINE_CACHE_PATH: &str = "pipeline_cache.bin";
+
+//================================================
+// Header
+//================================================
+
+/// Header written by the driver at the start of the pipeline cache data, see
+/// `VkPipelineCacheHeaderVersionOne`. Its fields are stored least significant byte first.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PipelineCacheHeader {
+    vendor_id: u32,
+    device_id: u32,
+    uuid: [u8; vk::UUID_SIZE],
+}
+
+impl PipelineCacheHeader {
+    const SIZE: usize = 16 + vk::UUID_SIZE;
+
+    pub fn new(vendor_id: u32, device_id: u32, uuid: [u8; vk::UUID_SIZE]) -> Self {
+        Self { vendor_id, device_id, uuid }
+    }
+
+    pub fn from_properties(properties: &vk::PhysicalDeviceProperties) -> Self {
+        Self::new(properties.vendor_id, properties.device_id, properties.pipeline_cache_uuid.into())
+    }
+
+    /// Reads the header of cache data, `None` if it is truncated or of an unknown version.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::SIZE {
+            return None;
+        }
+        let word = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let (length, version) = (word(0), word(4));
+        if (length as usize) < Self::SIZE || version != vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32 {
+            return None;
+        }
+        let mut uuid = [0; vk::UUID_SIZE];
+        uuid.copy_from_slice(&data[16..Self::SIZE]);
+        Some(Self::new(word(8), word(12), uuid))
+    }
+
+    /// Bytes of the header, as the driver writes them.
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::SIZE);
+        bytes.extend_from_slice(&(Self::SIZE as u32).to_le_bytes());
+        bytes.extend_from_slice(&(vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.vendor_id.to_le_bytes());
+        bytes.extend_from_slice(&self.device_id.to_le_bytes());
+        bytes.extend_from_slice(&self.uuid);
+        bytes
+    }
+
+    pub fn vendor_id(&self) -> u32 { self.vendor_id }
+    pub fn device_id(&self) -> u32 { self.device_id }
+    pub fn uuid(&self) -> [u8; vk::UUID_SIZE] { self.uuid }
+}
+
+/// Whether cache data was written for the device with `expected` header, data from another
+/// device or driver version must not be given to `create_pipeline_cache`.
+pub fn is_cache_compatible(data: &[u8], expected: &PipelineCacheHeader) -> bool {
+    PipelineCacheHeader::parse(data).as_ref() == Some(expected)
+}
+
+//================================================
+// Pipeline cache
+//================================================
+
+/// Pipeline cache of the device, loaded from a file at startup and written back by `save`.
+#[derive(Debug, Clone)]
+pub struct PipelineCache {
+    device: Arc<Device>,
+    cache: vk::PipelineCache,
+    path: PathBuf,
+    is_allocated: bool,
+}
+
+impl PipelineCache {
+    /// Starts from the content of `path` when it was written for this device, from an empty cache otherwise.
+    pub fn new(device: Arc<Device>, instance: &Instance, physical_device: vk::PhysicalDevice, path: &Path) -> Result<Self> {
+        let expected = unsafe { PipelineCacheHeader::from_properties(&instance.get_physical_device_properties(physical_device)) };
+        let data = match fs::read(path) {
+            Ok(data) if is_cache_compatible(&data, &expected) => data,
+            Ok(_) => {
+                info!("Ignoring pipeline cache `{}` written for another device or driver.", path.display());
+                vec![]
+            },
+            Err(_) => vec![],
+        };
+
+        let info = vk::PipelineCacheCreateInfo::builder()
+            .initial_data(&data);
+        let cache = unsafe { device.create_pipeline_cache(&info, None)? };
+        Ok(Self { device, cache, path: path.to_path_buf(), is_allocated: true })
+    }
+
+    /// Writes the cache to its file, through a temporary file so an interrupted write leaves the previous cache.
+    pub fn save(&self) -> Result<()> {
+        if !self.is_allocated {
+            return Ok(());
+        }
+        let data = unsafe { self.device.get_pipeline_cache_data(self.cache)? };
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, data)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    pub fn clean(&mut self) {
+        if self.is_allocated {
+            unsafe {
+                self.device.destroy_pipeline_cache(self.cache, None);
+            }
+            self.is_allocated = false;
+        }
+    }
+
+    pub fn cache(&self) -> vk::PipelineCache { self.cache }
+    pub fn path(&self) -> &Path { &self.path }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        self.clean();
+    }
+}
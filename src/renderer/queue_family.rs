@@ -9,10 +9,16 @@ use thiserror::Error;
 use crate::renderer::{
     instance::{VALIDATION_ENABLED, VALIDATION_LAYER},
     swapchain::SwapchainSupport,
+    rasterization::RasterizationFeatures,
 };
 
 const DEVICE_EXTENSIONS: &[vk::ExtensionName] = &[vk::KHR_SWAPCHAIN_EXTENSION.name];
 
+/// Device extensions needed for the given surface, a null surface means headless rendering.
+fn device_extensions(surface: vk::SurfaceKHR) -> &'static [vk::ExtensionName] {
+    if surface.is_null() { &[] } else { DEVICE_EXTENSIONS }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct QueueFamilyIndices {
     pub graphics: u32,
@@ -34,6 +40,10 @@ impl QueueFamilyIndices {
             .map(|i| i as u32);
 
         let mut present = None;
+        if surface.is_null() {
+            //headless: nothing is presented, the graphics queue stands in for the present one
+            present = graphics;
+        } else {
             for (index, _) in properties.iter().enumerate() {
                 if instance.get_physical_device_surface_support_khr(
                     physical_device,
@@ -44,6 +54,7 @@ impl QueueFamilyIndices {
                     break;
                 }
             }
+        }
 
             if let (Some(graphics), Some(present)) = (graphics, present) {
                 Ok(Self { graphics, present })
@@ -82,11 +93,13 @@ pub unsafe fn check_physical_device(
     physical_device: vk::PhysicalDevice,
 ) -> Result<()> {
     QueueFamilyIndices::get(instance, surface, physical_device)?;
-    check_physical_device_extensions(instance, physical_device)?;
+    check_physical_device_extensions(instance, surface, physical_device)?;
 
-    let support = SwapchainSupport::get(instance, surface, physical_device)?;
-    if support.formats.is_empty() || support.present_modes.is_empty() {
-        return Err(anyhow!(SuitabilityError("Insufficient swapchain support.")));
+    if !surface.is_null() {
+        let support = SwapchainSupport::get(instance, surface, physical_device)?;
+        if support.formats.is_empty() || support.present_modes.is_empty() {
+            return Err(anyhow!(SuitabilityError("Insufficient swapchain support.")));
+        }
     }
 
     let features = instance.get_physical_device_features(physical_device);
@@ -99,6 +112,7 @@ pub unsafe fn check_physical_device(
 
 unsafe fn check_physical_device_extensions(
     instance: &Instance,
+    surface: vk::SurfaceKHR,
     physical_device: vk::PhysicalDevice,
 ) -> Result<()> {
     let extensions = instance
@@ -106,7 +120,7 @@ unsafe fn check_physical_device_extensions(
         .iter()
         .map(|e| e.extension_name)
         .collect::<HashSet<_>>();
-    if DEVICE_EXTENSIONS.iter().all(|e| extensions.contains(e)) {
+    if device_extensions(surface).iter().all(|e| extensions.contains(e)) {
         Ok(())
     } else {
         Err(anyhow!(SuitabilityError("Missing required device extensions.")))
@@ -140,7 +154,7 @@ pub unsafe fn create_logical_device(
     instance: &Instance, 
     surface: vk::SurfaceKHR, 
     physical_device: vk::PhysicalDevice)
- -> Result<(Device, vk::Queue, vk::Queue)> {
+ -> Result<(Device, vk::Queue, vk::Queue, RasterizationFeatures)> {
     // Queue Create Infos
     let indices = QueueFamilyIndices::get(instance, surface, physical_device)?;
 
@@ -165,12 +179,15 @@ pub unsafe fn create_logical_device(
         vec![]
     };
 
-    // Features
+    // Features, the rasterization ones are optional
+    let rasterization_features = RasterizationFeatures::supported(&instance.get_physical_device_features(physical_device));
     let features = vk::PhysicalDeviceFeatures::builder()
         .sampler_anisotropy(true)
-        .sample_rate_shading(true);
+        .sample_rate_shading(true)
+        .fill_mode_non_solid(rasterization_features.fill_mode_non_solid)
+        .depth_bias_clamp(rasterization_features.depth_bias_clamp);
         
-    let extensions = DEVICE_EXTENSIONS
+    let extensions = device_extensions(surface)
                     .iter()
                     .map(|n| n.as_ptr())
                     .collect::<Vec<_>>();
@@ -188,5 +205,5 @@ pub unsafe fn create_logical_device(
     let graphics_queue = device.get_device_queue(indices.graphics, 0);
     let present_queue = device.get_device_queue(indices.present, 0);
 
-    Ok((device, graphics_queue, present_queue))
+    Ok((device, graphics_queue, present_queue, rasterization_features))
 }
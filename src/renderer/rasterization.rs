@@ -0,0 +1,146 @@
+use {
+    vulkanalia::prelude::v1_0::*,
+    anyhow::{Result, anyhow},
+};
+
+/// How the triangles of a material are filled.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PolygonMode {
+    #[default]
+    Fill,
+    /// Only the edges, needs the `fillModeNonSolid` device feature.
+    Wireframe,
+    /// Only the vertices, needs the `fillModeNonSolid` device feature.
+    Points,
+}
+
+/// Faces which are not drawn.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum CullMode {
+    None,
+    Front,
+    #[default]
+    Back,
+}
+
+/// Winding of the front faces as seen on screen.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum FrontFace {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Primitives assembled from the indices of an object.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Topology {
+    #[default]
+    TriangleList,
+    TriangleStrip,
+    TriangleFan,
+    LineList,
+    LineStrip,
+    PointList,
+}
+
+impl Topology {
+    pub fn vk_topology(&self) -> vk::PrimitiveTopology {
+        match self {
+            Topology::TriangleList => vk::PrimitiveTopology::TRIANGLE_LIST,
+            Topology::TriangleStrip => vk::PrimitiveTopology::TRIANGLE_STRIP,
+            Topology::TriangleFan => vk::PrimitiveTopology::TRIANGLE_FAN,
+            Topology::LineList => vk::PrimitiveTopology::LINE_LIST,
+            Topology::LineStrip => vk::PrimitiveTopology::LINE_STRIP,
+            Topology::PointList => vk::PrimitiveTopology::POINT_LIST,
+        }
+    }
+
+    /// Strips and fans restart at the index `u32::MAX`, to draw several of them at once.
+    pub fn is_strip(&self) -> bool {
+        matches!(self, Topology::TriangleStrip | Topology::TriangleFan | Topology::LineStrip)
+    }
+}
+
+/// Offset added to the depth of the fragments, to draw decals or outlines over coplanar faces.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct DepthBias {
+    pub constant_factor: f32,
+    pub slope_factor: f32,
+    /// Maximum offset, `0.0` for none. Other values need the `depthBiasClamp` device feature.
+    pub clamp: f32,
+}
+
+/// Optional device features used by the rasterization options, enabled when the device has them.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct RasterizationFeatures {
+    pub fill_mode_non_solid: bool,
+    pub depth_bias_clamp: bool,
+}
+
+impl RasterizationFeatures {
+    pub fn supported(features: &vk::PhysicalDeviceFeatures) -> Self {
+        Self {
+            fill_mode_non_solid: features.fill_mode_non_solid == vk::TRUE,
+            depth_bias_clamp: features.depth_bias_clamp == vk::TRUE,
+        }
+    }
+}
+
+/// Rasterization options of a material, the default fills the front faces only.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Rasterization {
+    polygon_mode: PolygonMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    depth_bias: Option<DepthBias>,
+}
+
+impl Rasterization {
+    /// Both sides of the faces are drawn, for flat geometry.
+    pub fn double_sided() -> Self {
+        Self { cull_mode: CullMode::None, ..Self::default() }
+    }
+
+    /// Checks the device has the features needed by the options.
+    pub fn check(&self, features: &RasterizationFeatures) -> Result<()> {
+        if self.polygon_mode != PolygonMode::Fill && !features.fill_mode_non_solid {
+            return Err(anyhow!("The device does not support the {:?} polygon mode.", self.polygon_mode));
+        }
+        if self.depth_bias.is_some_and(|b| b.clamp != 0.0) && !features.depth_bias_clamp {
+            return Err(anyhow!("The device does not support depth bias clamping."));
+        }
+        Ok(())
+    }
+
+    pub fn vk_polygon_mode(&self) -> vk::PolygonMode {
+        match self.polygon_mode {
+            PolygonMode::Fill => vk::PolygonMode::FILL,
+            PolygonMode::Wireframe => vk::PolygonMode::LINE,
+            PolygonMode::Points => vk::PolygonMode::POINT,
+        }
+    }
+
+    pub fn vk_cull_mode(&self) -> vk::CullModeFlags {
+        match self.cull_mode {
+            CullMode::None => vk::CullModeFlags::NONE,
+            CullMode::Front => vk::CullModeFlags::FRONT,
+            CullMode::Back => vk::CullModeFlags::BACK,
+        }
+    }
+
+    pub fn vk_front_face(&self) -> vk::FrontFace {
+        match self.front_face {
+            FrontFace::CounterClockwise => vk::FrontFace::COUNTER_CLOCKWISE,
+            FrontFace::Clockwise => vk::FrontFace::CLOCKWISE,
+        }
+    }
+
+    pub fn polygon_mode(&self) -> PolygonMode { self.polygon_mode }
+    pub fn cull_mode(&self) -> CullMode { self.cull_mode }
+    pub fn front_face(&self) -> FrontFace { self.front_face }
+    pub fn depth_bias(&self) -> Option<DepthBias> { self.depth_bias }
+    pub fn set_polygon_mode(&mut self, polygon_mode: PolygonMode) { self.polygon_mode = polygon_mode; }
+    pub fn set_cull_mode(&mut self, cull_mode: CullMode) { self.cull_mode = cull_mode; }
+    pub fn set_front_face(&mut self, front_face: FrontFace) { self.front_face = front_face; }
+    pub fn set_depth_bias(&mut self, depth_bias: Option<DepthBias>) { self.depth_bias = depth_bias; }
+}
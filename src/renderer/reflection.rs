@@ -0,0 +1,471 @@
+use {
+    std::collections::HashMap,
+    vulkanalia::prelude::v1_0::*,
+    anyhow::{Result, anyhow},
+};
+
+// Opcodes, storage classes and decorations of the SPIR-V specification used by the reflection.
+const OP_NAME: u32 = 5;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLER: u32 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+const STORAGE_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_INPUT: u32 = 1;
+const STORAGE_UNIFORM: u32 = 2;
+const STORAGE_FUNCTION: u32 = 7;
+const STORAGE_PUSH_CONSTANT: u32 = 9;
+const STORAGE_STORAGE_BUFFER: u32 = 12;
+
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BUILT_IN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const DIM_BUFFER: u32 = 5;
+const DIM_SUBPASS_DATA: u32 = 6;
+
+/// A descriptor used by a shader.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReflectedBinding {
+    name: String,
+    set: u32,
+    binding: u32,
+    descriptor_type: vk::DescriptorType,
+    count: u32,
+    stages: vk::ShaderStageFlags,
+    /// Size in bytes of uniform and storage blocks, without their runtime array.
+    block_size: Option<u64>,
+}
+
+impl ReflectedBinding {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn set(&self) -> u32 { self.set }
+    pub fn binding(&self) -> u32 { self.binding }
+    pub fn descriptor_type(&self) -> vk::DescriptorType { self.descriptor_type }
+    pub fn count(&self) -> u32 { self.count }
+    pub fn stages(&self) -> vk::ShaderStageFlags { self.stages }
+    pub fn block_size(&self) -> Option<u64> { self.block_size }
+}
+
+/// A vertex attribute read by a vertex shader.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VertexInput {
+    name: String,
+    location: u32,
+    format: vk::Format,
+}
+
+impl VertexInput {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn location(&self) -> u32 { self.location }
+    pub fn format(&self) -> vk::Format { self.format }
+}
+
+/// Interface of one or several shader stages, as declared in their SPIR-V modules.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ShaderReflection {
+    stages: vk::ShaderStageFlags,
+    bindings: Vec<ReflectedBinding>,
+    /// Offset, size and stages of the push constant block.
+    push_constants: Option<(u32, u32, vk::ShaderStageFlags)>,
+    vertex_inputs: Vec<VertexInput>,
+}
+
+impl ShaderReflection {
+    /// Adds the interface of another stage, a binding declared with different types by both is an error.
+    pub fn merge(&mut self, other: &ShaderReflection) -> Result<()> {
+        for binding in other.bindings.iter() {
+            match self.bindings.iter_mut().find(|b| b.set == binding.set && b.binding == binding.binding) {
+                Some(b) if b.descriptor_type != binding.descriptor_type || b.count != binding.count => {
+                    return Err(anyhow!("Binding {} of set {} is a {:?} in one stage and a {:?} in another.",
+                        binding.binding, binding.set, b.descriptor_type, binding.descriptor_type));
+                },
+                Some(b) => {
+                    b.stages |= binding.stages;
+                    b.block_size = b.block_size.max(binding.block_size);
+                },
+                None => self.bindings.push(binding.clone()),
+            }
+        }
+        self.bindings.sort_by_key(|b| (b.set, b.binding));
+
+        self.push_constants = match (self.push_constants, other.push_constants) {
+            (Some((offset, size, stages)), Some((other_offset, other_size, other_stages))) => {
+                let start = offset.min(other_offset);
+                let end = (offset + size).max(other_offset + other_size);
+                Some((start, end - start, stages | other_stages))
+            },
+            (push_constants, None) | (None, push_constants) => push_constants,
+        };
+        self.vertex_inputs.extend(other.vertex_inputs.iter().cloned());
+        self.stages |= other.stages;
+        Ok(())
+    }
+
+    /// Whether the fragment shader declares push constants, the range of every stage reaching `end`.
+    pub fn fragment_push_constants_reach(&self, end: u32) -> bool {
+        self.push_constants.is_some_and(|(offset, size, stages)|
+            stages.contains(vk::ShaderStageFlags::FRAGMENT) && offset + size >= end)
+    }
+
+    /// One range covering the push constants of every stage.
+    pub fn push_constant_range(&self) -> Option<vk::PushConstantRange> {
+        self.push_constants.map(|(offset, size, stages)| vk::PushConstantRange::builder()
+            .stage_flags(stages)
+            .offset(offset)
+            .size(size)
+            .build())
+    }
+
+    /// Number of descriptors of each type in one descriptor set.
+    pub fn descriptor_counts(&self) -> Vec<(vk::DescriptorType, u32)> {
+        let mut counts: Vec<(vk::DescriptorType, u32)> = vec![];
+        for binding in self.bindings.iter() {
+            match counts.iter_mut().find(|(t, _)| *t == binding.descriptor_type) {
+                Some((_, count)) => *count += binding.count,
+                None => counts.push((binding.descriptor_type, binding.count)),
+            }
+        }
+        counts
+    }
+
+    /// Checks every vertex input of the shader is provided by an attribute of the same format.
+    pub fn check_vertex_inputs(&self, attributes: &[vk::VertexInputAttributeDescription]) -> Result<()> {
+        for input in self.vertex_inputs.iter() {
+            match attributes.iter().find(|a| a.location == input.location) {
+                Some(attribute) if attribute.format == input.format => {},
+                Some(attribute) => return Err(anyhow!("Vertex input `{}` at location {} is a {:?}, the vertex attribute is a {:?}.",
+                    input.name, input.location, input.format, attribute.format)),
+                None => return Err(anyhow!("Vertex input `{}` at location {} has no vertex attribute.",
+                    input.name, input.location)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether both interfaces need the same descriptor set and pipeline layouts.
+    pub fn same_layout(&self, other: &ShaderReflection) -> bool {
+        self.bindings == other.bindings && self.push_constants == other.push_constants
+    }
+
+    pub fn stages(&self) -> vk::ShaderStageFlags { self.stages }
+    pub fn bindings(&self) -> &[ReflectedBinding] { &self.bindings }
+    pub fn vertex_inputs(&self) -> &[VertexInput] { &self.vertex_inputs }
+}
+
+//================================================
+// SPIR-V parsing
+//================================================
+
+#[derive(Clone, Debug)]
+enum Type {
+    Bool,
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, count: u32 },
+    Image { dim: u32, sampled: u32 },
+    Sampler,
+    SampledImage,
+    Array { element: u32, length: u32 },
+    RuntimeArray { element: u32 },
+    Struct { members: Vec<u32> },
+    Pointer { storage: u32, pointee: u32 },
+}
+
+#[derive(Default, Debug)]
+struct Decorations {
+    set: Option<u32>,
+    binding: Option<u32>,
+    location: Option<u32>,
+    array_stride: Option<u32>,
+    built_in: bool,
+    buffer_block: bool,
+}
+
+#[derive(Default, Debug)]
+struct Module {
+    stage: vk::ShaderStageFlags,
+    names: HashMap<u32, String>,
+    decorations: HashMap<u32, Decorations>,
+    /// Offset and matrix stride of the struct members.
+    members: HashMap<(u32, u32), (Option<u32>, Option<u32>)>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    /// Module scope variables with their pointer type and storage class.
+    variables: Vec<(u32, u32, u32)>,
+}
+
+/// Reads the descriptors, push constants and vertex inputs of a SPIR-V module.
+pub fn reflect(spirv: &[u32]) -> Result<ShaderReflection> {
+    let module = parse(spirv)?;
+    let mut reflection = ShaderReflection { stages: module.stage, ..Default::default() };
+
+    for &(id, pointer, storage) in module.variables.iter() {
+        let name = module.name(id);
+        let pointee = match module.types.get(&pointer) {
+            Some(Type::Pointer { pointee, .. }) => *pointee,
+            _ => return Err(anyhow!("Variable `{}` is not a pointer.", name)),
+        };
+        let decorations = module.decorations.get(&id);
+        match storage {
+            STORAGE_UNIFORM_CONSTANT | STORAGE_UNIFORM | STORAGE_STORAGE_BUFFER => {
+                let (element, count) = module.array_element(pointee);
+                let descriptor_type = module.descriptor_type(element, storage)
+                    .ok_or_else(|| anyhow!("Unsupported type for the descriptor `{}`.", name))?;
+                let block_size = match descriptor_type {
+                    vk::DescriptorType::UNIFORM_BUFFER | vk::DescriptorType::STORAGE_BUFFER => module.size(element, None),
+                    _ => None,
+                };
+                reflection.bindings.push(ReflectedBinding {
+                    name,
+                    set: decorations.and_then(|d| d.set).unwrap_or(0),
+                    binding: decorations.and_then(|d| d.binding)
+                        .ok_or_else(|| anyhow!("Descriptor `{}` has no binding.", module.name(id)))?,
+                    descriptor_type,
+                    count,
+                    stages: module.stage,
+                    block_size,
+                });
+            },
+            STORAGE_PUSH_CONSTANT => {
+                let members = match module.types.get(&pointee) {
+                    Some(Type::Struct { members }) => members.len() as u32,
+                    _ => return Err(anyhow!("Push constant `{}` is not a block.", name)),
+                };
+                let offset = (0..members)
+                    .filter_map(|m| module.members.get(&(pointee, m)).and_then(|(offset, _)| *offset))
+                    .min()
+                    .unwrap_or(0);
+                let size = module.size(pointee, None)
+                    .ok_or_else(|| anyhow!("Unable to compute the size of the push constant `{}`.", name))?;
+                reflection.push_constants = Some((offset, size as u32 - offset, module.stage));
+            },
+            STORAGE_INPUT if module.stage == vk::ShaderStageFlags::VERTEX => {
+                if decorations.is_some_and(|d| d.built_in) || module.is_built_in_block(pointee) {
+                    continue;
+                }
+                let location = decorations.and_then(|d| d.location)
+                    .ok_or_else(|| anyhow!("Vertex input `{}` has no location.", name))?;
+                for (i, format) in module.input_formats(pointee)?.into_iter().enumerate() {
+                    reflection.vertex_inputs.push(VertexInput { name: name.clone(), location: location + i as u32, format });
+                }
+            },
+            _ => {},
+        }
+    }
+    reflection.bindings.sort_by_key(|b| (b.set, b.binding));
+    reflection.vertex_inputs.sort_by_key(|i| i.location);
+    Ok(reflection)
+}
+
+fn parse(spirv: &[u32]) -> Result<Module> {
+    if spirv.len() < 5 || spirv[0] != 0x0723_0203 {
+        return Err(anyhow!("Not a SPIR-V module."));
+    }
+    let mut module = Module::default();
+    let mut position = 5;
+    while position < spirv.len() {
+        let word_count = (spirv[position] >> 16) as usize;
+        let opcode = spirv[position] & 0xffff;
+        if word_count == 0 || position + word_count > spirv.len() {
+            return Err(anyhow!("Truncated SPIR-V instruction at word {}.", position));
+        }
+        let operands = &spirv[position + 1..position + word_count];
+        position += word_count;
+
+        let operand = |i: usize| operands.get(i).copied()
+            .ok_or_else(|| anyhow!("Missing operand of SPIR-V instruction {}.", opcode));
+        match opcode {
+            OP_NAME => { module.names.insert(operand(0)?, literal_string(&operands[1..])); },
+            OP_ENTRY_POINT if module.stage.is_empty() => module.stage = execution_model_stage(operand(0)?)?,
+            OP_TYPE_BOOL => { module.types.insert(operand(0)?, Type::Bool); },
+            OP_TYPE_INT => { module.types.insert(operand(0)?, Type::Int { width: operand(1)?, signed: operand(2)? == 1 }); },
+            OP_TYPE_FLOAT => { module.types.insert(operand(0)?, Type::Float { width: operand(1)? }); },
+            OP_TYPE_VECTOR => { module.types.insert(operand(0)?, Type::Vector { component: operand(1)?, count: operand(2)? }); },
+            OP_TYPE_MATRIX => { module.types.insert(operand(0)?, Type::Matrix { column: operand(1)?, count: operand(2)? }); },
+            OP_TYPE_IMAGE => { module.types.insert(operand(0)?, Type::Image { dim: operand(2)?, sampled: operand(6)? }); },
+            OP_TYPE_SAMPLER => { module.types.insert(operand(0)?, Type::Sampler); },
+            OP_TYPE_SAMPLED_IMAGE => { module.types.insert(operand(0)?, Type::SampledImage); },
+            OP_TYPE_ARRAY => { module.types.insert(operand(0)?, Type::Array { element: operand(1)?, length: operand(2)? }); },
+            OP_TYPE_RUNTIME_ARRAY => { module.types.insert(operand(0)?, Type::RuntimeArray { element: operand(1)? }); },
+            OP_TYPE_STRUCT => { module.types.insert(operand(0)?, Type::Struct { members: operands[1..].to_vec() }); },
+            OP_TYPE_POINTER => { module.types.insert(operand(0)?, Type::Pointer { storage: operand(1)?, pointee: operand(2)? }); },
+            OP_CONSTANT => { module.constants.insert(operand(1)?, operand(2)?); },
+            OP_VARIABLE => {
+                // variables of functions are declared after the types, in the function bodies
+                let storage = operand(2)?;
+                if storage != STORAGE_FUNCTION {
+                    module.variables.push((operand(1)?, operand(0)?, storage));
+                }
+            },
+            OP_DECORATE => {
+                let decorations = module.decorations.entry(operand(0)?).or_default();
+                match operand(1)? {
+                    DECORATION_BUFFER_BLOCK => decorations.buffer_block = true,
+                    DECORATION_ARRAY_STRIDE => decorations.array_stride = Some(operand(2)?),
+                    DECORATION_BUILT_IN => decorations.built_in = true,
+                    DECORATION_LOCATION => decorations.location = Some(operand(2)?),
+                    DECORATION_BINDING => decorations.binding = Some(operand(2)?),
+                    DECORATION_DESCRIPTOR_SET => decorations.set = Some(operand(2)?),
+                    _ => {},
+                }
+            },
+            OP_MEMBER_DECORATE => {
+                let member = module.members.entry((operand(0)?, operand(1)?)).or_default();
+                match operand(2)? {
+                    DECORATION_OFFSET => member.0 = Some(operand(3)?),
+                    DECORATION_MATRIX_STRIDE => member.1 = Some(operand(3)?),
+                    DECORATION_BUILT_IN => {
+                        module.decorations.entry(operand(0)?).or_default().built_in = true;
+                    },
+                    _ => {},
+                }
+            },
+            _ => {},
+        }
+    }
+    if module.stage.is_empty() {
+        return Err(anyhow!("SPIR-V module has no entry point."));
+    }
+    Ok(module)
+}
+
+fn literal_string(words: &[u32]) -> String {
+    let bytes = words.iter()
+        .flat_map(|w| w.to_le_bytes())
+        .take_while(|b| *b != 0)
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn execution_model_stage(model: u32) -> Result<vk::ShaderStageFlags> {
+    Ok(match model {
+        0 => vk::ShaderStageFlags::VERTEX,
+        1 => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+        2 => vk::ShaderStageFlags::TESSELLATION_EVALUATION,
+        3 => vk::ShaderStageFlags::GEOMETRY,
+        4 => vk::ShaderStageFlags::FRAGMENT,
+        5 => vk::ShaderStageFlags::COMPUTE,
+        _ => return Err(anyhow!("Unsupported SPIR-V execution model {}.", model)),
+    })
+}
+
+impl Module {
+    fn name(&self, id: u32) -> String {
+        self.names.get(&id).cloned().unwrap_or_else(|| format!("%{}", id))
+    }
+
+    /// Element type and number of descriptors of a descriptor array, the type itself otherwise.
+    fn array_element(&self, id: u32) -> (u32, u32) {
+        match self.types.get(&id) {
+            Some(Type::Array { element, length }) => (*element, self.constants.get(length).copied().unwrap_or(1)),
+            _ => (id, 1),
+        }
+    }
+
+    fn descriptor_type(&self, id: u32, storage: u32) -> Option<vk::DescriptorType> {
+        let buffer_block = self.decorations.get(&id).is_some_and(|d| d.buffer_block);
+        Some(match (storage, self.types.get(&id)?) {
+            (STORAGE_UNIFORM, Type::Struct { .. }) if buffer_block => vk::DescriptorType::STORAGE_BUFFER,
+            (STORAGE_UNIFORM, Type::Struct { .. }) => vk::DescriptorType::UNIFORM_BUFFER,
+            (STORAGE_STORAGE_BUFFER, Type::Struct { .. }) => vk::DescriptorType::STORAGE_BUFFER,
+            (STORAGE_UNIFORM_CONSTANT, Type::Sampler) => vk::DescriptorType::SAMPLER,
+            (STORAGE_UNIFORM_CONSTANT, Type::SampledImage) => vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { dim: DIM_SUBPASS_DATA, .. }) => vk::DescriptorType::INPUT_ATTACHMENT,
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { dim: DIM_BUFFER, sampled: 2 }) => vk::DescriptorType::STORAGE_TEXEL_BUFFER,
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { dim: DIM_BUFFER, .. }) => vk::DescriptorType::UNIFORM_TEXEL_BUFFER,
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { sampled: 2, .. }) => vk::DescriptorType::STORAGE_IMAGE,
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { .. }) => vk::DescriptorType::SAMPLED_IMAGE,
+            _ => return None,
+        })
+    }
+
+    /// `gl_PerVertex` and the other blocks of built-in variables.
+    fn is_built_in_block(&self, id: u32) -> bool {
+        self.decorations.get(&id).is_some_and(|d| d.built_in)
+    }
+
+    /// Size in bytes following the explicit layout of the module, `None` for opaque and runtime sized types.
+    fn size(&self, id: u32, matrix_stride: Option<u32>) -> Option<u64> {
+        Some(match self.types.get(&id)? {
+            Type::Bool => 4,
+            Type::Int { width, .. } | Type::Float { width } => *width as u64 / 8,
+            Type::Vector { component, count } => self.size(*component, None)? * *count as u64,
+            Type::Matrix { column, count } => {
+                let stride = match matrix_stride {
+                    Some(stride) => stride as u64,
+                    None => self.size(*column, None)?,
+                };
+                stride * *count as u64
+            },
+            Type::Array { element, length } => {
+                let stride = match self.decorations.get(&id).and_then(|d| d.array_stride) {
+                    Some(stride) => stride as u64,
+                    None => self.size(*element, matrix_stride)?,
+                };
+                stride * *self.constants.get(length)? as u64
+            },
+            Type::Struct { members } => {
+                let mut size = 0;
+                for (i, member) in members.iter().enumerate() {
+                    let (offset, stride) = self.members.get(&(id, i as u32)).copied().unwrap_or_default();
+                    // a runtime array ends the block without adding to its size
+                    if let Some(Type::RuntimeArray { .. }) = self.types.get(member) {
+                        continue;
+                    }
+                    size = size.max(offset.unwrap_or(0) as u64 + self.size(*member, stride)?);
+                }
+                size
+            },
+            _ => return None,
+        })
+    }
+
+    /// Format of each location taken by a vertex input.
+    fn input_formats(&self, id: u32) -> Result<Vec<vk::Format>> {
+        let unsupported = || anyhow!("Unsupported vertex input type {:?}.", self.types.get(&id));
+        Ok(match self.types.get(&id).ok_or_else(unsupported)? {
+            Type::Matrix { column, count } => vec![self.input_format(*column).ok_or_else(unsupported)?; *count as usize],
+            Type::Array { element, length } => {
+                let formats = self.input_formats(*element)?;
+                let length = *self.constants.get(length).ok_or_else(unsupported)? as usize;
+                formats.iter().cycle().take(formats.len() * length).copied().collect()
+            },
+            _ => vec![self.input_format(id).ok_or_else(unsupported)?],
+        })
+    }
+
+    fn input_format(&self, id: u32) -> Option<vk::Format> {
+        let (component, count) = match self.types.get(&id)? {
+            Type::Vector { component, count } => (*component, *count),
+            _ => (id, 1),
+        };
+        let formats = match self.types.get(&component)? {
+            Type::Float { width: 32 } => [vk::Format::R32_SFLOAT, vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32_SFLOAT, vk::Format::R32G32B32A32_SFLOAT],
+            Type::Int { width: 32, signed: true } => [vk::Format::R32_SINT, vk::Format::R32G32_SINT, vk::Format::R32G32B32_SINT, vk::Format::R32G32B32A32_SINT],
+            Type::Int { width: 32, signed: false } => [vk::Format::R32_UINT, vk::Format::R32G32_UINT, vk::Format::R32G32B32_UINT, vk::Format::R32G32B32A32_UINT],
+            _ => return None,
+        };
+        formats.get(count as usize - 1).copied()
+    }
+}
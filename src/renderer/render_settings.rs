@@ -0,0 +1,91 @@
+use {
+    vulkanalia::prelude::v1_0::*,
+    nalgebra_glm as glm,
+};
+
+/// Clear color of the renderer until changed with `RenderSettings::set_clear_color`.
+pub fn default_clear_color() -> glm::Vec4 {
+    glm::vec4(0.0, 0.1, 0.1, 1.0)
+}
+
+/// Vertical gradient drawn over the clear color, behind the skybox and the objects.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Gradient {
+    top: glm::Vec4,
+    bottom: glm::Vec4,
+}
+
+impl Gradient {
+    pub fn new(top: glm::Vec4, bottom: glm::Vec4) -> Self {
+        Self { top, bottom }
+    }
+
+    /// Color at `height`, from 0 at the top of the screen to 1 at the bottom.
+    pub fn color_at(&self, height: f32) -> glm::Vec4 {
+        glm::lerp(&self.top, &self.bottom, height.clamp(0.0, 1.0))
+    }
+
+    pub fn uniform(&self) -> GradientUniform {
+        GradientUniform { top: self.top, bottom: self.bottom }
+    }
+
+    pub fn top(&self) -> glm::Vec4 { self.top }
+    pub fn bottom(&self) -> glm::Vec4 { self.bottom }
+    pub fn set_top(&mut self, top: glm::Vec4) { self.top = top; }
+    pub fn set_bottom(&mut self, bottom: glm::Vec4) { self.bottom = bottom; }
+}
+
+/// std140 layout of the gradient uniform.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct GradientUniform {
+    top: glm::Vec4,
+    bottom: glm::Vec4,
+}
+
+impl GradientUniform {
+    /// Bytes of the uniform, written field by field.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.top.iter()
+            .chain(self.bottom.iter())
+            .flat_map(|c| c.to_ne_bytes())
+            .collect()
+    }
+}
+
+/// How the render target is cleared and its background drawn, read at the start of every frame.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RenderSettings {
+    clear_color: glm::Vec4,
+    clear_depth: f32,
+    gradient: Option<Gradient>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self { clear_color: default_clear_color(), clear_depth: 1.0, gradient: None }
+    }
+}
+
+impl RenderSettings {
+    /// Color and depth the attachments of the render pass are cleared with.
+    pub fn clear_values(&self) -> [vk::ClearValue; 2] {
+        let color = vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: self.clear_color.into(),
+            },
+        };
+        let depth = vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue { depth: self.clear_depth, stencil: 0 },
+        };
+        [color, depth]
+    }
+
+    pub fn clear_color(&self) -> glm::Vec4 { self.clear_color }
+    pub fn set_clear_color(&mut self, clear_color: glm::Vec4) { self.clear_color = clear_color; }
+    pub fn clear_depth(&self) -> f32 { self.clear_depth }
+    /// Clamped to [0, 1], the objects farther than the clear depth are not drawn.
+    pub fn set_clear_depth(&mut self, clear_depth: f32) { self.clear_depth = clear_depth.clamp(0.0, 1.0); }
+    pub fn gradient(&self) -> Option<Gradient> { self.gradient }
+    pub fn set_gradient(&mut self, gradient: Option<Gradient>) { self.gradient = gradient; }
+}
@@ -7,10 +7,18 @@ use {
     },
     winit::window::Window,
     anyhow::{anyhow, Result},
-    crate::object::Object,
+    crate::{object::Object, tools::{texture::Texture, cubemap::Cubemap}},
     super::{
         core::*,
         commandbuffers::*, 
+        offscreen::read_offscreen_image,
+        camera::Camera,
+        light::Lights,
+        vulkan_model::ObjectId,
+        allocator::MemoryStats,
+        material::{Material, MaterialId},
+        debug_draw::DebugDraw,
+        render_settings::RenderSettings,
     },
 };
 pub const MAX_FRAMES_IN_FLIGHT: usize = 2;
@@ -24,6 +32,10 @@ pub struct Renderer {
     frame: usize,
     resized: bool,
     start: Instant,
+    camera: Camera,
+    lights: Lights,
+    debug_draw: DebugDraw,
+    settings: RenderSettings,
 }
 
 impl Renderer {
@@ -41,6 +53,34 @@ impl Renderer {
                 frame: 0, 
                 resized: false, 
                 start: Instant::now(), 
+                camera: Camera::default(),
+                lights: Lights::default(),
+                debug_draw: DebugDraw::default(),
+                settings: RenderSettings::default(),
+            };
+            Ok(renderer)
+        }
+    }
+
+    /// Creates a renderer without window, drawing into an offscreen image of `width` x `height`.
+    pub fn create_headless(width: u32, height: u32) -> Result<Self> {
+        unsafe {
+            let loader = LibloadingLoader::new(LIBRARY)?;
+            let entry = Entry::new(loader).map_err(|b| anyhow!("{}", b))?;
+            let extent = vk::Extent2D { width, height };
+            let core = Core::new_headless(&entry, extent)?;
+
+            let renderer = Self { 
+                _entry: entry,
+                device: core.device(),
+                core,
+                frame: 0, 
+                resized: false, 
+                start: Instant::now(), 
+                camera: Camera::default(),
+                lights: Lights::default(),
+                debug_draw: DebugDraw::default(),
+                settings: RenderSettings::default(),
             };
             Ok(renderer)
         }
@@ -48,17 +88,22 @@ impl Renderer {
 
     /// Renders a frame for our Vulkan app.
     pub fn render(&mut self, window: &Window) -> Result<()> {
+        if self.core.headless() {
+            return Err(anyhow!("A headless renderer has no swapchain, use render_to_texture."));
+        }
         unsafe {
             let in_flight_fence = self.core.in_flight_fences()[self.frame];
 
             self.device
-                .wait_for_fences(&[in_flight_fence], true, u64::max_value())?;
+                .wait_for_fences(&[in_flight_fence], true, u64::MAX)?;
+            self.core.release_retired_models();
+            self.core.reload_changed_shaders()?;
 
             let image_index = self
                 .device
                 .acquire_next_image_khr(
                     self.core.swapchain(),
-                    u64::max_value(),
+                    u64::MAX,
                     self.core.image_available_semaphores()[self.frame],
                     vk::Fence::null(),
                 )?
@@ -67,12 +112,12 @@ impl Renderer {
             let image_in_flight = self.core.images_in_flight()[image_index];
             if !image_in_flight.is_null() {
                 self.device
-                    .wait_for_fences(&[image_in_flight], true, u64::max_value())?;
+                    .wait_for_fences(&[image_in_flight], true, u64::MAX)?;
             }
 
             self.core.images_in_flight_mut()[image_index] = in_flight_fence;
 
-            update_command_buffer(&self.device, &mut self.core, image_index, &self.start)?;
+            update_command_buffer(&self.device, &mut self.core, image_index, &self.start, &self.camera, &self.lights, &self.debug_draw, &self.settings)?;
 
             let wait_semaphores = &[self.core.image_available_semaphores()[self.frame]];
             let wait_stages = &[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
@@ -107,18 +152,135 @@ impl Renderer {
                 
 
             self.frame = (self.frame + 1) % MAX_FRAMES_IN_FLIGHT;
+            self.core.end_frame();
+            self.debug_draw.clear();
 
             Ok(())
         }
     } 
 
-    pub fn add_object(&mut self, obj: &dyn Object) -> Result<()> {
+    /// Renders a frame of a headless renderer and reads the resolved image back as RGBA pixels.
+    pub fn render_to_texture(&mut self) -> Result<Texture> {
+        if !self.core.headless() {
+            return Err(anyhow!("Only a headless renderer can render to a texture."));
+        }
         unsafe {
-            self.core.add_object(obj)?;        
-            Ok(())
+            let in_flight_fence = self.core.in_flight_fences()[self.frame];
+
+            self.device
+                .wait_for_fences(&[in_flight_fence], true, u64::MAX)?;
+            self.core.release_retired_models();
+            self.core.reload_changed_shaders()?;
+
+            // A headless core owns a single offscreen image.
+            let image_index = 0;
+            update_command_buffer(&self.device, &mut self.core, image_index, &self.start, &self.camera, &self.lights, &self.debug_draw, &self.settings)?;
+
+            let command_buffers = &[self.core.command_buffers()[image_index]];
+            let submit_info = vk::SubmitInfo::builder()
+                .command_buffers(command_buffers);
+
+            self.device.reset_fences(&[in_flight_fence])?;
+
+            self.device
+                .queue_submit(self.core.graphics_queue(), &[submit_info], in_flight_fence)?;
+
+            self.device
+                .wait_for_fences(&[in_flight_fence], true, u64::MAX)?;
+
+            let extent = self.core.swapchain_extent();
+            let data = read_offscreen_image(
+                &self.device,
+                self.core.allocator(),
+                self.core.command_pool(),
+                self.core.graphics_queue(),
+                self.core.swapchain_images()[image_index],
+                extent)?;
+
+            self.frame = (self.frame + 1) % MAX_FRAMES_IN_FLIGHT;
+            self.core.end_frame();
+            self.debug_draw.clear();
+
+            Ok(Texture::from_rgba(extent.width, extent.height, data))
+        }
+    }
+
+    /// Renders a frame of a headless renderer and writes it to a PNG file.
+    pub fn render_to_png(&mut self, path: &str) -> Result<()> {
+        self.render_to_texture()?.save_png(path)
+    }
+
+    /// Uploads an object to the GPU, the returned id stays valid until the object is removed.
+    /// Its model index is the number of objects added before it, removed ones included.
+    pub fn add_object(&mut self, obj: &dyn Object) -> Result<ObjectId> {
+        unsafe {
+            self.core.add_object(obj)
+        }
+    }
+
+    /// Removes an object, its GPU resources are freed once no frame in flight uses them anymore.
+    pub fn remove_object(&mut self, id: ObjectId) -> Result<()> {
+        self.core.remove_object(id)
+    }
+
+    /// Replaces an object in place, keeping its id and model index.
+    pub fn replace_object(&mut self, id: ObjectId, obj: &dyn Object) -> Result<()> {
+        unsafe {
+            self.core.replace_object(id, obj)
+        }
+    }
+
+    /// Registers a material, its shaders are compiled and its pipeline built right away.
+    pub fn register_material(&mut self, material: Material) -> Result<MaterialId> {
+        unsafe {
+            self.core.register_material(material)
         }
     }
 
+    /// Sets the content of the `Uniform` at `binding` of a material, shared by every object drawn with it.
+    /// `data` holds the bytes in the std140 layout the shader expects, padding included.
+    pub fn set_material_uniform(&mut self, material: MaterialId, binding: u32, data: &[u8]) -> Result<()> {
+        self.core.set_material_uniform(material, binding, data)
+    }
+
+    /// Draws only the edges of the triangles, needs the `fillModeNonSolid` device feature.
+    pub fn set_wireframe(&mut self, wireframe: bool) -> Result<()> {
+        unsafe {
+            self.core.set_wireframe(wireframe)
+        }
+    }
+
+    pub fn wireframe(&self) -> bool { self.core.wireframe() }
+
+    /// Draws the cubemap behind every object instead of the clear color.
+    pub fn set_skybox(&mut self, cubemap: &Cubemap) -> Result<()> {
+        unsafe {
+            self.core.set_skybox(cubemap)
+        }
+    }
+
+    pub fn remove_skybox(&mut self) -> Result<()> {
+        unsafe {
+            self.core.remove_skybox()
+        }
+    }
+
+    /// Primitives drawn over the next frame only.
+    pub fn debug_draw(&mut self) -> &mut DebugDraw { &mut self.debug_draw }
+
+    pub fn camera(&self) -> &Camera { &self.camera }
+    pub fn camera_mut(&mut self) -> &mut Camera { &mut self.camera }
+    pub fn set_camera(&mut self, camera: Camera) { self.camera = camera; }
+    pub fn lights(&self) -> &Lights { &self.lights }
+    pub fn lights_mut(&mut self) -> &mut Lights { &mut self.lights }
+    pub fn set_lights(&mut self, lights: Lights) { self.lights = lights; }
+    /// Clear values and background, applied from the next frame.
+    pub fn render_settings(&self) -> &RenderSettings { &self.settings }
+    pub fn render_settings_mut(&mut self) -> &mut RenderSettings { &mut self.settings }
+    pub fn set_render_settings(&mut self, settings: RenderSettings) { self.settings = settings; }
+    /// Device memory used by the buffers and images of the renderer.
+    pub fn memory_stats(&self) -> MemoryStats { self.core.memory_stats() }
+
     pub fn clean(&mut self) {
         self.core.clean();
     }
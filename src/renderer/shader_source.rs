@@ -0,0 +1,162 @@
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+        time::{Duration, Instant, SystemTime},
+    },
+    anyhow::{Result, anyhow},
+};
+
+/// First word of every SPIR-V module.
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+//================================================
+// Shader source
+//================================================
+
+/// Where the SPIR-V code of a shader stage comes from.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ShaderSource {
+    /// SPIR-V (`.spv`) or GLSL file, read again each time the pipeline is built.
+    File(PathBuf),
+    /// SPIR-V built in the binary.
+    Embedded(&'static [u8]),
+}
+
+impl ShaderSource {
+    /// The file when it exists, so shaders can be edited next to the binary, the embedded code otherwise.
+    pub fn file_or_embedded(path: &str, spirv: &'static [u8]) -> Self {
+        if Path::new(path).is_file() {
+            ShaderSource::File(PathBuf::from(path))
+        } else {
+            ShaderSource::Embedded(spirv)
+        }
+    }
+
+    /// Reads the SPIR-V code, compiling GLSL files.
+    pub fn spirv(&self, stage: ShaderStage) -> Result<Vec<u32>> {
+        match self {
+            ShaderSource::Embedded(bytes) => spirv_from_bytes(bytes),
+            ShaderSource::File(path) => load_spirv(path, stage)
+                .map_err(|e| anyhow!("Unable to load shader `{}`: {}", path.display(), e)),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ShaderSource::File(path) => Some(path),
+            ShaderSource::Embedded(_) => None,
+        }
+    }
+}
+
+/// Loads a `.spv` file as is, any other file is compiled as GLSL.
+pub fn load_spirv(path: &Path, stage: ShaderStage) -> Result<Vec<u32>> {
+    if path.extension().is_some_and(|e| e == "spv") {
+        spirv_from_bytes(&fs::read(path)?)
+    } else {
+        compile_glsl(&fs::read_to_string(path)?, stage)
+    }
+}
+
+/// Checks the magic number and copies the words of a SPIR-V module.
+pub fn spirv_from_bytes(bytes: &[u8]) -> Result<Vec<u32>> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(anyhow!("SPIR-V size must be a multiple of 4 bytes."));
+    }
+    let words = bytes.chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect::<Vec<_>>();
+    if words.first() != Some(&SPIRV_MAGIC) {
+        return Err(anyhow!("Not a SPIR-V module."));
+    }
+    Ok(words)
+}
+
+//================================================
+// GLSL
+//================================================
+
+/// Compiles a GLSL shader to SPIR-V, errors hold the compiler messages with their source lines.
+pub fn compile_glsl(source: &str, stage: ShaderStage) -> Result<Vec<u32>> {
+    let naga_stage = match stage {
+        ShaderStage::Vertex => naga::ShaderStage::Vertex,
+        ShaderStage::Fragment => naga::ShaderStage::Fragment,
+    };
+    let module = naga::front::glsl::Frontend::default()
+        .parse(&naga::front::glsl::Options::from(naga_stage), source)
+        .map_err(|e| anyhow!("{}", e.emit_to_string(source)))?;
+
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|e| anyhow!("{}", e.emit_to_string_with_path(source, "glsl")))?;
+
+    let options = naga::back::spv::Options {
+        lang_version: (1, 0),
+        ..Default::default()
+    };
+    let pipeline_options = naga::back::spv::PipelineOptions {
+        shader_stage: naga_stage,
+        entry_point: "main".to_string(),
+    };
+    Ok(naga::back::spv::write_vec(&module, &info, &options, Some(&pipeline_options))?)
+}
+
+//================================================
+// File watcher
+//================================================
+
+/// Polls the modification time of files, at most once per `interval`.
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(interval: Duration) -> Self {
+        Self { files: HashMap::new(), interval, last_poll: None }
+    }
+
+    pub fn watch(&mut self, path: &Path) {
+        if !self.files.contains_key(path) {
+            self.files.insert(path.to_path_buf(), modified(path));
+        }
+    }
+
+    /// Files modified, created or removed since the previous poll.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        if self.last_poll.is_some_and(|last| last.elapsed() < self.interval) {
+            return vec![];
+        }
+        self.last_poll = Some(Instant::now());
+
+        let mut changed = vec![];
+        for (path, time) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *time {
+                *time = current;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(250))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
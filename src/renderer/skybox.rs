@@ -0,0 +1,98 @@
+use {
+    std::{cell::RefCell, rc::Rc, sync::Arc},
+    anyhow::Result,
+    nalgebra_glm as glm,
+    vulkanalia::prelude::v1_0::*,
+    crate::tools::cubemap::Cubemap,
+    super::{
+        vulkan_shader::{VulkanShader, ShaderContainer},
+        vulkan_texture::VulkanTexture,
+        vertexbuffers::VertexBuffer,
+        allocator::MemoryAllocator,
+        uniformbuffers::UniformBuffer,
+        descriptor::Descriptor,
+        vertex::Vertex,
+        material::MaterialId,
+    },
+};
+
+/// Cube around the camera sampling a cubemap, drawn before the objects without depth test
+/// so that they all cover it.
+#[derive(Clone, Debug)]
+pub struct Skybox {
+    shader: Rc<RefCell<VulkanShader>>,
+    texture: VulkanTexture,
+    cube: VertexBuffer,
+    descriptor: Descriptor,
+}
+
+impl Skybox {
+    // uploads the cubemap and the cube, bound with the shared uniforms
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, shader_container: &mut ShaderContainer,
+        instance: &Instance,
+        physical_device: vk::PhysicalDevice,
+        command_pool: vk::CommandPool,
+        graphics_queue: vk::Queue,
+        swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass,
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer,
+        cubemap: &Cubemap) -> Result<Self>
+    {
+        let shader = shader_container.get(MaterialId::SKYBOX, swapchain_images, msaa_samples, render_pass)?;
+        let texture = VulkanTexture::cubemap(device.clone(), allocator.clone(), instance, physical_device, command_pool, graphics_queue, cubemap)?;
+        let (vertices, indices) = cube();
+        let cube = VertexBuffer::new(device.clone(), allocator, command_pool, graphics_queue, &vertices, &indices)?;
+        let descriptor = {
+            let shader = shader.borrow();
+            Descriptor::new(device,
+                swapchain_images,
+                shader.descriptor_set_layout(),
+                &shader.reflection().descriptor_counts(),
+                shader.material().bindings(),
+                &shader.uniform_buffers(camera_buffer, lights_buffer),
+                Some(&texture))?
+        };
+        Ok(Self { shader, texture, cube, descriptor })
+    }
+
+    pub fn reload_swapchain(&mut self,
+        swapchain_images: &[vk::Image],
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<()>
+    {
+        let shader = self.shader.borrow();
+        self.descriptor.reload_swapchain(swapchain_images,
+            shader.descriptor_set_layout(),
+            &shader.reflection().descriptor_counts(),
+            shader.material().bindings(),
+            &shader.uniform_buffers(camera_buffer, lights_buffer),
+            Some(&self.texture))
+    }
+
+    pub fn shader(&self) -> Rc<RefCell<VulkanShader>> { self.shader.clone() }
+    pub fn cube(&self) -> &VertexBuffer { &self.cube }
+    pub fn descriptor(&self) -> &Descriptor { &self.descriptor }
+}
+
+/// Corners of the unit cube and its 12 triangles, seen from the inside as the material is double sided.
+fn cube() -> (Vec<Vertex>, Vec<u32>) {
+    let vertices = (0..8)
+        .map(|i| glm::vec3(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 }))
+        .map(|pos| Vertex::new(pos, glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0)))
+        .collect();
+    let indices = vec![
+        0, 2, 1, 1, 2, 3, // -Z
+        4, 5, 6, 5, 7, 6, // +Z
+        0, 1, 4, 1, 5, 4, // -Y
+        2, 6, 3, 3, 6, 7, // +Y
+        0, 4, 2, 2, 4, 6, // -X
+        1, 3, 5, 3, 7, 5, // +X
+    ];
+    (vertices, indices)
+}
@@ -94,7 +94,7 @@ pub unsafe fn create_swapchain(
 
 pub unsafe fn create_swapchain_image_views(
     device: &Device, 
-    swapchain_images: &Vec<vk::Image>, 
+    swapchain_images: &[vk::Image], 
     swapchain_format: vk::Format) 
 -> Result<Vec<vk::ImageView>> {
     let swapchain_image_views = swapchain_images.iter()
@@ -131,7 +131,7 @@ fn get_swapchain_extent(
     window: &Window,
     capabilities: vk::SurfaceCapabilitiesKHR,
 ) -> vk::Extent2D {
-    if capabilities.current_extent.width != u32::max_value() {
+    if capabilities.current_extent.width != u32::MAX {
         capabilities.current_extent
     } else {
         let size = window.inner_size();
@@ -11,8 +11,11 @@ use crate::renderer::{
 // Sync objects
 //================================================
 
-pub unsafe fn create_sync_objects(device: &Device, swapchain_images: &Vec<vk::Image>)
--> Result<(Vec<vk::Fence>, Vec<vk::Semaphore>, Vec<vk::Semaphore>, Vec<vk::Fence>)> {
+/// In flight fences, render finished and image available semaphores, images in flight fences.
+pub type SyncObjects = (Vec<vk::Fence>, Vec<vk::Semaphore>, Vec<vk::Semaphore>, Vec<vk::Fence>);
+
+pub unsafe fn create_sync_objects(device: &Device, swapchain_images: &[vk::Image])
+-> Result<SyncObjects> {
     let semaphore_info = vk::SemaphoreCreateInfo::builder();
     let fence_info = vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
 
@@ -0,0 +1,54 @@
+use {
+    std::{collections::HashMap, sync::{Arc, Weak}},
+    vulkanalia::prelude::v1_0::*,
+    anyhow::Result,
+    crate::tools::texture::Texture,
+    super::{vulkan_texture::VulkanTexture, allocator::MemoryAllocator},
+};
+
+/// GPU textures shared by every object using the same `Arc<Texture>`.
+///
+/// Objects own the `Arc<VulkanTexture>`, the GPU image is freed with its last user;
+/// the cache only keeps the source texture alive so its address can not be reused as a key.
+#[derive(Clone, Default)]
+pub struct TextureCache {
+    textures: HashMap<usize, (Arc<Texture>, Weak<VulkanTexture>)>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the GPU texture of `texture`, uploading it on first use.
+    // uploading a missing texture needs everything `VulkanTexture::new` takes
+    #[allow(clippy::too_many_arguments)]
+    pub fn get(&mut self, device: Arc<Device>, allocator: Arc<MemoryAllocator>, instance: &Instance,
+        physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool,
+        graphics_queue: vk::Queue, texture: Arc<Texture>) -> Result<Arc<VulkanTexture>>
+    {
+        self.release_unused();
+        let key = Arc::as_ptr(&texture) as usize;
+        if let Some(vulkan_texture) = self.textures.get(&key).and_then(|(_, weak)| weak.upgrade()) {
+            return Ok(vulkan_texture);
+        }
+
+        let vulkan_texture = Arc::new(VulkanTexture::new(device, allocator, instance, physical_device, command_pool, graphics_queue, texture.clone())?);
+        self.textures.insert(key, (texture, Arc::downgrade(&vulkan_texture)));
+        Ok(vulkan_texture)
+    }
+
+    /// Forgets the textures whose last user went away.
+    pub fn release_unused(&mut self) {
+        self.textures.retain(|_, (_, weak)| weak.strong_count() > 0);
+    }
+
+    /// Number of textures currently uploaded.
+    pub fn len(&self) -> usize {
+        self.textures.values().filter(|(_, weak)| weak.strong_count() > 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,37 +1,41 @@
 use {
     std::{
-        mem::size_of,
+        mem::{size_of, offset_of},
         ptr::copy_nonoverlapping as memcpy,
         sync::Arc,
     },
     vulkanalia::prelude::v1_0::*,
-    anyhow::Result,
+    anyhow::{Result, anyhow},
     nalgebra_glm as glm,
     super::{
-        buffers_tools::create_buffer,
+        buffers_tools::{create_buffer, destroy_buffer},
+        allocator::{MemoryAllocator, Allocation},
     },
 };
 
+/// One host visible uniform buffer of `size` bytes per swapchain image.
 #[derive(Debug, Clone)]
 pub struct UniformBuffer {
     device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
     uniform_buffers: Vec<vk::Buffer>,
-    uniform_buffers_memory: Vec<vk::DeviceMemory>,
-    fn_update_matrix: fn(usize, f32, u32, u32) -> MatrixShaderObject,
+    uniform_buffers_allocation: Vec<Allocation>,
+    size: u64,
     is_allocated: bool,
 }
 
 impl UniformBuffer {
-    pub fn new(device: Arc<Device>, instance: &Instance, physical_device: vk::PhysicalDevice, swapchain_images: &Vec<vk::Image>) -> Result<Self> {
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, swapchain_images: &[vk::Image], size: u64) -> Result<Self> {
         unsafe {
             let (uniform_buffers, 
-                uniform_buffers_memory,
-            ) = create_uniform_buffers(instance, &device, physical_device, swapchain_images)?;
+                uniform_buffers_allocation,
+            ) = create_uniform_buffers(&device, &allocator, swapchain_images, size)?;
             Ok(UniformBuffer {
                 device,
+                allocator,
                 uniform_buffers,
-                uniform_buffers_memory,
-                fn_update_matrix: |_, _, _, _| -> MatrixShaderObject { MatrixShaderObject::identity() },
+                uniform_buffers_allocation,
+                size,
                 is_allocated: true,
             })
         }
@@ -42,54 +46,51 @@ impl UniformBuffer {
         if self.is_allocated {
             unsafe {
             self.uniform_buffers.iter()
-                .for_each(|b| self.device.destroy_buffer(*b, None));
-            self.uniform_buffers_memory.iter()
-                .for_each(|m| self.device.free_memory(*m, None));
+                .zip(self.uniform_buffers_allocation.iter())
+                .for_each(|(b, a)| destroy_buffer(&self.device, &self.allocator, *b, a));
             }
             self.is_allocated = false;
         }
     }
 
-    pub fn reload_swapchain_models(&mut self, instance: &Instance, physical_device: vk::PhysicalDevice, swapchain_images: &Vec<vk::Image>) -> Result<()> {
+    pub fn reload_swapchain(&mut self, swapchain_images: &[vk::Image]) -> Result<()> {
         self.clean();
         unsafe {
-            let (uniform_buffers, uniform_buffers_memory) = create_uniform_buffers(instance, &self.device, physical_device, swapchain_images)?;
+            let (uniform_buffers, uniform_buffers_allocation) = create_uniform_buffers(&self.device, &self.allocator, swapchain_images, self.size)?;
             self.uniform_buffers = uniform_buffers;
-            self.uniform_buffers_memory = uniform_buffers_memory;
+            self.uniform_buffers_allocation = uniform_buffers_allocation;
             self.is_allocated = true;
         }
         Ok(())
     }
 
-    pub fn set_fn_update_matrix(&mut self, f: fn(usize, f32, u32, u32) -> MatrixShaderObject) {
-        self.fn_update_matrix = f;
+    /// Writes `data` in the buffer used by the given swapchain image.
+    pub unsafe fn update<T>(&self, image_index: usize, data: &T) -> Result<()> {
+        if size_of::<T>() as u64 > self.size {
+            return Err(anyhow!("{} bytes do not fit in a uniform buffer of {} bytes.", size_of::<T>(), self.size));
+        }
+        let memory = self.uniform_buffers_allocation[image_index].mapped()?;
+        memcpy(data, memory.cast(), 1);
+        Ok(())
     }
 
-    pub unsafe fn update_matrix(&self, device: &Device,
-        swapchain_extent: vk::Extent2D, image_index: usize,
-        model_index: usize, elapsed_time: f32) -> Result<PushConstantObject> {
-        let fn_update = self.fn_update_matrix;
-        let matrix = fn_update(model_index, elapsed_time, swapchain_extent.width, swapchain_extent.height);
-        let ubo = matrix.to_ubo();
-        let pcs = matrix.to_push_constant();
-
-        let memory = device.map_memory(
-            self.uniform_buffers_memory[image_index],
-            0,
-            size_of::<UniformBufferObject>() as u64,
-            vk::MemoryMapFlags::empty(),
-        )?;
-        
-        memcpy(&ubo, memory.cast(), 1);
-        
-        device.unmap_memory(self.uniform_buffers_memory[image_index]);    
-    
-        Ok(pcs)
+    /// Writes raw bytes at the start of the buffer used by the given swapchain image.
+    pub unsafe fn update_bytes(&self, image_index: usize, data: &[u8]) -> Result<()> {
+        if data.len() as u64 > self.size {
+            return Err(anyhow!("{} bytes do not fit in a uniform buffer of {} bytes.", data.len(), self.size));
+        }
+        let memory = self.uniform_buffers_allocation[image_index].mapped()?;
+        memcpy(data.as_ptr(), memory, data.len());
+        Ok(())
     }
 
     pub fn uniform_buffers(&self) -> &Vec<vk::Buffer> {
        &self.uniform_buffers 
     }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
 }
 
 impl Drop for UniformBuffer {
@@ -98,67 +99,49 @@ impl Drop for UniformBuffer {
     }
 }
 
+/// Camera matrices and position, written once per frame and shared by every object.
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
-pub struct MatrixShaderObject {
+pub struct CameraUniform {
     view: glm::Mat4,
-    model: glm::Mat4,
     proj: glm::Mat4,
+    position: glm::Vec4,
 }
-impl MatrixShaderObject {
-    pub fn construct(view: glm::Mat4, model: glm::Mat4, proj: glm::Mat4) -> Self {
-        Self { view, model, proj }
-    }
-    fn identity() -> Self {
-        Self { view: glm::identity(), model: glm::identity(), proj: glm::identity(), }
-    }
-    pub fn view(&self) -> glm::Mat4 { self.view }
-    pub fn model(&self) -> glm::Mat4 { self.model }
-    pub fn proj(&self) -> glm::Mat4 { self.proj }
 
-    pub fn to_ubo(&self) -> UniformBufferObject {
-        UniformBufferObject { proj: self.proj }
-    }
-    pub fn to_push_constant(&self) -> PushConstantObject {
-        PushConstantObject { view: self.view, model: self.model }
-    }
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug)]
-pub struct UniformBufferObject {
-    proj: glm::Mat4,
-}
-
-impl UniformBufferObject {
-    pub fn construct(proj: glm::Mat4) -> Self {
-        Self { proj }
+impl CameraUniform {
+    pub fn construct(view: glm::Mat4, proj: glm::Mat4, position: glm::Vec3) -> Self {
+        Self { view, proj, position: glm::vec4(position.x, position.y, position.z, 1.0) }
     }
     pub fn identity() -> Self {
-        Self { proj: glm::identity() }
+        Self { view: glm::identity(), proj: glm::identity(), position: glm::vec4(0.0, 0.0, 0.0, 1.0) }
     }
+    pub fn view(&self) -> glm::Mat4 { self.view }
     pub fn proj(&self) -> glm::Mat4 { self.proj }
-    pub fn set_proj(&mut self, proj: glm::Mat4) { self.proj = proj; }
+    pub fn position(&self) -> glm::Vec3 { self.position.xyz() }
 }
 
+/// End of the alpha cutoff in the push constants, the fragment shaders of cutout materials read up to it.
+pub const ALPHA_CUTOFF_END: u32 = (offset_of!(PushConstantObject, alpha_cutoff) + size_of::<f32>()) as u32;
+
+/// Per object data pushed before each draw.
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct PushConstantObject {
-    view: glm::Mat4,
     model: glm::Mat4,
+    alpha_cutoff: f32,
 }
 
 impl PushConstantObject {
-    pub fn construct(view: glm::Mat4, model: glm::Mat4) -> Self {
-        Self { view, model }
+    pub fn construct(model: glm::Mat4, alpha_cutoff: f32) -> Self {
+        Self { model, alpha_cutoff }
     }
     pub fn identity() -> Self {
-        Self { view: glm::identity(), model: glm::identity() }
+        Self { model: glm::identity(), alpha_cutoff: 0.0 }
     }
-    pub fn view(&self) -> glm::Mat4 { self.view }
     pub fn model(&self) -> glm::Mat4 { self.model }
-    pub fn set_view(&mut self, view: glm::Mat4) { self.view = view; }
     pub fn set_model(&mut self, model: glm::Mat4) { self.model = model; }
+    pub fn alpha_cutoff(&self) -> f32 { self.alpha_cutoff }
+    pub fn set_alpha_cutoff(&mut self, alpha_cutoff: f32) { self.alpha_cutoff = alpha_cutoff; }
 }
 
 //================================================
@@ -166,27 +149,26 @@ impl PushConstantObject {
 //================================================
 
 pub unsafe fn create_uniform_buffers(
-    instance: &Instance,
     device: &Device,
-    physical_device: vk::PhysicalDevice,
-    swapchain_images: &Vec<vk::Image>)
--> Result<(Vec<vk::Buffer>, Vec<vk::DeviceMemory>)> {
+    allocator: &MemoryAllocator,
+    swapchain_images: &[vk::Image],
+    size: u64)
+-> Result<(Vec<vk::Buffer>, Vec<Allocation>)> {
     let mut uniform_buffers : Vec<vk::Buffer> = Vec::default(); 
-    let mut uniform_buffers_memory : Vec<vk::DeviceMemory> = Vec::default(); 
+    let mut uniform_buffers_allocation : Vec<Allocation> = Vec::default(); 
 
     for _ in 0..swapchain_images.len() {
-        let (uniform_buffer, uniform_buffer_memory) = create_buffer(
-            instance,
+        let (uniform_buffer, uniform_buffer_allocation) = create_buffer(
             device,
-            physical_device,
-            size_of::<UniformBufferObject>() as u64,
+            allocator,
+            size,
             vk::BufferUsageFlags::UNIFORM_BUFFER,
             vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
         )?;
 
         uniform_buffers.push(uniform_buffer);
-        uniform_buffers_memory.push(uniform_buffer_memory);
+        uniform_buffers_allocation.push(uniform_buffer_allocation);
     }
 
-    Ok((uniform_buffers, uniform_buffers_memory))
-}
\ No newline at end of file
+    Ok((uniform_buffers, uniform_buffers_allocation))
+}
@@ -17,11 +17,17 @@ pub struct Vertex {
     pos: glm::Vec3,
     color: glm::Vec3,
     tex_coord: glm::Vec2,
+    normal: glm::Vec3,
 }
 
 impl Vertex {
+    /// Vertex without normal, see `with_normal` and `object::generate_normals`.
     pub fn new(pos: glm::Vec3, color: glm::Vec3, tex_coord: glm::Vec2) -> Self {
-        Self { pos, color, tex_coord }
+        Self { pos, color, tex_coord, normal: glm::Vec3::zeros() }
+    }
+
+    pub fn with_normal(pos: glm::Vec3, color: glm::Vec3, tex_coord: glm::Vec2, normal: glm::Vec3) -> Self {
+        Self { pos, color, tex_coord, normal }
     }
 
     pub fn binding_description() -> vk::VertexInputBindingDescription {
@@ -32,7 +38,7 @@ impl Vertex {
             .build()
     }
 
-    pub fn attribute_descriptions() -> [vk::VertexInputAttributeDescription; 3] {
+    pub fn attribute_descriptions() -> [vk::VertexInputAttributeDescription; 4] {
         let pos = vk::VertexInputAttributeDescription::builder()
             .binding(0)
             .location(0)
@@ -51,12 +57,20 @@ impl Vertex {
             .format(vk::Format::R32G32_SFLOAT)
             .offset((size_of::<glm::Vec3>() + size_of::<glm::Vec3>()) as u32)
             .build();
-        [pos, color, tex_coord]
+        let normal = vk::VertexInputAttributeDescription::builder()
+            .binding(0)
+            .location(3)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset((size_of::<glm::Vec3>() + size_of::<glm::Vec3>() + size_of::<glm::Vec2>()) as u32)
+            .build();
+        [pos, color, tex_coord, normal]
     }
 
     pub fn pos(&self) -> glm::Vec3 { self.pos }
     pub fn color(&self) -> glm::Vec3 { self.color }
     pub fn tex_coord(&self) -> glm::Vec2 { self.tex_coord }
+    pub fn normal(&self) -> glm::Vec3 { self.normal }
+    pub fn set_normal(&mut self, normal: glm::Vec3) { self.normal = normal; }
     pub fn set_tex_coord(&mut self, tex_coord: glm::Vec2) { self.tex_coord = tex_coord; }
     pub fn set_color(&mut self, color: glm::Vec3) { self.color = color; }
     pub fn set_pos(&mut self, pos: glm::Vec3) { self.pos = pos; }
@@ -66,7 +80,8 @@ impl PartialEq for Vertex {
     fn eq(&self, other: &Self) -> bool {
         self.pos == other.pos &&
         self.color == other.color &&
-        self.tex_coord == other.tex_coord
+        self.tex_coord == other.tex_coord &&
+        self.normal == other.normal
     }
 }
 
@@ -82,5 +97,88 @@ impl Hash for Vertex {
         self.color[2].to_bits().hash(state);
         self.tex_coord[0].to_bits().hash(state);
         self.tex_coord[1].to_bits().hash(state);
+        self.normal[0].to_bits().hash(state);
+        self.normal[1].to_bits().hash(state);
+        self.normal[2].to_bits().hash(state);
+    }
+}
+//================================================
+// Instance
+//================================================
+
+/// Per-instance data read from the second vertex binding.
+/// Objects which are not instanced are drawn with a single default instance.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InstanceData {
+    transform: glm::Mat4,
+    tint: glm::Vec3,
+}
+
+impl InstanceData {
+    /// `transform` is applied before the model matrix of the object, `tint` multiplies the vertex colors.
+    pub fn new(transform: glm::Mat4, tint: glm::Vec3) -> Self {
+        Self { transform, tint }
     }
-}
\ No newline at end of file
+
+    pub fn from_transform(transform: glm::Mat4) -> Self {
+        Self::new(transform, glm::vec3(1.0, 1.0, 1.0))
+    }
+
+    pub fn binding_description() -> vk::VertexInputBindingDescription {
+        vk::VertexInputBindingDescription::builder()
+            .binding(1)
+            .stride(size_of::<InstanceData>() as u32)
+            .input_rate(vk::VertexInputRate::INSTANCE)
+            .build()
+    }
+
+    /// The transform takes one location per column.
+    pub fn attribute_descriptions() -> [vk::VertexInputAttributeDescription; 5] {
+        let column = |i: u32| vk::VertexInputAttributeDescription::builder()
+            .binding(1)
+            .location(4 + i)
+            .format(vk::Format::R32G32B32A32_SFLOAT)
+            .offset(i * size_of::<glm::Vec4>() as u32)
+            .build();
+        let tint = vk::VertexInputAttributeDescription::builder()
+            .binding(1)
+            .location(8)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset(size_of::<glm::Mat4>() as u32)
+            .build();
+        [column(0), column(1), column(2), column(3), tint]
+    }
+
+    pub fn transform(&self) -> glm::Mat4 { self.transform }
+    pub fn tint(&self) -> glm::Vec3 { self.tint }
+    pub fn set_transform(&mut self, transform: glm::Mat4) { self.transform = transform; }
+    pub fn set_tint(&mut self, tint: glm::Vec3) { self.tint = tint; }
+}
+
+impl Default for InstanceData {
+    fn default() -> Self {
+        Self::from_transform(glm::identity())
+    }
+}
+
+/// Attributes of the vertex and instance bindings, the inputs the vertex shaders can read.
+pub fn vertex_attribute_descriptions() -> Vec<vk::VertexInputAttributeDescription> {
+    Vertex::attribute_descriptions().into_iter()
+        .chain(InstanceData::attribute_descriptions())
+        .collect()
+}
+
+/// Center of the bounding box of the vertices, placed by each instance transform and averaged.
+pub fn instances_center(vertices: &[Vertex], instances: &[InstanceData]) -> glm::Vec3 {
+    if vertices.is_empty() || instances.is_empty() {
+        return glm::Vec3::zeros();
+    }
+    let (min, max) = vertices.iter().fold(
+        (vertices[0].pos, vertices[0].pos),
+        |(min, max), v| (glm::min2(&min, &v.pos), glm::max2(&max, &v.pos)));
+    let center = (min + max) * 0.5;
+    instances.iter()
+        .map(|i| (i.transform * glm::vec4(center.x, center.y, center.z, 1.0)).xyz())
+        .sum::<glm::Vec3>() / instances.len() as f32
+}
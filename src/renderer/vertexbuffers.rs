@@ -1,5 +1,5 @@
 use std::{
-    mem::size_of,
+    mem::size_of_val,
     ptr::copy_nonoverlapping as memcpy,
     sync::Arc,
 };
@@ -10,42 +10,45 @@ use anyhow::{Result, anyhow};
 use crate::renderer::{
     vertex::*,
     buffers_tools::*,
+    allocator::{MemoryAllocator, Allocation},
 };
 
 #[derive(Debug, Clone)]
 pub struct VertexBuffer {
     device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
     is_allocated: bool,
 
 //vertex buffer
     indices_size: usize,
     vertex_buffer: vk::Buffer,
-    vertex_buffer_memory: vk::DeviceMemory,
+    vertex_buffer_allocation: Allocation,
 
 //index buffer
     index_buffer: vk::Buffer,
-    index_buffer_memory: vk::DeviceMemory,
+    index_buffer_allocation: Allocation,
 }
 
 impl VertexBuffer {
-    pub fn empty(device: Arc<Device>) -> Result<Self> {
+    pub fn empty(device: Arc<Device>, allocator: Arc<MemoryAllocator>) -> Result<Self> {
         Ok(
         VertexBuffer {
             device,
+            allocator,
             is_allocated: false,
             indices_size: 0,
             // vertices: Vec::default(),
             // indices: Vec::default(),
             vertex_buffer: vk::Buffer::default(),
-            vertex_buffer_memory: vk::DeviceMemory::default(),
+            vertex_buffer_allocation: Allocation::default(),
             index_buffer: vk::Buffer::default(),
-            index_buffer_memory: vk::DeviceMemory::default(),
+            index_buffer_allocation: Allocation::default(),
         })
     } 
 
-    pub fn allocate(&mut self, device: Arc<Device>, instance: &Instance,
-        physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool, 
-        graphics_queue: vk::Queue, vertices: &Vec<Vertex>, indices: &Vec<u32>) -> Result<()>
+    pub fn allocate(&mut self, device: Arc<Device>, allocator: Arc<MemoryAllocator>,
+        command_pool: vk::CommandPool, 
+        graphics_queue: vk::Queue, vertices: &[Vertex], indices: &[u32]) -> Result<()>
     {
         if vertices.is_empty() || indices.is_empty() {
             return Err(anyhow!("vertices or indices can't be empty"));
@@ -55,37 +58,36 @@ impl VertexBuffer {
         }
 
         unsafe {
-            let (vertex_buffer, vertex_buffer_memory) = load_vertex_buffer(instance, &device, physical_device, command_pool, graphics_queue, &vertices)?;
-            let (index_buffer, index_buffer_memory) = load_index_buffer(instance, &device, physical_device, command_pool, graphics_queue, &indices)?;
+            let (vertex_buffer, vertex_buffer_allocation) = load_vertex_buffer(&device, &allocator, command_pool, graphics_queue, vertices)?;
+            let (index_buffer, index_buffer_allocation) = load_index_buffer(&device, &allocator, command_pool, graphics_queue, indices)?;
             self.device = device;
+            self.allocator = allocator;
             // self.vertices = vertices;
             // self.indices = indices;
             self.indices_size = indices.len();
             self.vertex_buffer = vertex_buffer;
-            self.vertex_buffer_memory = vertex_buffer_memory;
+            self.vertex_buffer_allocation = vertex_buffer_allocation;
             self.index_buffer = index_buffer;
-            self.index_buffer_memory = index_buffer_memory;         
+            self.index_buffer_allocation = index_buffer_allocation;         
             self.is_allocated = true;     
             Ok(())
         }
     }
 
-    pub fn new(device: Arc<Device>, instance: &Instance,
-        physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool, 
-        graphics_queue: vk::Queue, vertices: &Vec<Vertex>, indices: &Vec<u32>) -> Result<Self>
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>,
+        command_pool: vk::CommandPool, 
+        graphics_queue: vk::Queue, vertices: &[Vertex], indices: &[u32]) -> Result<Self>
     {
-        let mut buffer = VertexBuffer::empty(device.clone())?;
-        buffer.allocate(device.clone(), instance, physical_device, command_pool, graphics_queue, vertices, indices)?;
+        let mut buffer = VertexBuffer::empty(device.clone(), allocator.clone())?;
+        buffer.allocate(device, allocator, command_pool, graphics_queue, vertices, indices)?;
         Ok(buffer)
     }
 
     pub fn clean(&mut self) {
         if self.is_allocated {
             unsafe {
-                self.device.destroy_buffer(self.index_buffer, None);
-                self.device.free_memory(self.index_buffer_memory, None);
-                self.device.destroy_buffer(self.vertex_buffer, None);
-                self.device.free_memory(self.vertex_buffer_memory, None);
+                destroy_buffer(&self.device, &self.allocator, self.index_buffer, &self.index_buffer_allocation);
+                destroy_buffer(&self.device, &self.allocator, self.vertex_buffer, &self.vertex_buffer_allocation);
             }
             self.is_allocated = false;
         }
@@ -104,55 +106,74 @@ impl Drop for VertexBuffer {
 
 
 //================================================
-// Vertex buffer
+// Instance buffer
 //================================================
 
-pub unsafe fn load_vertex_buffer(
-    instance: &Instance, 
-    device: &Device, 
-    physical_device: vk::PhysicalDevice,
-    command_pool: vk::CommandPool, 
-    graphics_queue: vk::Queue,
-    vertices: &Vec<Vertex>)
--> Result<(vk::Buffer, vk::DeviceMemory)>
-{
-    // Create (staging)
-    let size = (size_of::<Vertex>() * vertices.len()) as u64;
-
-    let (staging_buffer, staging_buffer_memory) = create_buffer(
-        instance,
-        device,
-        physical_device,
-        size,
-        vk::BufferUsageFlags::TRANSFER_SRC,
-        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
-    )?;
-
-    // Copy (staging)
-    let memory = device.map_memory(staging_buffer_memory, 0, size, vk::MemoryMapFlags::empty())?;
+/// Per-instance transforms and tints of an object, bound to the second vertex binding.
+#[derive(Debug, Clone)]
+pub struct InstanceBuffer {
+    device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
+    is_allocated: bool,
+    instance_count: usize,
+    buffer: vk::Buffer,
+    buffer_allocation: Allocation,
+}
 
-    memcpy(vertices.as_ptr(), memory.cast(), vertices.len());
+impl InstanceBuffer {
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>,
+        command_pool: vk::CommandPool,
+        graphics_queue: vk::Queue, instances: &[InstanceData]) -> Result<Self>
+    {
+        if instances.is_empty() {
+            return Err(anyhow!("instances can't be empty"));
+        }
+        unsafe {
+            let (buffer, buffer_allocation) = load_device_local_buffer(&device, &allocator, command_pool, graphics_queue,
+                instances, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+            Ok(Self {
+                device,
+                allocator,
+                is_allocated: true,
+                instance_count: instances.len(),
+                buffer,
+                buffer_allocation,
+            })
+        }
+    }
 
-    device.unmap_memory(staging_buffer_memory);
+    pub fn clean(&mut self) {
+        if self.is_allocated {
+            unsafe {
+                destroy_buffer(&self.device, &self.allocator, self.buffer, &self.buffer_allocation);
+            }
+            self.is_allocated = false;
+        }
+    }
 
-    // Create (vertex)
-    let (vertex_buffer, vertex_buffer_memory) = create_buffer(
-        instance,
-        device,
-        physical_device,
-        size,
-        vk::BufferUsageFlags::TRANSFER_DST | vk::BufferUsageFlags::VERTEX_BUFFER,
-        vk::MemoryPropertyFlags::DEVICE_LOCAL,
-    )?;
+    pub fn buffer(&self) -> vk::Buffer { self.buffer }
+    pub fn instance_count(&self) -> usize { self.instance_count }
+}
 
-    // Copy (vertex)
-    copy_buffer(device, command_pool, graphics_queue, staging_buffer, vertex_buffer, size)?;
+impl Drop for InstanceBuffer {
+    fn drop(&mut self) {
+        self.clean();
+    }
+}
 
-    // Cleanup
-    device.destroy_buffer(staging_buffer, None);
-    device.free_memory(staging_buffer_memory, None);
+//================================================
+// Vertex buffer
+//================================================
 
-    Ok((vertex_buffer, vertex_buffer_memory))
+pub unsafe fn load_vertex_buffer(
+    device: &Device, 
+    allocator: &MemoryAllocator,
+    command_pool: vk::CommandPool, 
+    graphics_queue: vk::Queue,
+    vertices: &[Vertex])
+-> Result<(vk::Buffer, Allocation)>
+{
+    load_device_local_buffer(device, allocator, command_pool, graphics_queue, vertices, vk::BufferUsageFlags::VERTEX_BUFFER)
 }
 
 //================================================
@@ -160,49 +181,58 @@ pub unsafe fn load_vertex_buffer(
 //================================================
 
 pub unsafe fn load_index_buffer(
-    instance: &Instance,
     device: &Device, 
-    physical_device: vk::PhysicalDevice,
+    allocator: &MemoryAllocator,
     command_pool: vk::CommandPool, 
     graphics_queue: vk::Queue,
-    indices: &Vec<u32>)
--> Result<(vk::Buffer, vk::DeviceMemory)>
+    indices: &[u32])
+-> Result<(vk::Buffer, Allocation)>
+{
+    load_device_local_buffer(device, allocator, command_pool, graphics_queue, indices, vk::BufferUsageFlags::INDEX_BUFFER)
+}
+
+//================================================
+// Device local buffer
+//================================================
+
+/// Copies `data` to a new device local buffer through a staging buffer.
+unsafe fn load_device_local_buffer<T: Copy>(
+    device: &Device,
+    allocator: &MemoryAllocator,
+    command_pool: vk::CommandPool,
+    graphics_queue: vk::Queue,
+    data: &[T],
+    usage: vk::BufferUsageFlags)
+-> Result<(vk::Buffer, Allocation)>
 {
-    let size = (size_of::<u32>() * indices.len()) as u64;
+    // Create (staging)
+    let size = size_of_val(data) as u64;
 
-    let (staging_buffer, staging_buffer_memory) = create_buffer(
-        instance,
+    let (staging_buffer, staging_buffer_allocation) = create_buffer(
         device,
-        physical_device,
+        allocator,
         size,
         vk::BufferUsageFlags::TRANSFER_SRC,
         vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
     )?;
 
-    let memory = device.map_memory(
-        staging_buffer_memory,
-        0,
-        size,
-        vk::MemoryMapFlags::empty(),
-    )?;
-
-    memcpy(indices.as_ptr(), memory.cast(), indices.len());
-
-    device.unmap_memory(staging_buffer_memory);
+    // Copy (staging)
+    memcpy(data.as_ptr(), staging_buffer_allocation.mapped()?.cast(), data.len());
 
-    let (index_buffer, index_buffer_memory) = create_buffer(
-        instance,
+    // Create (device local)
+    let (buffer, buffer_allocation) = create_buffer(
         device,
-        physical_device,
+        allocator,
         size,
-        vk::BufferUsageFlags::TRANSFER_DST | vk::BufferUsageFlags::INDEX_BUFFER,
+        vk::BufferUsageFlags::TRANSFER_DST | usage,
         vk::MemoryPropertyFlags::DEVICE_LOCAL,
     )?;
 
-    copy_buffer(device, command_pool, graphics_queue, staging_buffer, index_buffer, size)?;
+    // Copy (device local)
+    copy_buffer(device, command_pool, graphics_queue, staging_buffer, buffer, size)?;
 
-    device.destroy_buffer(staging_buffer, None);
-    device.free_memory(staging_buffer_memory, None);
+    // Cleanup
+    destroy_buffer(device, allocator, staging_buffer, &staging_buffer_allocation);
 
-    Ok((index_buffer, index_buffer_memory))
-}
\ No newline at end of file
+    Ok((buffer, buffer_allocation))
+}
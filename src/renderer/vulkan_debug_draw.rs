@@ -0,0 +1,179 @@
+use {
+    std::{
+        cell::RefCell,
+        rc::Rc,
+        mem::size_of,
+        ptr::copy_nonoverlapping as memcpy,
+        sync::Arc,
+    },
+    anyhow::Result,
+    vulkanalia::prelude::v1_0::*,
+    super::{
+        vulkan_shader::{VulkanShader, ShaderContainer},
+        buffers_tools::{create_buffer, destroy_buffer},
+        allocator::{MemoryAllocator, Allocation},
+        uniformbuffers::UniformBuffer,
+        descriptor::Descriptor,
+        vertex::{Vertex, InstanceData},
+        material::MaterialId,
+        rasterization::Topology,
+        debug_draw::{DebugDraw, DebugDrawCall},
+    },
+};
+
+/// Smallest vertex buffer allocated for the debug primitives, in bytes.
+const MIN_BUFFER_SIZE: u64 = 64 * 1024;
+
+/// GPU side of `DebugDraw`: a host visible buffer per swapchain image, holding the identity
+/// instance followed by the vertices of the frame, and the depth tested and overlay pipelines.
+#[derive(Clone, Debug)]
+pub struct VulkanDebugDraw {
+    device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
+    //depth tested, then overlay
+    shaders: [Rc<RefCell<VulkanShader>>; 2],
+    descriptors: Vec<Descriptor>,
+    buffers: Vec<Option<(vk::Buffer, Allocation)>>,
+    calls: Vec<Vec<DebugDrawCall>>,
+    is_allocated: bool,
+}
+
+impl VulkanDebugDraw {
+    // the shared uniforms are bound in the descriptors of both pipelines
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, shader_container: &mut ShaderContainer,
+        swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass,
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<Self>
+    {
+        let mut shader = |id| -> Result<Rc<RefCell<VulkanShader>>> {
+            let shader = shader_container.get(id, swapchain_images, msaa_samples, render_pass)?;
+            shader.borrow_mut().prepare(Topology::LineList)?;
+            Ok(shader)
+        };
+        let shaders = [shader(MaterialId::DEBUG)?, shader(MaterialId::DEBUG_OVERLAY)?];
+        let descriptors = shaders.iter()
+            .map(|shader| create_descriptor(device.clone(), &shader.borrow(), swapchain_images, camera_buffer, lights_buffer))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            device,
+            allocator,
+            shaders,
+            descriptors,
+            buffers: vec![None; swapchain_images.len()],
+            calls: vec![vec![]; swapchain_images.len()],
+            is_allocated: true,
+        })
+    }
+
+    pub fn clean(&mut self) {
+        if self.is_allocated {
+            unsafe {
+                self.buffers.iter_mut()
+                    .filter_map(|b| b.take())
+                    .for_each(|(b, a)| destroy_buffer(&self.device, &self.allocator, b, &a));
+            }
+            self.descriptors.iter_mut().for_each(|d| d.clean());
+            self.is_allocated = false;
+        }
+    }
+
+    pub fn reload_swapchain(&mut self,
+        swapchain_images: &[vk::Image],
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<()>
+    {
+        unsafe {
+            self.buffers.iter_mut()
+                .filter_map(|b| b.take())
+                .for_each(|(b, a)| destroy_buffer(&self.device, &self.allocator, b, &a));
+        }
+        self.buffers = vec![None; swapchain_images.len()];
+        self.calls = vec![vec![]; swapchain_images.len()];
+        for (descriptor, shader) in self.descriptors.iter_mut().zip(self.shaders.iter()) {
+            let shader = shader.borrow();
+            descriptor.reload_swapchain(swapchain_images,
+                shader.descriptor_set_layout(),
+                &shader.reflection().descriptor_counts(),
+                shader.material().bindings(),
+                &shader.uniform_buffers(camera_buffer, lights_buffer),
+                None)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the primitives in the buffer of the swapchain image, growing it if needed.
+    /// The frame previously drawn from this buffer must have completed.
+    pub fn upload(&mut self, image_index: usize, debug_draw: &DebugDraw) -> Result<()> {
+        let (vertices, calls) = debug_draw.draw_calls();
+        self.calls[image_index] = calls;
+        if vertices.is_empty() {
+            return Ok(());
+        }
+        let size = (size_of::<InstanceData>() + size_of::<Vertex>() * vertices.len()) as u64;
+        if self.buffers[image_index].as_ref().is_none_or(|(_, a)| a.size() < size) {
+            unsafe {
+                if let Some((buffer, allocation)) = self.buffers[image_index].take() {
+                    destroy_buffer(&self.device, &self.allocator, buffer, &allocation);
+                }
+                self.buffers[image_index] = Some(create_buffer(
+                    &self.device,
+                    &self.allocator,
+                    size.next_power_of_two().max(MIN_BUFFER_SIZE),
+                    vk::BufferUsageFlags::VERTEX_BUFFER,
+                    vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                )?);
+            }
+        }
+        if let Some((_, allocation)) = &self.buffers[image_index] {
+            unsafe {
+                let memory = allocation.mapped()?;
+                memcpy(&InstanceData::default(), memory.cast(), 1);
+                memcpy(vertices.as_ptr(), memory.add(size_of::<InstanceData>()).cast(), vertices.len());
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws of the last primitives uploaded for the swapchain image.
+    pub fn calls(&self, image_index: usize) -> &[DebugDrawCall] {
+        &self.calls[image_index]
+    }
+
+    /// Buffer of the swapchain image, the vertices start after the instance.
+    pub fn buffer(&self, image_index: usize) -> Option<vk::Buffer> {
+        self.buffers[image_index].as_ref().map(|(b, _)| *b)
+    }
+
+    pub fn vertex_offset(&self) -> vk::DeviceSize {
+        size_of::<InstanceData>() as vk::DeviceSize
+    }
+
+    /// Shader and descriptor of the depth tested or overlay primitives.
+    pub fn shader(&self, depth_test: bool) -> (Rc<RefCell<VulkanShader>>, &Descriptor) {
+        let index = if depth_test { 0 } else { 1 };
+        (self.shaders[index].clone(), &self.descriptors[index])
+    }
+}
+
+impl Drop for VulkanDebugDraw {
+    fn drop(&mut self) {
+        self.clean();
+    }
+}
+
+fn create_descriptor(device: Arc<Device>, shader: &VulkanShader,
+    swapchain_images: &[vk::Image],
+    camera_buffer: &UniformBuffer,
+    lights_buffer: &UniformBuffer) -> Result<Descriptor>
+{
+    Descriptor::new(device,
+        swapchain_images,
+        shader.descriptor_set_layout(),
+        &shader.reflection().descriptor_counts(),
+        shader.material().bindings(),
+        &shader.uniform_buffers(camera_buffer, lights_buffer),
+        None)
+}
@@ -0,0 +1,65 @@
+use {
+    std::{cell::RefCell, rc::Rc, sync::Arc},
+    anyhow::Result,
+    vulkanalia::prelude::v1_0::*,
+    super::{
+        vulkan_shader::{VulkanShader, ShaderContainer},
+        uniformbuffers::UniformBuffer,
+        descriptor::{Descriptor, GRADIENT_BINDING},
+        material::MaterialId,
+        render_settings::Gradient,
+    },
+};
+
+/// GPU side of the gradient background: the pipeline of the full-screen triangle and the
+/// descriptor of its colors, written in the material uniform.
+#[derive(Clone, Debug)]
+pub struct VulkanGradient {
+    shader: Rc<RefCell<VulkanShader>>,
+    descriptor: Descriptor,
+}
+
+impl VulkanGradient {
+    pub fn new(device: Arc<Device>, shader_container: &mut ShaderContainer,
+        swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass,
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<Self>
+    {
+        let shader = shader_container.get(MaterialId::GRADIENT, swapchain_images, msaa_samples, render_pass)?;
+        let descriptor = {
+            let shader = shader.borrow();
+            Descriptor::new(device,
+                swapchain_images,
+                shader.descriptor_set_layout(),
+                &shader.reflection().descriptor_counts(),
+                shader.material().bindings(),
+                &shader.uniform_buffers(camera_buffer, lights_buffer),
+                None)?
+        };
+        Ok(Self { shader, descriptor })
+    }
+
+    pub fn reload_swapchain(&mut self,
+        swapchain_images: &[vk::Image],
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<()>
+    {
+        let shader = self.shader.borrow();
+        self.descriptor.reload_swapchain(swapchain_images,
+            shader.descriptor_set_layout(),
+            &shader.reflection().descriptor_counts(),
+            shader.material().bindings(),
+            &shader.uniform_buffers(camera_buffer, lights_buffer),
+            None)
+    }
+
+    /// Colors drawn from the next update of the material uniforms.
+    pub fn set_colors(&self, gradient: &Gradient) -> Result<()> {
+        self.shader.borrow_mut().set_uniform(GRADIENT_BINDING, &gradient.uniform().bytes())
+    }
+
+    pub fn shader(&self) -> Rc<RefCell<VulkanShader>> { self.shader.clone() }
+    pub fn descriptor(&self) -> &Descriptor { &self.descriptor }
+}
@@ -1,94 +1,162 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, rc::Rc};
 
-use super::vulkan_shader::{VulkanShader, ShaderContainer, ShaderType};
+use super::vulkan_shader::{VulkanShader, ShaderContainer};
 
 use {
     std::sync::Arc,
-    anyhow::Result,
+    anyhow::{Result, anyhow},
     vulkanalia::prelude::v1_0::*,
+    nalgebra_glm as glm,
     super::{
         vulkan_texture::VulkanTexture,
-        vertexbuffers::VertexBuffer, 
+        texture_cache::TextureCache,
+        buffer_cache::BufferCache,
+        vertexbuffers::{VertexBuffer, InstanceBuffer},
+        vertex::{InstanceData, instances_center},
+        allocator::MemoryAllocator,
         uniformbuffers::UniformBuffer,
         descriptor::Descriptor,
+        material::MaterialId,
+        rasterization::Topology,
     },
     crate::object::Object,
 };
 
+/// Stable handle of an object added to the renderer.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ObjectId(pub(crate) u64);
+
 #[derive(Debug, Clone)]
 pub struct VulkanModel {
-    shader: Arc<RefCell<VulkanShader>>,
-    texture: Option<VulkanTexture>,
-    buffer: VertexBuffer,
-    uniform_buffer: UniformBuffer,
+    shader: Rc<RefCell<VulkanShader>>,
+    texture: Option<Arc<VulkanTexture>>,
+    buffer: Arc<VertexBuffer>,
+    //indices of the buffer drawn by this object
+    first_index: u32,
+    index_count: u32,
+    topology: Topology,
+    instances: InstanceBuffer,
+    //center of the drawn vertices before the model matrix, to sort transparent objects
+    center: glm::Vec3,
     descriptor: Descriptor,
+    //model index given to the matrix function, kept while the object lives
+    model_index: usize,
+    fn_update_matrix: fn(usize, f32) -> glm::Mat4,
 }
 
 impl VulkanModel {
-    pub fn from_obj(device: Arc<Device>, shader_container: &mut ShaderContainer, instance: &Instance, 
+    // uploads the mesh, texture and instances of the object through the caches of Core
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_obj(device: Arc<Device>, allocator: Arc<MemoryAllocator>, shader_container: &mut ShaderContainer, texture_cache: &mut TextureCache,
+        buffer_cache: &mut BufferCache, instance: &Instance, 
         physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool, 
-        graphics_queue: vk::Queue, swapchain_images: &Vec<vk::Image>,
-        swapchain_extent: vk::Extent2D, msaa_samples: vk::SampleCountFlags,
-        render_pass: vk::RenderPass, obj: &dyn Object,) -> Result<Self> 
+        graphics_queue: vk::Queue, swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass, camera_buffer: &UniformBuffer, lights_buffer: &UniformBuffer, model_index: usize, obj: &dyn Object,) -> Result<Self> 
     {
-        let vertices = &obj.vertices().to_vec();
-        let indices = &obj.indices().to_vec();
-        let shader : Arc<RefCell<VulkanShader>>;
-        let vulkan_texture : Option<VulkanTexture>;
-        if let Some(texture) = obj.texture() {
-            shader = shader_container.get(ShaderType::Textured, swapchain_extent, msaa_samples, render_pass)?;
-            vulkan_texture = Some(VulkanTexture::new(device.clone(), instance, physical_device, command_pool, graphics_queue, texture)?);
-        } 
-        else {
-            shader = shader_container.get(ShaderType::Untextured, swapchain_extent, msaa_samples, render_pass)?;
-            vulkan_texture = None;
-        }
-        let buffer = VertexBuffer::new(device.clone(), instance, physical_device, command_pool, graphics_queue, vertices, indices)?;
-        let mut uniform_buffer = UniformBuffer::new(device.clone(), instance,physical_device,swapchain_images)?;
-        if let Some(f) = obj.get_fn_update_matrix() {
-            uniform_buffer.set_fn_update_matrix(f);
-        }
+        let material = obj.material().unwrap_or(match (obj.texture(), obj.double_sided()) {
+            (Some(_), false) => MaterialId::TEXTURED,
+            (None, false) => MaterialId::UNTEXTURED,
+            (Some(_), true) => MaterialId::TEXTURED_DOUBLE_SIDED,
+            (None, true) => MaterialId::UNTEXTURED_DOUBLE_SIDED,
+        });
+        let shader = shader_container.get(material, swapchain_images, msaa_samples, render_pass)?;
+        let topology = obj.topology();
+        shader.borrow_mut().prepare(topology)?;
+        let vulkan_texture = if shader.borrow().material().uses_texture() {
+            let texture = obj.texture()
+                .ok_or_else(|| anyhow!("Material `{}` needs an object with a texture.", shader.borrow().material().name()))?;
+            Some(texture_cache.get(device.clone(), allocator.clone(), instance, physical_device, command_pool, graphics_queue, texture)?)
+        } else {
+            None
+        };
+        let (buffer, first_index, index_count) = match obj.shared_model() {
+            Some((model, range)) => (
+                buffer_cache.get(device.clone(), allocator.clone(), command_pool, graphics_queue, model)?,
+                range.start as u32,
+                range.len() as u32,
+            ),
+            None => (
+                Arc::new(VertexBuffer::new(device.clone(), allocator.clone(), command_pool, graphics_queue,
+                    obj.vertices(), obj.indices())?),
+                0,
+                obj.indices().len() as u32,
+            ),
+        };
+        let default_instance = [InstanceData::default()];
+        let instance_data = obj.instances().unwrap_or(&default_instance);
+        let instances = InstanceBuffer::new(device.clone(), allocator, command_pool, graphics_queue, instance_data)?;
+        let center = instances_center(obj.vertices(), instance_data);
+        let fn_update_matrix = obj.get_fn_update_matrix()
+            .unwrap_or(|_, _| -> glm::Mat4 { glm::identity() });
         
-        let descriptor = Descriptor::new(device.clone(),             
-            swapchain_images,
-            shader.borrow().descriptor_set_layout(), 
-            &uniform_buffer, 
-            &vulkan_texture)?;    
+        let descriptor = {
+            let shader = shader.borrow();
+            Descriptor::new(device.clone(),
+                swapchain_images,
+                shader.descriptor_set_layout(),
+                &shader.reflection().descriptor_counts(),
+                shader.material().bindings(),
+                &shader.uniform_buffers(camera_buffer, lights_buffer),
+                vulkan_texture.as_deref())?
+        };
         Ok(VulkanModel {
             shader,
             texture: vulkan_texture,
             buffer,
-            uniform_buffer,
+            first_index,
+            index_count,
+            topology,
+            instances,
+            center,
             descriptor,
+            model_index,
+            fn_update_matrix,
         })
     }
 
+    /// Frees the descriptor and instances of the model, its texture and buffers are freed with their last user.
     pub fn clean(&mut self) {
-        if let Some(texture) = &mut self.texture {
-            texture.clean();
-        } 
-        self.buffer.clean();
-        self.uniform_buffer.clean();
+        self.texture = None;
+        self.instances.clean();
         self.descriptor.clean();  
     }
 
     pub fn reload_swapchain(&mut self,
-        instance: &Instance, 
-        physical_device: vk::PhysicalDevice,
-        swapchain_images: &Vec<vk::Image>) -> Result<()> 
+        swapchain_images: &[vk::Image],
+        camera_buffer: &UniformBuffer,
+        lights_buffer: &UniformBuffer) -> Result<()> 
     {
-        self.uniform_buffer.reload_swapchain_models(instance, physical_device, swapchain_images)?;
-        self.descriptor.reload_swapchain(swapchain_images, self.shader().borrow().descriptor_set_layout(), &self.uniform_buffer, &self.texture)?;
+        let shader = self.shader.borrow();
+        self.descriptor.reload_swapchain(swapchain_images,
+            shader.descriptor_set_layout(),
+            &shader.reflection().descriptor_counts(),
+            shader.material().bindings(),
+            &shader.uniform_buffers(camera_buffer, lights_buffer),
+            self.texture.as_deref())?;
         
         Ok(())
     }
 
-    pub fn texture(&self) -> Option<VulkanTexture> { self.texture.clone() }
+    pub fn texture(&self) -> Option<Arc<VulkanTexture>> { self.texture.clone() }
     pub fn buffer(&self) -> &VertexBuffer { &self.buffer }
+    pub fn first_index(&self) -> u32 { self.first_index }
+    pub fn index_count(&self) -> u32 { self.index_count }
+    pub fn topology(&self) -> Topology { self.topology }
+    pub fn instances(&self) -> &InstanceBuffer { &self.instances }
     pub fn descriptor(&self) -> &Descriptor { &self.descriptor }
-    pub fn uniform_buffer(&self) -> &UniformBuffer { &self.uniform_buffer }
 
-    pub fn shader(&self) -> Arc<RefCell<VulkanShader>> {
+    /// World position of the center of the object for the elapsed time in seconds.
+    pub fn world_center(&self, elapsed: f32) -> glm::Vec3 {
+        (self.model_matrix(elapsed) * glm::vec4(self.center.x, self.center.y, self.center.z, 1.0)).xyz()
+    }
+
+    /// Model matrix of the object for its model index and the elapsed time in seconds.
+    pub fn model_matrix(&self, elapsed: f32) -> glm::Mat4 {
+        (self.fn_update_matrix)(self.model_index, elapsed)
+    }
+
+    pub fn shader(&self) -> Rc<RefCell<VulkanShader>> {
         self.shader.clone()
     }
 }
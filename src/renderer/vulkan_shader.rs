@@ -3,57 +3,212 @@ use {
         sync::Arc,
         collections::HashMap,
         cell::RefCell,
+        rc::Rc,
+        mem::size_of,
     },
-    anyhow::Result,
+    anyhow::{Result, anyhow},
+    log::*,
     vulkanalia::prelude::v1_0::*,
     super::{
         descriptor::create_descriptor_set_layout,
-        pipeline::create_pipeline_type,
+        pipeline::{create_pipeline, create_pipeline_layout},
+        vertex::vertex_attribute_descriptions,
+        shader_source::{ShaderSource, ShaderStage, FileWatcher},
+        material::{Material, MaterialId, MaterialResource},
+        rasterization::{Rasterization, RasterizationFeatures, PolygonMode, Topology},
+        reflection::{ShaderReflection, reflect},
+        allocator::MemoryAllocator,
+        uniformbuffers::{UniformBuffer, PushConstantObject},
     },
 };
 
+/// The registered materials and the pipelines built for them.
 #[derive(Clone)]
 pub struct ShaderContainer {
     device: Arc<Device>,
-    shaders: HashMap<ShaderType, Arc<RefCell<VulkanShader>>>,
+    allocator: Arc<MemoryAllocator>,
+    pipeline_cache: vk::PipelineCache,
+    materials: Vec<Material>,
+    shaders: HashMap<MaterialId, Rc<RefCell<VulkanShader>>>,
+    watcher: FileWatcher,
+    features: RasterizationFeatures,
+    wireframe: bool,
 }
 
 impl ShaderContainer {
-    pub fn new(device: Arc<Device>) -> Self {
-        Self {device, shaders: HashMap::default()}
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, pipeline_cache: vk::PipelineCache,
+        features: RasterizationFeatures) -> Self {
+        Self {
+            device,
+            allocator,
+            pipeline_cache,
+            // in the order of the MaterialId constants
+            materials: vec![
+                Material::textured(),
+                Material::untextured(),
+                Material::textured_double_sided(),
+                Material::untextured_double_sided(),
+                Material::debug(true),
+                Material::debug(false),
+                Material::skybox(),
+                Material::gradient(),
+            ],
+            shaders: HashMap::default(),
+            watcher: FileWatcher::default(),
+            features,
+            wireframe: false,
+        }
     }
-    pub fn get(&mut self, shader_type: ShaderType,
-        swapchain_extent: vk::Extent2D,
-        msaa_samples: vk::SampleCountFlags, 
-        render_pass: vk::RenderPass) -> Result<Arc<RefCell<VulkanShader>>>
+
+    /// Adds a material and builds its pipeline, so that shader errors are reported here.
+    pub fn register(&mut self, material: Material,
+        swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass) -> Result<MaterialId>
     {
-        if let Some(shader) = self.shaders.get(&shader_type) {
+        material.validate()?;
+        material.rasterization().check(&self.features)
+            .map_err(|e| anyhow!("Material `{}`: {}", material.name(), e))?;
+        let id = MaterialId(self.materials.len());
+        self.materials.push(material);
+        if let Err(e) = self.get(id, swapchain_images, msaa_samples, render_pass) {
+            self.materials.pop();
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    pub fn get(&mut self, id: MaterialId,
+        swapchain_images: &[vk::Image],
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass) -> Result<Rc<RefCell<VulkanShader>>>
+    {
+        if let Some(shader) = self.shaders.get(&id) {
             Ok(shader.clone())
         }
         else {
-            let shader = Arc::new(RefCell::new(
-                VulkanShader::new(
-                    self.device.clone(),
-                    shader_type,
-                    swapchain_extent,
-                    msaa_samples,
-                    render_pass,
-                )?));
-            self.shaders.insert(shader_type, shader.clone());
-            Ok(shader.clone())                    
+            let material = self.material(id)?.clone();
+            let shader = VulkanShader::new(
+                self.device.clone(),
+                self.allocator.clone(),
+                self.pipeline_cache,
+                material,
+                swapchain_images,
+                msaa_samples,
+                self.wireframe,
+                render_pass,
+            )?;
+            for path in shader.sources().filter_map(|source| source.path()) {
+                self.watcher.watch(path);
+            }
+            let shader = Rc::new(RefCell::new(shader));
+            self.shaders.insert(id, shader.clone());
+            Ok(shader.clone())
+        }
+    }
+
+    pub fn material(&self, id: MaterialId) -> Result<&Material> {
+        self.materials.get(id.0)
+            .ok_or_else(|| anyhow!("No material with id {:?}.", id))
+    }
+
+    /// Sets the content of a uniform of a material, copied to the GPU when the next frames are recorded.
+    pub fn set_uniform(&mut self, id: MaterialId, binding: u32, data: &[u8]) -> Result<()> {
+        let shader = self.shaders.get(&id)
+            .ok_or_else(|| anyhow!("No material with id {:?}.", id))?;
+        shader.borrow_mut().set_uniform(binding, data)
+    }
+
+    /// Writes the uniforms of every material in the buffers of the given swapchain image.
+    pub fn update_uniforms(&self, image_index: usize) -> Result<()> {
+        for (_, shader) in self.shaders.iter() {
+            shader.borrow().update_uniforms(image_index)?;
         }
+        Ok(())
     }
 
-    pub fn reload_swapchain(&mut self, 
-        swapchain_extent: vk::Extent2D,
+    pub fn reload_swapchain(&mut self, swapchain_images: &[vk::Image]) -> Result<()> {
+        for (_, shader) in self.shaders.iter() {
+            shader.borrow_mut().reload_swapchain(swapchain_images)?;
+        }
+        Ok(())
+    }
+
+    /// Recreates the pipelines for a render pass which is not compatible with the previous one.
+    pub fn reload_render_pass(&mut self,
         msaa_samples: vk::SampleCountFlags,
-        render_pass: vk::RenderPass) -> Result<()> 
+        render_pass: vk::RenderPass) -> Result<()>
     {
         for (_, ptr_shader) in self.shaders.iter() {
             unsafe {
                 let mut_ptr_shader = ptr_shader.as_ptr().as_mut();
                 if let Some(shader) = mut_ptr_shader {
-                    shader.reload_swapchain(swapchain_extent, msaa_samples, render_pass)?;
+                    shader.reload_render_pass(msaa_samples, render_pass)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the edges of the triangles of every material, to debug the geometry.
+    pub fn set_wireframe(&mut self, wireframe: bool) -> Result<()> {
+        if wireframe == self.wireframe {
+            return Ok(());
+        }
+        if wireframe && !self.features.fill_mode_non_solid {
+            return Err(anyhow!("The device does not support wireframe rendering."));
+        }
+        unsafe {
+            // The old pipelines may still be used by frames in flight.
+            self.device.device_wait_idle()?;
+            let mut switched: Vec<&mut VulkanShader> = vec![];
+            for (_, ptr_shader) in self.shaders.iter() {
+                let mut_ptr_shader = ptr_shader.as_ptr().as_mut();
+                if let Some(shader) = mut_ptr_shader {
+                    if let Err(e) = shader.set_wireframe(wireframe) {
+                        // Switch back the shaders already changed so that they all draw the same mode
+                        for shader in switched {
+                            if let Err(e) = shader.set_wireframe(!wireframe) {
+                                error!("Material `{}` keeps drawing in the other wireframe mode: {}", shader.material().name(), e);
+                            }
+                        }
+                        return Err(e);
+                    }
+                    switched.push(shader);
+                }
+            }
+        }
+        self.wireframe = wireframe;
+        Ok(())
+    }
+
+    pub fn wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    /// Rebuilds the pipelines of the shaders whose files changed on disk. A shader which fails
+    /// to compile is logged and keeps its previous pipeline.
+    pub fn reload_changed(&mut self) -> Result<()> {
+        let changed = self.watcher.changed();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        unsafe {
+            // The old pipelines may still be used by frames in flight.
+            self.device.device_wait_idle()?;
+            for (_, ptr_shader) in self.shaders.iter() {
+                let mut_ptr_shader = ptr_shader.as_ptr().as_mut();
+                if let Some(shader) = mut_ptr_shader {
+                    let affected = shader.sources()
+                        .filter_map(|source| source.path())
+                        .any(|path| changed.iter().any(|c| c == path));
+                    if !affected {
+                        continue;
+                    }
+                    match shader.reload() {
+                        Ok(()) => info!("Reloaded the shaders of material `{}`.", shader.material().name()),
+                        Err(e) => error!("Keeping the previous shaders of material `{}`: {}", shader.material().name(), e),
+                    }
                 }
             }
         }
@@ -69,7 +224,7 @@ impl ShaderContainer {
                 }
             }
         }
-    } 
+    }
 }
 
 impl Drop for ShaderContainer {
@@ -78,75 +233,238 @@ impl Drop for ShaderContainer {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub enum ShaderType {
-    Textured,
-    Untextured,
+/// Uniform block owned by a material, with the bytes last set by the user.
+#[derive(Clone, Debug)]
+struct MaterialUniform {
+    binding: u32,
+    buffer: UniformBuffer,
+    data: Vec<u8>,
 }
 
+/// Pipelines, layouts and uniforms of a material, with one pipeline per topology drawn.
 #[derive(Clone, Debug)]
 pub struct VulkanShader {
     device: Arc<Device>,
-    shader_type: ShaderType,
-    pipeline: vk::Pipeline,
+    pipeline_cache: vk::PipelineCache,
+    material: Material,
+    reflection: ShaderReflection,
+    vert: Vec<u32>,
+    frag: Vec<u32>,
+    uniforms: Vec<MaterialUniform>,
+    msaa_samples: vk::SampleCountFlags,
+    wireframe: bool,
+    render_pass: vk::RenderPass,
+    pipelines: HashMap<Topology, vk::Pipeline>,
     pipeline_layout: vk::PipelineLayout,
     descriptor_set_layout: vk::DescriptorSetLayout,
     is_allocated: bool,
 }
 
 impl VulkanShader {
-    pub fn new(device: Arc<Device>, shader_type: ShaderType,
-        swapchain_extent: vk::Extent2D, 
+    /// Builds the triangle list pipeline, the other topologies are built by `prepare`.
+    // the render pass and sample count belong to Core, the pipeline cache to the container
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, pipeline_cache: vk::PipelineCache,
+        material: Material,
+        swapchain_images: &[vk::Image],
         msaa_samples: vk::SampleCountFlags,
-        render_pass: vk::RenderPass) -> Result<Self> 
+        wireframe: bool,
+        render_pass: vk::RenderPass) -> Result<Self>
     {
-        let descriptor_set_layout = create_descriptor_set_layout(&device, shader_type)?;
-                    
-        let ( pipeline, 
-            pipeline_layout
-        ) = create_pipeline_type(&device, shader_type, swapchain_extent, msaa_samples, descriptor_set_layout, render_pass)?;
-        Ok(Self {
+        // the uniform buffers free themselves when dropped on error
+        let mut uniforms = vec![];
+        for binding in material.bindings() {
+            if let MaterialResource::Uniform(size) = binding.resource() {
+                uniforms.push(MaterialUniform {
+                    binding: binding.binding(),
+                    buffer: UniformBuffer::new(device.clone(), allocator.clone(), swapchain_images, size)?,
+                    data: vec![0; size as usize],
+                });
+            }
+        }
+        let (vert, frag, reflection) = load_shaders(&material)?;
+        let descriptor_set_layout = create_descriptor_set_layout(&device, reflection.bindings())?;
+        let pipeline_layout = match unsafe { create_pipeline_layout(&device, descriptor_set_layout, push_constant_range(&reflection)) } {
+            Ok(pipeline_layout) => pipeline_layout,
+            Err(e) => {
+                unsafe { device.destroy_descriptor_set_layout(descriptor_set_layout, None); }
+                return Err(e);
+            }
+        };
+
+        // cleaned by its drop if the pipeline fails
+        let mut shader = Self {
             device,
-            shader_type,
-            descriptor_set_layout,
-            pipeline,
+            pipeline_cache,
+            material,
+            reflection,
+            vert,
+            frag,
+            uniforms,
+            msaa_samples,
+            wireframe,
+            render_pass,
+            pipelines: HashMap::default(),
             pipeline_layout,
+            descriptor_set_layout,
             is_allocated: true,
-        })
-        
+        };
+        shader.prepare(Topology::TriangleList)?;
+        Ok(shader)
     }
 
     pub fn clean(&mut self) {
         if self.is_allocated {
             unsafe {
-                self.device.destroy_pipeline(self.pipeline, None);
+                self.pipelines.drain().for_each(|(_, p)| self.device.destroy_pipeline(p, None));
                 self.device.destroy_pipeline_layout(self.pipeline_layout, None);
                 self.device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+                self.uniforms.iter_mut().for_each(|u| u.buffer.clean());
                 self.is_allocated = false;
             }
         }
     }
 
-    pub fn reload_swapchain(&mut self, 
-        swapchain_extent: vk::Extent2D,
+    pub fn reload_swapchain(&mut self, swapchain_images: &[vk::Image]) -> Result<()> {
+        for uniform in self.uniforms.iter_mut() {
+            uniform.buffer.reload_swapchain(swapchain_images)?;
+        }
+        Ok(())
+    }
+
+    pub fn reload_render_pass(&mut self,
         msaa_samples: vk::SampleCountFlags,
         render_pass: vk::RenderPass) -> Result<()> {
-        unsafe {
-            if self.is_allocated {                
-                    self.device.destroy_pipeline(self.pipeline, None);
-                    self.device.destroy_pipeline_layout(self.pipeline_layout, None);                
+        self.msaa_samples = msaa_samples;
+        self.render_pass = render_pass;
+        self.rebuild(self.vert.clone(), self.frag.clone(), self.wireframe)
+    }
+
+    /// Builds the pipelines again from the shader sources, the current pipelines are only
+    /// replaced once the new ones are created. The pipelines must not be in use.
+    /// The descriptor bindings and push constants of the shaders must not change.
+    pub fn reload(&mut self) -> Result<()> {
+        let (vert, frag, reflection) = load_shaders(&self.material)?;
+        if !reflection.same_layout(&self.reflection) {
+            return Err(anyhow!("The descriptors or push constants of material `{}` changed, they are only read at startup.",
+                self.material.name()));
+        }
+        self.rebuild(vert, frag, self.wireframe)
+    }
+
+    /// Builds the pipeline drawing `topology` primitives if the material does not have it yet.
+    pub fn prepare(&mut self, topology: Topology) -> Result<()> {
+        if !self.pipelines.contains_key(&topology) {
+            let pipeline = self.create_pipeline(&self.vert, &self.frag, topology, self.rasterization())?;
+            self.pipelines.insert(topology, pipeline);
+        }
+        Ok(())
+    }
+
+    /// Pipeline drawing `topology` primitives, built by `prepare`.
+    pub fn pipeline(&self, topology: Topology) -> Result<vk::Pipeline> {
+        self.pipelines.get(&topology).copied()
+            .ok_or_else(|| anyhow!("Material `{}` has no {:?} pipeline.", self.material.name(), topology))
+    }
+
+    /// Replaces every pipeline with one built from the given shaders and wireframe mode, keeps them all on error.
+    fn rebuild(&mut self, vert: Vec<u32>, frag: Vec<u32>, wireframe: bool) -> Result<()> {
+        let rasterization = rasterization(&self.material, wireframe);
+        let mut pipelines = HashMap::default();
+        for topology in self.pipelines.keys() {
+            match self.create_pipeline(&vert, &frag, *topology, rasterization) {
+                Ok(pipeline) => { pipelines.insert(*topology, pipeline); }
+                Err(e) => {
+                    pipelines.into_values().for_each(|p| unsafe { self.device.destroy_pipeline(p, None) });
+                    return Err(e);
+                }
             }
-            let (pipeline, 
-                pipeline_layout
-            ) = create_pipeline_type(&self.device, self.shader_type, swapchain_extent, msaa_samples, self.descriptor_set_layout, render_pass)?;
-            self.pipeline = pipeline;
-            self.pipeline_layout = pipeline_layout;
         }
+        let old = std::mem::replace(&mut self.pipelines, pipelines);
+        old.into_values().for_each(|p| unsafe { self.device.destroy_pipeline(p, None) });
+        self.vert = vert;
+        self.frag = frag;
+        self.wireframe = wireframe;
         Ok(())
     }
 
-    pub fn pipeline(&self) -> vk::Pipeline {
-        self.pipeline
+    fn create_pipeline(&self, vert: &[u32], frag: &[u32], topology: Topology, rasterization: Rasterization) -> Result<vk::Pipeline> {
+        unsafe {
+            create_pipeline(
+                &self.device,
+                self.pipeline_cache,
+                vert,
+                frag,
+                self.msaa_samples,
+                self.pipeline_layout,
+                self.material.blend_mode(),
+                rasterization,
+                self.material.depth_test(),
+                topology,
+                self.render_pass)
+        }
+    }
+
+    /// Rebuilds the pipeline with the edges of the triangles only, or back with the material options.
+    /// The mode only changes once the new pipelines are built.
+    pub fn set_wireframe(&mut self, wireframe: bool) -> Result<()> {
+        self.rebuild(self.vert.clone(), self.frag.clone(), wireframe)
+    }
+
+    /// Rasterization options of the pipeline.
+    pub fn rasterization(&self) -> Rasterization {
+        rasterization(&self.material, self.wireframe)
+    }
+
+    /// Sets the bytes of the uniform at `binding`, a shorter block leaves the end of the uniform unchanged.
+    pub fn set_uniform(&mut self, binding: u32, data: &[u8]) -> Result<()> {
+        let uniform = self.uniforms.iter_mut()
+            .find(|u| u.binding == binding)
+            .ok_or_else(|| anyhow!("Material `{}` has no uniform at binding {}.", self.material.name(), binding))?;
+        if data.len() > uniform.data.len() {
+            return Err(anyhow!("{} bytes do not fit in the uniform of {} bytes at binding {} of material `{}`.",
+                data.len(), uniform.data.len(), binding, self.material.name()));
+        }
+        uniform.data[..data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    pub fn update_uniforms(&self, image_index: usize) -> Result<()> {
+        for uniform in self.uniforms.iter() {
+            unsafe { uniform.buffer.update_bytes(image_index, &uniform.data)?; }
+        }
+        Ok(())
+    }
+
+    /// Buffer bound at each uniform binding, the camera and lights ones are shared by every material.
+    pub fn uniform_buffers<'a>(&'a self, camera: &'a UniformBuffer, lights: &'a UniformBuffer) -> Vec<(u32, &'a UniformBuffer)> {
+        self.material.bindings().iter()
+            .filter_map(|binding| match binding.resource() {
+                MaterialResource::Camera => Some((binding.binding(), camera)),
+                MaterialResource::Lights => Some((binding.binding(), lights)),
+                MaterialResource::Uniform(_) => self.uniforms.iter()
+                    .find(|u| u.binding == binding.binding())
+                    .map(|u| (binding.binding(), &u.buffer)),
+                MaterialResource::Texture | MaterialResource::Sampler => None,
+            })
+            .collect()
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &ShaderSource> {
+        [self.material.vertex(), self.material.fragment()].into_iter()
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    pub fn reflection(&self) -> &ShaderReflection {
+        &self.reflection
+    }
+
+    /// Stages the model matrix is pushed to.
+    pub fn push_constant_stages(&self) -> vk::ShaderStageFlags {
+        push_constant_range(&self.reflection).stage_flags
     }
 
     pub fn pipeline_layout(&self) -> vk::PipelineLayout {
@@ -162,4 +480,40 @@ impl Drop for VulkanShader {
     fn drop(&mut self) {
         self.clean();
     }
-}
\ No newline at end of file
+}
+
+/// SPIR-V of both stages of a material and their interface, checked against the vertex
+/// attributes and the material bindings.
+fn load_shaders(material: &Material) -> Result<(Vec<u32>, Vec<u32>, ShaderReflection)> {
+    let vert = material.vertex().spirv(ShaderStage::Vertex)?;
+    let frag = material.fragment().spirv(ShaderStage::Fragment)?;
+    let mut reflection = reflect(&vert)?;
+    reflection.merge(&reflect(&frag)?)
+        .and_then(|_| reflection.check_vertex_inputs(&vertex_attribute_descriptions()))
+        .map_err(|e| anyhow!("Material `{}`: {}", material.name(), e))?;
+    material.check(&reflection)?;
+    Ok((vert, frag, reflection))
+}
+
+/// Options of the material, the wireframe debug view only changes the filled triangles.
+fn rasterization(material: &Material, wireframe: bool) -> Rasterization {
+    let mut rasterization = material.rasterization();
+    if wireframe && rasterization.polygon_mode() == PolygonMode::Fill {
+        rasterization.set_polygon_mode(PolygonMode::Wireframe);
+    }
+    rasterization
+}
+
+/// Push constants of the shaders, grown to hold the `PushConstantObject` pushed before each draw.
+fn push_constant_range(reflection: &ShaderReflection) -> vk::PushConstantRange {
+    let size = size_of::<PushConstantObject>() as u32;
+    let (stages, end) = match reflection.push_constant_range() {
+        Some(range) => (range.stage_flags, range.offset + range.size),
+        None => (vk::ShaderStageFlags::VERTEX, 0),
+    };
+    vk::PushConstantRange::builder()
+        .stage_flags(stages)
+        .offset(0)
+        .size(end.max(size))
+        .build()
+}
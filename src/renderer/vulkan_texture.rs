@@ -1,4 +1,4 @@
-use crate::tools::texture::Texture;
+use crate::tools::{texture::Texture, cubemap::Cubemap};
 
 use {
     std::sync::Arc,
@@ -6,46 +6,51 @@ use {
     anyhow::Result,
     super::{
         image::*,
+        allocator::{MemoryAllocator, Allocation},
     }
 };
 #[derive(Debug, Clone)]
 pub struct VulkanTexture {
     device: Arc<Device>,
+    allocator: Arc<MemoryAllocator>,
     texture_image: vk::Image,
-    texture_image_memory: vk::DeviceMemory,
+    texture_image_allocation: Allocation,
     texture_image_view: vk::ImageView,
     texture_sampler: vk::Sampler,
     is_allocated: bool,
 }
 
 impl VulkanTexture {
-    pub fn empty(device: Arc<Device>) -> Result<Self> {
+    pub fn empty(device: Arc<Device>, allocator: Arc<MemoryAllocator>) -> Result<Self> {
         Ok(VulkanTexture {
             device,
+            allocator,
             texture_image: vk::Image::default(),
-            texture_image_memory: vk::DeviceMemory::default(),
+            texture_image_allocation: Allocation::default(),
             texture_image_view: vk::ImageView::default(),
             texture_sampler: vk::Sampler::default(),
             is_allocated: false,
         })
     }
 
-    pub fn new(device: Arc<Device>,instance: &Instance,
+    pub fn new(device: Arc<Device>, allocator: Arc<MemoryAllocator>, instance: &Instance,
         physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool, 
         graphics_queue: vk::Queue, texture: Arc<Texture>) -> Result<Self>
     {
         unsafe {
             let (texture_image,
-                texture_image_memory,
+                texture_image_allocation,
                 mip_levels
-            ) = create_texture_image(instance, &device, physical_device, command_pool, graphics_queue, texture)?;
-            let texture_image_view = create_texture_image_view(&device, texture_image, mip_levels)?;
-            let texture_sampler = create_texture_sampler(&device, mip_levels)?;
+            ) = create_texture_image(instance, &device, &allocator, physical_device, command_pool, graphics_queue, texture.clone())?;
+            let texture_image_view = create_texture_image_view(&device, texture_image, texture.format(), mip_levels)?;
+            let linear = supports_linear_filter(instance, physical_device, texture_format(texture.format()));
+            let texture_sampler = create_texture_sampler(&device, mip_levels, linear)?;
         
             Ok(VulkanTexture {
                 device,
+                allocator,
                 texture_image,
-                texture_image_memory,
+                texture_image_allocation,
                 texture_image_view,
                 texture_sampler,
                 is_allocated: true,
@@ -53,20 +58,44 @@ impl VulkanTexture {
         }        
     }
 
+    /// Cube view of the six faces, sampled with a direction.
+    pub fn cubemap(device: Arc<Device>, allocator: Arc<MemoryAllocator>, instance: &Instance,
+        physical_device: vk::PhysicalDevice, command_pool: vk::CommandPool,
+        graphics_queue: vk::Queue, cubemap: &Cubemap) -> Result<Self>
+    {
+        unsafe {
+            let (texture_image,
+                texture_image_allocation
+            ) = create_cubemap_image(&device, &allocator, command_pool, graphics_queue, cubemap)?;
+            let texture_image_view = create_cubemap_image_view(&device, texture_image, cubemap.format())?;
+            let linear = supports_linear_filter(instance, physical_device, texture_format(cubemap.format()));
+            let texture_sampler = create_cubemap_sampler(&device, linear)?;
+
+            Ok(VulkanTexture {
+                device,
+                allocator,
+                texture_image,
+                texture_image_allocation,
+                texture_image_view,
+                texture_sampler,
+                is_allocated: true,
+            })
+        }
+    }
+
     pub fn clean(&mut self) {
         if self.is_allocated {
             unsafe {
                 self.device.destroy_sampler(self.texture_sampler, None);
                 self.device.destroy_image_view(self.texture_image_view, None);
-                self.device.destroy_image(self.texture_image, None);
-                self.device.free_memory(self.texture_image_memory, None);
+                destroy_image(&self.device, &self.allocator, self.texture_image, &self.texture_image_allocation);
                 self.is_allocated = false;
             }
         }
     }
 
     pub fn texture_image(&self) -> vk::Image { self.texture_image }
-    pub fn texture_image_memory(&self) -> vk::DeviceMemory { self.texture_image_memory }
+    pub fn texture_image_allocation(&self) -> &Allocation { &self.texture_image_allocation }
     pub fn texture_image_view(&self) -> vk::ImageView { self.texture_image_view }
     pub fn texture_sampler(&self) -> vk::Sampler { self.texture_sampler }
     
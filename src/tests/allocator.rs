@@ -0,0 +1,30 @@
+use crate::renderer::allocator::FreeList;
+
+#[test]
+fn allocations_are_aligned_and_padding_stays_free() {
+    let mut list = FreeList::new(1024);
+    assert_eq!(list.allocate(100, 1), Some(0));
+    assert_eq!(list.allocate(64, 256), Some(256));
+    // the padding between 100 and 256 is reused by a smaller allocation
+    assert_eq!(list.allocate(100, 4), Some(100));
+    assert_eq!(list.used_bytes(), 264);
+    assert_eq!(list.allocate(1024, 1), None);
+}
+
+#[test]
+fn freed_ranges_merge_with_their_neighbours() {
+    let mut list = FreeList::new(300);
+    let offsets = (0..3).map(|_| list.allocate(100, 1).unwrap()).collect::<Vec<_>>();
+    assert_eq!(offsets, vec![0, 100, 200]);
+    assert!(list.free_ranges().is_empty());
+
+    list.free(0, 100);
+    list.free(200, 100);
+    assert_eq!(list.free_ranges(), &[0..100, 200..300]);
+    // too fragmented for 200 bytes until the middle range is back
+    assert_eq!(list.allocate(200, 1), None);
+    list.free(100, 100);
+    assert_eq!(list.free_ranges().len(), 1);
+    assert!(list.is_empty());
+    assert_eq!(list.allocate(200, 1), Some(0));
+}
@@ -0,0 +1,54 @@
+use nalgebra_glm as glm;
+use crate::renderer::{
+    material::{Material, BlendMode, ALPHA_CUTOFF, draw_order},
+    vertex::{Vertex, InstanceData, instances_center},
+};
+
+#[test]
+fn materials_are_opaque_by_default() {
+    let mut material = Material::textured();
+    assert_eq!(material.blend_mode(), BlendMode::Opaque);
+    material.set_blend_mode(BlendMode::Alpha);
+    assert_eq!(material.blend_mode(), BlendMode::Alpha);
+}
+
+#[test]
+fn only_blended_modes_are_transparent() {
+    assert!(!BlendMode::Opaque.is_transparent());
+    assert!(!BlendMode::Cutout.is_transparent());
+    assert!(BlendMode::Alpha.is_transparent());
+    assert!(BlendMode::Additive.is_transparent());
+    assert!(BlendMode::Premultiplied.is_transparent());
+
+    assert_eq!(BlendMode::Cutout.alpha_cutoff(), ALPHA_CUTOFF);
+    assert_eq!(BlendMode::Opaque.alpha_cutoff(), 0.0);
+    assert_eq!(BlendMode::Alpha.alpha_cutoff(), 0.0);
+}
+
+#[test]
+fn transparent_objects_are_drawn_last_from_back_to_front() {
+    let objects = [
+        (BlendMode::Alpha, 2.0),
+        (BlendMode::Opaque, 1.0),
+        (BlendMode::Additive, 5.0),
+        (BlendMode::Cutout, 9.0),
+        (BlendMode::Premultiplied, 3.0),
+        (BlendMode::Opaque, 4.0),
+    ];
+    assert_eq!(draw_order(&objects), vec![1, 3, 5, 2, 4, 0]);
+    assert!(draw_order(&[]).is_empty());
+}
+
+#[test]
+fn instances_center_averages_the_placed_bounding_box() {
+    let vertex = |x, y, z| Vertex::new(glm::vec3(x, y, z), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0));
+    let vertices = [vertex(0.0, 0.0, 0.0), vertex(2.0, 4.0, 0.0), vertex(1.0, 1.0, -2.0)];
+    assert_eq!(instances_center(&vertices, &[InstanceData::default()]), glm::vec3(1.0, 2.0, -1.0));
+
+    let instances = [
+        InstanceData::from_transform(glm::translation(&glm::vec3(-3.0, 0.0, 0.0))),
+        InstanceData::from_transform(glm::translation(&glm::vec3(5.0, 0.0, 0.0))),
+    ];
+    assert_eq!(instances_center(&vertices, &instances), glm::vec3(2.0, 2.0, -1.0));
+    assert_eq!(instances_center(&[], &instances), glm::Vec3::zeros());
+}
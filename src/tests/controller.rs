@@ -0,0 +1,65 @@
+use nalgebra_glm as glm;
+use winit::event::{DeviceId, ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+use crate::renderer::{
+    camera::Camera,
+    controller::{CameraController, FlyController, OrbitController},
+};
+
+#[allow(deprecated)]
+fn key(key: VirtualKeyCode, state: ElementState) -> WindowEvent<'static> {
+    WindowEvent::KeyboardInput {
+        device_id: unsafe { DeviceId::dummy() },
+        input: KeyboardInput { scancode: 0, state, virtual_keycode: Some(key), modifiers: Default::default() },
+        is_synthetic: false,
+    }
+}
+
+fn assert_close(a: &glm::Vec3, b: &glm::Vec3) {
+    assert!(glm::distance(a, b) < 1e-4, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn fly_speed_does_not_depend_on_frame_rate() {
+    let mut one_frame = Camera::default();
+    let mut many_frames = Camera::default();
+    let mut controller = FlyController::new();
+    controller.window_event(&key(VirtualKeyCode::W, ElementState::Pressed));
+
+    controller.update(&mut one_frame, 0.5);
+    for _ in 0..10 {
+        controller.update(&mut many_frames, 0.05);
+    }
+
+    assert_close(&one_frame.position(), &many_frames.position());
+    let travelled = glm::distance(&one_frame.position(), &Camera::default().position());
+    assert!((travelled - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn fly_stops_when_key_released() {
+    let mut camera = Camera::default();
+    let mut controller = FlyController::new();
+    controller.window_event(&key(VirtualKeyCode::D, ElementState::Pressed));
+    controller.window_event(&key(VirtualKeyCode::D, ElementState::Released));
+
+    controller.update(&mut camera, 1.0);
+
+    assert_close(&camera.position(), &Camera::default().position());
+}
+
+#[test]
+fn orbit_keeps_distance_and_never_crosses_the_pole() {
+    let mut camera = Camera::default();
+    let distance = glm::distance(&camera.position(), &camera.target());
+    let mut controller = OrbitController::new();
+    controller.window_event(&key(VirtualKeyCode::Up, ElementState::Pressed));
+
+    for _ in 0..100 {
+        controller.update(&mut camera, 0.1);
+    }
+
+    let offset = camera.position() - camera.target();
+    assert!((glm::length(&offset) - distance).abs() < 1e-4);
+    assert!(glm::angle(&offset, &camera.up()) > 0.0);
+    assert_close(&camera.target(), &Camera::default().target());
+}
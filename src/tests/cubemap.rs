@@ -0,0 +1,96 @@
+use nalgebra_glm as glm;
+use crate::tools::{
+    cubemap::{Cubemap, cube_direction, equirectangular_coordinates},
+    texture::{Texture, TextureFormat},
+};
+
+fn solid(size: u32, value: u8) -> Texture {
+    Texture::from_rgba(size, size, vec![value; (size * size * 4) as usize])
+}
+
+/// Face and face coordinates Vulkan samples for a direction.
+fn select_face(direction: &glm::Vec3) -> (usize, f32, f32) {
+    let (x, y, z) = (direction.x, direction.y, direction.z);
+    let (face, sc, tc, ma) = if x.abs() >= y.abs() && x.abs() >= z.abs() {
+        if x > 0.0 { (0, -z, -y, x) } else { (1, z, -y, -x) }
+    } else if y.abs() >= z.abs() {
+        if y > 0.0 { (2, x, z, y) } else { (3, x, -z, -y) }
+    } else if z > 0.0 { (4, x, -y, z) } else { (5, -x, -y, -z) };
+    (face, sc / ma, tc / ma)
+}
+
+#[test]
+fn face_directions_follow_the_vulkan_face_selection() {
+    let axes = [
+        glm::vec3(1.0, 0.0, 0.0), glm::vec3(-1.0, 0.0, 0.0),
+        glm::vec3(0.0, 1.0, 0.0), glm::vec3(0.0, -1.0, 0.0),
+        glm::vec3(0.0, 0.0, 1.0), glm::vec3(0.0, 0.0, -1.0),
+    ];
+    for (face, axis) in axes.iter().enumerate() {
+        assert!(glm::distance(&cube_direction(face, 0.0, 0.0), axis) < 1e-6);
+        for (s, t) in [(-0.9, -0.5), (0.5, 0.25), (0.75, -0.8)] {
+            let direction = cube_direction(face, s, t);
+            assert!((glm::length(&direction) - 1.0).abs() < 1e-6);
+            let (selected, sc, tc) = select_face(&direction);
+            assert_eq!(selected, face);
+            assert!((sc - s).abs() < 1e-5 && (tc - t).abs() < 1e-5, "face {} at ({}, {})", face, s, t);
+        }
+    }
+}
+
+#[test]
+fn equirectangular_panoramas_go_around_the_up_axis() {
+    let coordinates = |x, y, z| equirectangular_coordinates(&glm::vec3(x, y, z));
+    let close = |(u, v): (f32, f32), (eu, ev): (f32, f32)| (u - eu).abs() < 1e-6 && (v - ev).abs() < 1e-6;
+    assert!(close(coordinates(1.0, 0.0, 0.0), (0.5, 0.5)));
+    assert!(close(coordinates(0.0, 1.0, 0.0), (0.75, 0.5)));
+    assert!(close(coordinates(0.0, -1.0, 0.0), (0.25, 0.5)));
+    // the top row looks up, whatever the longitude
+    assert!(coordinates(0.0, 0.0, 1.0).1.abs() < 1e-6);
+    assert!((coordinates(0.0, 0.0, -1.0).1 - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn panoramas_are_projected_on_the_faces() {
+    // white sky over a black ground
+    let (width, height) = (16, 8);
+    let data = (0..height).flat_map(|y| {
+        let value = if y < height / 2 { 255 } else { 0 };
+        std::iter::repeat_n([value, value, value, 255], width as usize).flatten()
+    }).collect();
+    let cubemap = Cubemap::from_equirectangular(&Texture::from_rgba(width, height, data), 4).unwrap();
+    assert_eq!(cubemap.size(), 4);
+    assert_eq!(cubemap.format(), TextureFormat::Rgba8Srgb);
+    assert_eq!(cubemap.data().len(), 6 * 4 * 4 * 4);
+    assert!(cubemap.faces()[4].data().iter().all(|&c| c == 255));
+    assert!(cubemap.faces()[5].data().chunks(4).all(|p| p == [0, 0, 0, 255]));
+    // the horizon splits the side faces
+    let side = cubemap.faces()[0].data();
+    assert_eq!(&side[..4], &[255, 255, 255, 255]);
+    assert_eq!(&side[side.len() - 4..], &[0, 0, 0, 255]);
+
+    let hdr = Texture::from_rgba_f32(8, 4, &[2.5; 8 * 4 * 4]);
+    let cubemap = Cubemap::from_equirectangular(&hdr, 2).unwrap();
+    assert_eq!(cubemap.format(), TextureFormat::Rgba32Float);
+    assert!(cubemap.faces().iter().all(|f| f.data().chunks(4).all(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]]) == 2.5)));
+}
+
+#[test]
+fn faces_must_be_square_and_alike() {
+    let faces = || [0, 1, 2, 3, 4, 5].map(|i| solid(2, i * 40));
+    assert!(Cubemap::from_faces(faces()).is_ok());
+
+    let mut different_size = faces();
+    different_size[3] = solid(4, 0);
+    assert!(Cubemap::from_faces(different_size).is_err());
+
+    let mut not_square = faces();
+    not_square[0] = Texture::from_rgba(2, 1, vec![0; 8]);
+    assert!(Cubemap::from_faces(not_square).is_err());
+
+    let mut different_format = faces();
+    different_format[5] = Texture::from_rgba_f32(2, 2, &[0.0; 16]);
+    assert!(Cubemap::from_faces(different_format).is_err());
+
+    assert!(Cubemap::from_equirectangular(&solid(2, 0), 0).is_err());
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use nalgebra_glm as glm;
+use crate::renderer::{
+    debug_draw::{DebugDraw, DebugDrawCall, SPHERE_SEGMENTS},
+    material::Material,
+    rasterization::{Topology, CullMode},
+};
+
+fn red() -> glm::Vec3 {
+    glm::vec3(1.0, 0.0, 0.0)
+}
+
+#[test]
+fn primitives_are_batched_by_depth_mode() {
+    let mut debug_draw = DebugDraw::default();
+    assert!(debug_draw.is_empty());
+    debug_draw.line(glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), red());
+    debug_draw.set_depth_test(false);
+    debug_draw.triangle(glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0), red());
+    debug_draw.line(glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 1.0), red());
+
+    assert_eq!(debug_draw.depth_tested().lines().len(), 2);
+    assert!(debug_draw.depth_tested().triangles().is_empty());
+    assert_eq!(debug_draw.overlay().lines().len(), 2);
+    assert_eq!(debug_draw.overlay().triangles().len(), 3);
+    // unlit
+    assert!(debug_draw.overlay().triangles().iter().all(|v| v.normal() == glm::Vec3::zeros() && v.color() == red()));
+
+    let (vertices, calls) = debug_draw.draw_calls();
+    assert_eq!(vertices.len(), 7);
+    assert_eq!(calls, vec![
+        DebugDrawCall { depth_test: true, topology: Topology::LineList, first_vertex: 0, vertex_count: 2 },
+        DebugDrawCall { depth_test: false, topology: Topology::LineList, first_vertex: 2, vertex_count: 2 },
+        DebugDrawCall { depth_test: false, topology: Topology::TriangleList, first_vertex: 4, vertex_count: 3 },
+    ]);
+
+    debug_draw.clear();
+    assert!(debug_draw.is_empty());
+    assert!(debug_draw.draw_calls().1.is_empty());
+    assert!(!debug_draw.depth_test());
+}
+
+#[test]
+fn shapes_are_made_of_lines() {
+    let mut debug_draw = DebugDraw::default();
+    debug_draw.aabb(glm::vec3(-1.0, -2.0, -3.0), glm::vec3(1.0, 2.0, 3.0), red());
+    let lines = debug_draw.depth_tested().lines().to_vec();
+    assert_eq!(lines.len(), 12 * 2);
+    for line in lines.chunks_exact(2) {
+        // edges are axis aligned and as long as the box
+        let d = line[1].pos() - line[0].pos();
+        assert_eq!(d.iter().filter(|c| **c != 0.0).count(), 1);
+        assert!(d == glm::vec3(2.0, 0.0, 0.0) || d == glm::vec3(0.0, 4.0, 0.0) || d == glm::vec3(0.0, 0.0, 6.0));
+    }
+
+    debug_draw.clear();
+    let center = glm::vec3(1.0, 1.0, 1.0);
+    debug_draw.sphere(center, 2.0, red());
+    assert_eq!(debug_draw.depth_tested().lines().len(), 3 * SPHERE_SEGMENTS * 2);
+    assert!(debug_draw.depth_tested().lines().iter().all(|v| (glm::distance(&v.pos(), &center) - 2.0).abs() < 1e-5));
+
+    debug_draw.clear();
+    debug_draw.arrow(glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 5.0), red());
+    let lines = debug_draw.depth_tested().lines();
+    assert_eq!(lines.len(), 5 * 2);
+    // the head lines go back from the tip
+    assert!(lines[2..].chunks_exact(2).all(|l| l[0].pos() == glm::vec3(0.0, 0.0, 5.0) && l[1].pos().z == 4.0));
+}
+
+#[test]
+fn debug_materials_are_double_sided() {
+    let tested = Material::debug(true);
+    let overlay = Material::debug(false);
+    assert!(tested.depth_test());
+    assert!(!overlay.depth_test());
+    assert_eq!(overlay.rasterization().cull_mode(), CullMode::None);
+    assert!(!overlay.uses_texture());
+    assert!(Material::textured().depth_test());
+}
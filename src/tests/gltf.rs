@@ -0,0 +1,151 @@
+use std::{env, fs, sync::Arc};
+use nalgebra_glm as glm;
+use crate::{
+    object::{Object, mesh::Mesh},
+    tools::{loader::Loader, model::Model, texture::Texture},
+};
+
+/// Binary glTF with one textured triangle, its mesh on a child node of a translated node.
+fn triangle_glb(png: &[u8]) -> Vec<u8> {
+    let mut bin = Vec::new();
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        bin.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in [0u16, 1, 2, 0] {
+        bin.extend_from_slice(&index.to_le_bytes());
+    }
+    let image_offset = bin.len();
+    bin.extend_from_slice(png);
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+
+    let json = format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [{{ "translation": [1.0, 0.0, 0.0], "children": [1] }}, {{ "mesh": 0 }}],
+        "meshes": [{{ "name": "triangle", "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }}] }}],
+        "materials": [{{ "name": "painted", "pbrMetallicRoughness": {{ "baseColorFactor": [1.0, 0.5, 0.5, 0.25], "baseColorTexture": {{ "index": 0 }} }} }}],
+        "textures": [{{ "source": 0 }}],
+        "images": [{{ "bufferView": 2, "mimeType": "image/png" }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }},
+            {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }}
+        ],
+        "buffers": [{{ "byteLength": {} }}]
+    }}"#, image_offset, png.len(), bin.len());
+    let mut json = json.into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+
+    let mut glb = Vec::new();
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+#[test]
+fn glb_with_embedded_texture() {
+    let directory = env::temp_dir().join(format!("vulkan-rs-gltf-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let png_path = directory.join("paint.png");
+    Texture::from_rgba(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 255])
+        .save_png(png_path.to_str().unwrap())
+        .unwrap();
+    let glb_path = directory.join("triangle.glb");
+    fs::write(&glb_path, triangle_glb(&fs::read(&png_path).unwrap())).unwrap();
+
+    let model = Arc::new(Model::new(glb_path.to_str().unwrap()).unwrap());
+    assert_eq!(model.indices(), &[0, 1, 2]);
+    // the parent node translation is applied
+    assert_eq!(model.vertices()[1].pos(), glm::vec3(2.0, 0.0, 0.0));
+    // normals are generated when the primitive has none
+    assert_eq!(model.vertices()[0].normal(), glm::vec3(0.0, 0.0, 1.0));
+    assert_eq!(model.vertices()[0].color(), glm::vec3(1.0, 0.5, 0.5));
+
+    let material = model.submesh_material(0).unwrap();
+    assert_eq!(material.name(), "painted");
+    assert_eq!(material.opacity(), 0.25);
+    let image = material.diffuse_image().unwrap();
+    assert_eq!((image.width(), image.height()), (2, 1));
+    assert_eq!(image.data(), &[255, 0, 0, 255, 0, 255, 0, 255]);
+
+    let meshes = Mesh::from_materials(model, &mut Loader::<Texture>::default()).unwrap();
+    assert!(Arc::ptr_eq(&meshes[0].texture().unwrap(), &image));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+/// Text glTF of one textured triangle with authored normals, its buffer and image in separate files.
+fn triangle_gltf(bin_uri: &str, image_uri: &str) -> (String, Vec<u8>) {
+    let mut bin = Vec::new();
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        bin.extend_from_slice(&value.to_le_bytes());
+    }
+    for _ in 0..3 {
+        for value in [0.0f32, 0.6, 0.8] {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    let json = format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [{{ "mesh": 0 }}],
+        "meshes": [{{ "name": "triangle", "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1 }}, "material": 0 }}] }}],
+        "materials": [{{ "name": "painted", "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0 }} }} }}],
+        "textures": [{{ "source": 0 }}],
+        "images": [{{ "uri": "{}" }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+            {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 36 }}
+        ],
+        "buffers": [{{ "uri": "{}", "byteLength": {} }}]
+    }}"#, image_uri, bin_uri, bin.len());
+    (json, bin)
+}
+
+#[test]
+fn gltf_with_external_buffer_and_image() {
+    let directory = env::temp_dir().join(format!("vulkan-rs-gltf-external-{}", std::process::id()));
+    fs::create_dir_all(directory.join("textures")).unwrap();
+    Texture::from_rgba(1, 2, vec![0, 0, 255, 255, 255, 255, 255, 255])
+        .save_png(directory.join("textures/paint.png").to_str().unwrap())
+        .unwrap();
+    let (json, bin) = triangle_gltf("triangle.bin", "textures/paint.png");
+    fs::write(directory.join("triangle.bin"), bin).unwrap();
+    let gltf_path = directory.join("triangle.gltf");
+    fs::write(&gltf_path, json).unwrap();
+
+    // the uris are resolved relative to the file, not to the working directory
+    let model = Model::new(gltf_path.to_str().unwrap()).unwrap();
+    assert_eq!(model.indices(), &[0, 1, 2]);
+    assert_eq!(model.vertices()[1].pos(), glm::vec3(1.0, 0.0, 0.0));
+    // authored normals are kept
+    assert!(glm::distance(&model.vertices()[0].normal(), &glm::vec3(0.0, 0.6, 0.8)) < 1e-6);
+
+    let image = model.submesh_material(0).unwrap().diffuse_image().unwrap();
+    assert_eq!((image.width(), image.height()), (1, 2));
+    assert_eq!(image.data(), &[0, 0, 255, 255, 255, 255, 255, 255]);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,191 @@
+//!
+//! Golden-image regression tests.
+//!
+//! Every scene is rendered through a headless renderer and compared to `tests/golden/<scene>.png`.
+//! On failure the rendered frame and a diff image are written to `target/golden/`.
+//!
+//! - `GOLDEN_BLESS=1` writes the rendered frames as the new references.
+//! - `GOLDEN_REQUIRE=1` fails instead of skipping when no Vulkan loader or device is available
+//!   (set it in CI, with a CPU implementation such as lavapipe on machines without a GPU).
+//!
+
+use {
+    std::{env, fs, path::Path},
+    anyhow::Result,
+    nalgebra_glm as glm,
+    crate::{
+        renderer::{renderer::Renderer, vertex::Vertex, camera::Camera},
+        object::{Object, cube::Cube, sphere::Sphere, rectangle::Rectangle, mesh::Mesh},
+        tools::{loader::{Loader, Loadable}, texture::Texture, model::Model},
+    },
+};
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+const REFERENCE_DIR: &str = "tests/golden";
+const OUTPUT_DIR: &str = "target/golden";
+
+/// Maximum difference allowed on each channel of a pixel.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Fraction of pixels allowed to exceed the tolerance (multisampled edges differ between drivers).
+const MAX_MISMATCH_RATIO: f32 = 0.002;
+
+//================================================
+// Scenes
+//================================================
+
+#[test]
+fn golden_cube() {
+    check_scene("cube", |app, _| {
+        let one = Vertex::new(glm::vec3(-0.5, 0.5, -0.5), glm::vec3(0.8, 0.3, 0.2), glm::vec2(0.0, 0.0));
+        let mut cube = Cube::from_one(one, 1.0, 1.0, 1.0, None);
+        cube.set_fn_update_matrix(rotated_matrix);
+        app.set_camera(corner_camera());
+        app.add_object(&cube)?;
+        Ok(())
+    });
+}
+
+#[test]
+fn golden_sphere() {
+    check_scene("sphere", |app, _| {
+        let center = Vertex::new(glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.2, 0.6, 0.9), glm::vec2(0.0, 0.0));
+        let sphere = Sphere::new(center, 16, 16, None);
+        app.set_camera(corner_camera());
+        app.add_object(&sphere)?;
+        Ok(())
+    });
+}
+
+#[test]
+fn golden_rectangle() {
+    check_scene("rectangle", |app, loaders| {
+        let texture = loaders.textures.load("resources/texture.png")?;
+        let one = Vertex::new(glm::vec3(-0.5, -0.5, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0));
+        let rectangle = Rectangle::from_one(one, 1.0, 1.0, Some(texture));
+        app.set_camera(facing_camera());
+        app.add_object(&rectangle)?;
+        Ok(())
+    });
+}
+
+#[test]
+fn golden_viking_room() {
+    check_scene("viking_room", |app, loaders| {
+        let texture = loaders.textures.load("resources/viking_room.png")?;
+        let model = loaders.models.load("resources/viking_room.obj")?;
+        let room = Mesh::new(model, Some(texture));
+        app.set_camera(corner_camera());
+        app.add_object(&room)?;
+        Ok(())
+    });
+}
+
+//================================================
+// Scene matrices, independent of the elapsed time
+//================================================
+
+fn corner_camera() -> Camera {
+    Camera::perspective(
+        glm::vec3(2.0, 2.0, 2.0),
+        glm::vec3(0.0, 0.0, 0.0),
+        glm::vec3(0.0, 0.0, 1.0),
+        glm::radians(&glm::vec1(45.0))[0],
+        0.1,
+        10.0,
+    )
+}
+
+fn facing_camera() -> Camera {
+    Camera::perspective(
+        glm::vec3(0.0, 0.0, 2.0),
+        glm::vec3(0.0, 0.0, 0.0),
+        glm::vec3(0.0, 1.0, 0.0),
+        glm::radians(&glm::vec1(45.0))[0],
+        0.1,
+        10.0,
+    )
+}
+
+fn rotated_matrix(_: usize, _: f32) -> glm::Mat4 {
+    glm::rotate(&glm::identity(), glm::radians(&glm::vec1(30.0))[0], &glm::vec3(0.0, 0.0, 1.0))
+}
+
+//================================================
+// Harness
+//================================================
+
+struct Loaders {
+    textures: Loader<Texture>,
+    models: Loader<Model>,
+}
+
+/// Renders a scene offscreen and compares it to its reference image.
+fn check_scene(name: &str, fill: impl FnOnce(&mut Renderer, &mut Loaders) -> Result<()>) {
+    let mut app = match Renderer::create_headless(WIDTH, HEIGHT) {
+        Ok(app) => app,
+        Err(e) if env::var_os("GOLDEN_REQUIRE").is_none() => {
+            eprintln!("skipping golden scene `{}`, no usable Vulkan loader or device (set GOLDEN_REQUIRE=1 to fail instead): {}", name, e);
+            return;
+        },
+        Err(e) => panic!("unable to create a headless renderer: {}", e),
+    };
+    let mut loaders = Loaders { textures: Loader::default(), models: Loader::default() };
+    fill(&mut app, &mut loaders).expect("unable to build the scene");
+    let actual = app.render_to_texture().expect("unable to render the scene");
+
+    let reference_path = format!("{}/{}.png", REFERENCE_DIR, name);
+    if env::var_os("GOLDEN_BLESS").is_some() {
+        actual.save_png(&reference_path).expect("unable to write the reference");
+        return;
+    }
+    if !Path::new(&reference_path).exists() {
+        panic!("missing reference `{}`, run the tests with GOLDEN_BLESS=1 to create it", reference_path);
+    }
+    let expected = Texture::load(&reference_path).expect("unable to read the reference");
+
+    if let Some(message) = compare(&expected, &actual, name) {
+        panic!("golden scene `{}` differs from its reference: {}", name, message);
+    }
+}
+
+/// Returns a description of the mismatch, after writing the rendered frame and a diff image.
+fn compare(expected: &Texture, actual: &Texture, name: &str) -> Option<String> {
+    fs::create_dir_all(OUTPUT_DIR).expect("unable to create the output directory");
+    let actual_path = format!("{}/{}.actual.png", OUTPUT_DIR, name);
+
+    if expected.width() != actual.width() || expected.height() != actual.height() {
+        actual.save_png(&actual_path).ok();
+        return Some(format!("size {}x{} instead of {}x{}",
+            actual.width(), actual.height(), expected.width(), expected.height()));
+    }
+
+    let (mismatches, diff) = diff_pixels(expected.data(), actual.data());
+    let pixels = (actual.width() * actual.height()) as usize;
+    if mismatches as f32 <= MAX_MISMATCH_RATIO * pixels as f32 {
+        return None;
+    }
+
+    let diff_path = format!("{}/{}.diff.png", OUTPUT_DIR, name);
+    actual.save_png(&actual_path).ok();
+    Texture::from_rgba(actual.width(), actual.height(), diff).save_png(&diff_path).ok();
+    Some(format!("{} of {} pixels above tolerance, see `{}` and `{}`", mismatches, pixels, actual_path, diff_path))
+}
+
+/// Counts the RGBA pixels differing by more than the tolerance and builds a diff image:
+/// mismatching pixels in red over a dimmed copy of the expected image.
+fn diff_pixels(expected: &[u8], actual: &[u8]) -> (usize, Vec<u8>) {
+    let mut mismatches = 0;
+    let mut diff = Vec::with_capacity(expected.len());
+    for (e, a) in expected.chunks(4).zip(actual.chunks(4)) {
+        let differs = e.iter().zip(a).any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE);
+        if differs {
+            mismatches += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 12) as u8;
+            diff.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+    (mismatches, diff)
+}
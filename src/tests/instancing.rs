@@ -0,0 +1,40 @@
+use std::{mem::size_of, sync::Arc};
+use nalgebra_glm as glm;
+use crate::{
+    object::{Object, mesh::Mesh, instanced::Instanced},
+    renderer::vertex::{Vertex, InstanceData},
+    tools::model::Model,
+};
+
+#[test]
+fn instance_attributes_follow_vertex_locations() {
+    let attributes = InstanceData::attribute_descriptions();
+    let locations = attributes.iter().map(|a| a.location).collect::<Vec<_>>();
+    assert_eq!(locations, vec![4, 5, 6, 7, 8]);
+    assert!(attributes.iter().all(|a| a.binding == InstanceData::binding_description().binding));
+    // the tint follows the four columns of the transform
+    assert_eq!(attributes[4].offset as usize, size_of::<glm::Mat4>());
+    assert_eq!(InstanceData::binding_description().stride as usize, size_of::<InstanceData>());
+    assert_eq!(Vertex::attribute_descriptions().iter().map(|a| a.location).max(), Some(3));
+}
+
+#[test]
+fn instanced_object_keeps_its_model() {
+    let vertices = (0..3).map(|i| Vertex::new(glm::vec3(i as f32, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0))).collect();
+    let model = Arc::new(Model::construct(vertices, vec![0, 1, 2]));
+    let mesh = Mesh::new(model.clone(), None);
+    assert!(mesh.instances().is_none());
+
+    let transforms = (0..10)
+        .map(|i| glm::translate(&glm::identity(), &glm::vec3(i as f32, 0.0, 0.0)))
+        .collect::<Vec<_>>();
+    let mut forest = Instanced::from_transforms(mesh, &transforms);
+    forest.push_instance(InstanceData::new(glm::identity(), glm::vec3(1.0, 0.0, 0.0)));
+
+    let instances = forest.instances().unwrap();
+    assert_eq!(instances.len(), 11);
+    assert_eq!(instances[3].transform(), transforms[3]);
+    assert_eq!(instances[3].tint(), glm::vec3(1.0, 1.0, 1.0));
+    assert_eq!(instances[10].tint(), glm::vec3(1.0, 0.0, 0.0));
+    assert!(Arc::ptr_eq(&forest.shared_model().unwrap().0, &model));
+}
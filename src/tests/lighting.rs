@@ -0,0 +1,51 @@
+use std::mem::size_of;
+use nalgebra_glm as glm;
+use crate::{
+    renderer::{light::{Lights, LightsUniform, PointLight, MAX_POINT_LIGHTS}, vertex::Vertex},
+    object::{Object, generate_normals, cube::Cube, rectangle::Rectangle},
+};
+
+fn vertex(x: f32, y: f32, z: f32) -> Vertex {
+    Vertex::new(glm::vec3(x, y, z), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0))
+}
+
+#[test]
+fn generated_normals_follow_the_winding() {
+    let mut vertices = vec![vertex(0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0), vertex(5.0, 5.0, 5.0)];
+    generate_normals(&mut vertices, &[0, 1, 2]);
+
+    for vertex in &vertices[..3] {
+        assert_eq!(vertex.normal(), glm::vec3(0.0, 0.0, 1.0));
+    }
+    assert_eq!(vertices[3].normal(), glm::Vec3::zeros());
+}
+
+#[test]
+fn primitives_have_unit_normals() {
+    let cube = Cube::from_one(vertex(0.0, 0.0, 0.0), 1.0, 1.0, 1.0, None);
+    let rectangle = Rectangle::from_one(vertex(0.0, 0.0, 0.0), 1.0, 1.0, None);
+    let objects: [&dyn Object; 2] = [&cube, &rectangle];
+    for object in objects {
+        for vertex in object.vertices() {
+            assert!((glm::length(&vertex.normal()) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    // flat primitives are drawn double sided instead of duplicating their faces
+    assert!(rectangle.vertices().iter().all(|v| v.normal() == glm::vec3(0.0, 0.0, 1.0)));
+    assert_eq!(rectangle.indices().len(), 6);
+    assert!(rectangle.double_sided());
+    assert!(!cube.double_sided());
+}
+
+#[test]
+fn point_lights_are_limited() {
+    let mut lights = Lights::default();
+    let light = PointLight::new(glm::vec3(0.0, 0.0, 1.0), glm::vec3(1.0, 1.0, 1.0), 1.0, 5.0);
+    for _ in 0..MAX_POINT_LIGHTS {
+        lights.add_point_light(light).unwrap();
+    }
+    assert!(lights.add_point_light(light).is_err());
+    // std140: 4 vec4 then two vec4 per point light
+    assert_eq!(size_of::<LightsUniform>(), 16 * (4 + 2 * MAX_POINT_LIGHTS));
+}
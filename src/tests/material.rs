@@ -0,0 +1,49 @@
+use crate::renderer::{
+    material::{Material, MaterialBinding, MaterialResource},
+    shader_source::ShaderSource,
+};
+
+fn material(bindings: Vec<MaterialBinding>) -> Material {
+    Material::new("custom",
+        ShaderSource::File("shaders/custom.vert".into()),
+        ShaderSource::File("shaders/custom.frag".into()),
+        bindings)
+}
+
+#[test]
+fn built_in_materials_are_valid() {
+    assert!(Material::textured().validate().is_ok());
+    assert!(Material::untextured().validate().is_ok());
+    assert!(Material::textured().uses_texture());
+    assert!(!Material::untextured().uses_texture());
+}
+
+#[test]
+fn custom_material_gives_its_uniform_sizes() {
+    let material = material(vec![
+        MaterialBinding::new(0, MaterialResource::Camera),
+        MaterialBinding::new(1, MaterialResource::Uniform(32)),
+        MaterialBinding::new(2, MaterialResource::Uniform(16)),
+        MaterialBinding::new(3, MaterialResource::Texture),
+        MaterialBinding::new(4, MaterialResource::Sampler),
+    ]);
+    assert!(material.validate().is_ok());
+    assert_eq!(material.uniform_size(1), Some(32));
+    assert_eq!(material.uniform_size(0), None);
+    assert!(material.uses_texture());
+}
+
+#[test]
+fn invalid_materials_are_rejected() {
+    let duplicate = material(vec![
+        MaterialBinding::new(0, MaterialResource::Camera),
+        MaterialBinding::new(0, MaterialResource::Lights),
+    ]);
+    assert!(duplicate.validate().is_err());
+
+    let empty_uniform = material(vec![MaterialBinding::new(0, MaterialResource::Uniform(0))]);
+    assert!(empty_uniform.validate().is_err());
+
+    let texture_without_sampler = material(vec![MaterialBinding::new(0, MaterialResource::Texture)]);
+    assert!(texture_without_sampler.validate().is_err());
+}
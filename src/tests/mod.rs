@@ -0,0 +1,19 @@
+mod golden;
+mod controller;
+mod lighting;
+mod model;
+mod gltf;
+mod texture;
+mod instancing;
+mod allocator;
+mod pipeline_cache;
+
+mod shaders;
+mod material;
+mod reflection;
+mod blending;
+mod rasterization;
+mod topology;
+mod debug_draw;
+mod cubemap;
+mod render_settings;
\ No newline at end of file
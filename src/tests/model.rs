@@ -0,0 +1,117 @@
+use std::{env, fs, sync::Arc};
+use nalgebra_glm as glm;
+use crate::{
+    object::{Object, mesh::Mesh},
+    tools::{loader::Loader, model::Model, texture::Texture},
+};
+
+const OBJ: &str = "\
+mtllib scene.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 1
+o red
+usemtl red
+f 1/1 2/2 3/3
+o painted
+usemtl painted
+f 2/2 4/4 3/3
+";
+
+const MTL: &str = "\
+newmtl red
+Kd 1 0 0
+Ks 0.5 0.5 0.5
+Ns 10
+d 0.5
+newmtl painted
+Kd 1 1 1
+map_Kd paint.png
+";
+
+#[test]
+fn obj_materials_are_kept_per_submesh() {
+    let directory = env::temp_dir().join(format!("vulkan-rs-model-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("scene.obj"), OBJ).unwrap();
+    fs::write(directory.join("scene.mtl"), MTL).unwrap();
+    Texture::from_rgba(1, 1, vec![255, 255, 255, 255])
+        .save_png(directory.join("paint.png").to_str().unwrap())
+        .unwrap();
+
+    let model = Arc::new(Model::new(directory.join("scene.obj").to_str().unwrap()).unwrap());
+    assert_eq!(model.submeshes().len(), 2);
+    assert_eq!(model.submesh_indices(0).len(), 3);
+    assert_eq!(model.submesh_indices(1).len(), 3);
+
+    let red = model.submesh_material(0).unwrap();
+    assert_eq!(red.name(), "red");
+    assert_eq!(red.diffuse(), glm::vec3(1.0, 0.0, 0.0));
+    assert_eq!(red.specular(), glm::vec3(0.5, 0.5, 0.5));
+    assert_eq!(red.opacity(), 0.5);
+    assert!(red.diffuse_texture().is_none());
+    // the diffuse color is baked in the vertices
+    assert_eq!(model.vertices()[model.submesh_indices(0)[0] as usize].color(), glm::vec3(1.0, 0.0, 0.0));
+
+    let mut loader = Loader::<Texture>::default();
+    let meshes = Mesh::from_materials(model.clone(), &mut loader).unwrap();
+    assert!(meshes[0].texture().is_none());
+    assert!(meshes[1].texture().is_some());
+    assert_eq!(meshes[1].indices(), model.submesh_indices(1));
+    // every sub-mesh draws its range of the shared model buffers
+    let (shared, range) = meshes[1].shared_model().unwrap();
+    assert!(Arc::ptr_eq(&shared, &model));
+    assert_eq!(range, 3..6);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn missing_mtl_keeps_geometry() {
+    let directory = env::temp_dir().join(format!("vulkan-rs-model-nomtl-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("scene.obj"), OBJ).unwrap();
+
+    let model = Model::new(directory.join("scene.obj").to_str().unwrap()).unwrap();
+    assert!(model.materials().is_empty());
+    assert_eq!(model.indices().len(), 6);
+    assert!(model.submeshes().iter().all(|s| s.material().is_none()));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+const MIXED_NORMALS_OBJ: &str = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vn 0 0.6 0.8
+o authored
+f 1//1 2//1 3//1
+o generated
+f 2 4 3
+";
+
+#[test]
+fn obj_normals_are_generated_per_submesh() {
+    let directory = env::temp_dir().join(format!("vulkan-rs-model-normals-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("scene.obj"), MIXED_NORMALS_OBJ).unwrap();
+
+    let model = Model::new(directory.join("scene.obj").to_str().unwrap()).unwrap();
+    assert_eq!(model.submeshes().len(), 2);
+    // the normals of the file are kept, only the sub-mesh without normals gets face normals
+    for &index in model.submesh_indices(0) {
+        assert_eq!(model.vertices()[index as usize].normal(), glm::vec3(0.0, 0.6, 0.8));
+    }
+    for &index in model.submesh_indices(1) {
+        assert_eq!(model.vertices()[index as usize].normal(), glm::vec3(0.0, 0.0, 1.0));
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use crate::renderer::pipeline_cache::{PipelineCacheHeader, is_cache_compatible};
+
+fn header() -> PipelineCacheHeader {
+    PipelineCacheHeader::new(0x10de, 0x2204, [7; 16])
+}
+
+#[test]
+fn header_round_trips_through_cache_data() {
+    let mut data = header().to_bytes();
+    data.extend_from_slice(&[0xab; 64]);
+    assert_eq!(PipelineCacheHeader::parse(&data), Some(header()));
+    assert!(is_cache_compatible(&data, &header()));
+}
+
+#[test]
+fn cache_of_another_device_or_driver_is_rejected() {
+    let data = header().to_bytes();
+    assert!(!is_cache_compatible(&data, &PipelineCacheHeader::new(0x1002, 0x2204, [7; 16])));
+    assert!(!is_cache_compatible(&data, &PipelineCacheHeader::new(0x10de, 0x1234, [7; 16])));
+    assert!(!is_cache_compatible(&data, &PipelineCacheHeader::new(0x10de, 0x2204, [8; 16])));
+    // truncated file or unknown header version
+    assert!(!is_cache_compatible(&data[..20], &header()));
+    let mut version_two = data.clone();
+    version_two[4] = 2;
+    assert!(!is_cache_compatible(&version_two, &header()));
+    assert!(!is_cache_compatible(&[], &header()));
+}
@@ -0,0 +1,72 @@
+use vulkanalia::prelude::v1_0::*;
+use nalgebra_glm as glm;
+use crate::{
+    renderer::{
+        material::Material,
+        vertex::Vertex,
+        rasterization::{Rasterization, RasterizationFeatures, PolygonMode, CullMode, FrontFace, DepthBias},
+    },
+    object::{Object, triangle::Triangle, circle::Circle, instanced::Instanced},
+};
+
+#[test]
+fn default_rasterization_fills_the_front_faces() {
+    let rasterization = Rasterization::default();
+    assert_eq!(rasterization.vk_polygon_mode(), vk::PolygonMode::FILL);
+    assert_eq!(rasterization.vk_cull_mode(), vk::CullModeFlags::BACK);
+    assert_eq!(rasterization.vk_front_face(), vk::FrontFace::COUNTER_CLOCKWISE);
+    assert_eq!(rasterization.depth_bias(), None);
+    assert_eq!(Material::textured().rasterization(), rasterization);
+
+    assert_eq!(Rasterization::double_sided().vk_cull_mode(), vk::CullModeFlags::NONE);
+    assert_eq!(Material::untextured_double_sided().rasterization().cull_mode(), CullMode::None);
+}
+
+#[test]
+fn rasterization_options_map_to_vulkan() {
+    let mut rasterization = Rasterization::default();
+    rasterization.set_polygon_mode(PolygonMode::Points);
+    rasterization.set_cull_mode(CullMode::Front);
+    rasterization.set_front_face(FrontFace::Clockwise);
+    assert_eq!(rasterization.vk_polygon_mode(), vk::PolygonMode::POINT);
+    assert_eq!(rasterization.vk_cull_mode(), vk::CullModeFlags::FRONT);
+    assert_eq!(rasterization.vk_front_face(), vk::FrontFace::CLOCKWISE);
+    rasterization.set_polygon_mode(PolygonMode::Wireframe);
+    assert_eq!(rasterization.vk_polygon_mode(), vk::PolygonMode::LINE);
+}
+
+#[test]
+fn rasterization_needs_the_device_features() {
+    let none = RasterizationFeatures::default();
+    let all = RasterizationFeatures { fill_mode_non_solid: true, depth_bias_clamp: true };
+    assert!(Rasterization::default().check(&none).is_ok());
+
+    let mut wireframe = Rasterization::default();
+    wireframe.set_polygon_mode(PolygonMode::Wireframe);
+    assert!(wireframe.check(&none).is_err());
+    assert!(wireframe.check(&all).is_ok());
+
+    let mut biased = Rasterization::default();
+    biased.set_depth_bias(Some(DepthBias { constant_factor: 1.0, slope_factor: 1.5, clamp: 0.0 }));
+    assert!(biased.check(&none).is_ok());
+    biased.set_depth_bias(Some(DepthBias { constant_factor: 1.0, slope_factor: 1.5, clamp: 0.01 }));
+    assert!(biased.check(&none).is_err());
+    assert!(biased.check(&all).is_ok());
+
+    let features = vk::PhysicalDeviceFeatures { fill_mode_non_solid: vk::TRUE, ..Default::default() };
+    assert_eq!(RasterizationFeatures::supported(&features),
+        RasterizationFeatures { fill_mode_non_solid: true, depth_bias_clamp: false });
+}
+
+#[test]
+fn flat_objects_do_not_duplicate_their_faces() {
+    let vertex = Vertex::new(glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0));
+    let triangle = Triangle::from_one(vertex, 1.0, 1.0, None);
+    assert_eq!(triangle.vertices().len(), 3);
+    assert_eq!(triangle.indices().len(), 3);
+
+    let circle = Circle::new(vertex, 1.0, 8, None);
+    assert_eq!(circle.indices().len(), 8 * 3);
+    assert!(circle.double_sided());
+    assert!(Instanced::new(circle, vec![]).double_sided());
+}
@@ -0,0 +1,110 @@
+use {
+    vulkanalia::prelude::v1_0::*,
+    crate::renderer::{
+        reflection::{ShaderReflection, reflect},
+        material::{Material, BlendMode},
+        vertex::vertex_attribute_descriptions,
+        shader_source::{ShaderStage, compile_glsl, spirv_from_bytes},
+    },
+};
+
+fn reflect_pair(vert: &[u8], frag: &[u8]) -> ShaderReflection {
+    let mut reflection = reflect(&spirv_from_bytes(vert).unwrap()).unwrap();
+    reflection.merge(&reflect(&spirv_from_bytes(frag).unwrap()).unwrap()).unwrap();
+    reflection
+}
+
+fn textured() -> ShaderReflection {
+    reflect_pair(include_bytes!("../../shaders/texture_vert.spv"), include_bytes!("../../shaders/texture_frag.spv"))
+}
+
+#[test]
+fn textured_shaders_are_reflected() {
+    let reflection = textured();
+    assert_eq!(reflection.stages(), vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT);
+
+    let bindings = reflection.bindings().iter()
+        .map(|b| (b.binding(), b.descriptor_type(), b.stages()))
+        .collect::<Vec<_>>();
+    assert_eq!(bindings, vec![
+        (0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT),
+        (1, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::FRAGMENT),
+        (2, vk::DescriptorType::SAMPLED_IMAGE, vk::ShaderStageFlags::FRAGMENT),
+        (3, vk::DescriptorType::SAMPLER, vk::ShaderStageFlags::FRAGMENT),
+    ]);
+    // view, proj and position
+    assert_eq!(reflection.bindings()[0].block_size(), Some(144));
+    assert_eq!(reflection.descriptor_counts(), vec![
+        (vk::DescriptorType::UNIFORM_BUFFER, 2),
+        (vk::DescriptorType::SAMPLED_IMAGE, 1),
+        (vk::DescriptorType::SAMPLER, 1),
+    ]);
+
+    // the model matrix and the alpha cutoff read by the fragment shader
+    let push_constants = reflection.push_constant_range().unwrap();
+    assert_eq!((push_constants.stage_flags, push_constants.offset, push_constants.size),
+        (vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT, 0, 68));
+
+    let locations = reflection.vertex_inputs().iter().map(|i| i.location()).collect::<Vec<_>>();
+    assert_eq!(locations, (0..=8).collect::<Vec<_>>());
+    assert!(reflection.check_vertex_inputs(&vertex_attribute_descriptions()).is_ok());
+}
+
+#[test]
+fn materials_are_checked_against_the_shaders() {
+    let reflection = textured();
+    assert!(Material::textured().check(&reflection).is_ok());
+    // the untextured material does not bind the texture read by the shader
+    assert!(Material::untextured().check(&reflection).is_err());
+
+    let untextured = reflect_pair(include_bytes!("../../shaders/only_color_vert.spv"), include_bytes!("../../shaders/only_color_frag.spv"));
+    assert!(Material::untextured().check(&untextured).is_ok());
+    // the textured material binds a texture the shaders do not declare
+    assert!(Material::textured().check(&untextured).is_err());
+
+    // only the textured fragment shader discards the fragments under the alpha cutoff
+    let mut cutout = Material::textured();
+    cutout.set_blend_mode(BlendMode::Cutout);
+    assert!(cutout.check(&reflection).is_ok());
+    let mut cutout = Material::untextured();
+    cutout.set_blend_mode(BlendMode::Cutout);
+    assert!(cutout.check(&untextured).is_err());
+
+    // the cube image is a sampled image like the 2D ones, only the position is read
+    let skybox = reflect_pair(include_bytes!("../../shaders/skybox_vert.spv"), include_bytes!("../../shaders/skybox_frag.spv"));
+    assert!(Material::skybox().check(&skybox).is_ok());
+    assert!(skybox.push_constant_range().is_none());
+    assert!(skybox.check_vertex_inputs(&vertex_attribute_descriptions()).is_ok());
+}
+
+#[test]
+fn vertex_inputs_must_match_the_vertex_attributes() {
+    let wrong_format = "#version 450\nlayout(location = 0) in vec4 inPosition;\nvoid main() { gl_Position = inPosition; }\n";
+    let reflection = reflect(&compile_glsl(wrong_format, ShaderStage::Vertex).unwrap()).unwrap();
+    assert_eq!(reflection.vertex_inputs()[0].format(), vk::Format::R32G32B32A32_SFLOAT);
+    assert!(reflection.check_vertex_inputs(&vertex_attribute_descriptions()).is_err());
+
+    let unknown_location = "#version 450\nlayout(location = 12) in vec3 inPosition;\nvoid main() { gl_Position = vec4(inPosition, 1.0); }\n";
+    let reflection = reflect(&compile_glsl(unknown_location, ShaderStage::Vertex).unwrap()).unwrap();
+    assert!(reflection.check_vertex_inputs(&vertex_attribute_descriptions()).is_err());
+}
+
+#[test]
+fn blocks_follow_their_explicit_layout() {
+    let source = "#version 450
+layout(location = 0) out vec4 outColor;
+layout(set = 0, binding = 5) uniform Params { mat4 transform; vec3 tint; float strength; } params;
+layout(std430, set = 1, binding = 0) buffer Data { vec4 values[]; } data;
+void main() { outColor = params.transform * data.values[0] * params.strength; }
+";
+    let reflection = reflect(&compile_glsl(source, ShaderStage::Fragment).unwrap()).unwrap();
+    let bindings = reflection.bindings().iter()
+        .map(|b| (b.set(), b.binding(), b.descriptor_type(), b.block_size()))
+        .collect::<Vec<_>>();
+    assert_eq!(bindings, vec![
+        (0, 5, vk::DescriptorType::UNIFORM_BUFFER, Some(80)),
+        (1, 0, vk::DescriptorType::STORAGE_BUFFER, Some(0)),
+    ]);
+    assert!(reflection.push_constant_range().is_none());
+    assert!(reflect(&[0x0723_0203, 0, 0]).is_err());
+}
@@ -0,0 +1,84 @@
+use {
+    std::mem::size_of,
+    nalgebra_glm as glm,
+    crate::renderer::{
+        render_settings::{RenderSettings, Gradient, GradientUniform, default_clear_color},
+        material::{Material, MaterialResource},
+        reflection::{ShaderReflection, reflect},
+        shader_source::spirv_from_bytes,
+    },
+};
+
+#[test]
+fn default_settings_clear_to_the_previous_values() {
+    let settings = RenderSettings::default();
+    assert_eq!(settings.clear_color(), glm::vec4(0.0, 0.1, 0.1, 1.0));
+    assert_eq!(settings.clear_color(), default_clear_color());
+    assert_eq!(settings.clear_depth(), 1.0);
+    assert!(settings.gradient().is_none());
+
+    let [color, depth] = settings.clear_values();
+    unsafe {
+        assert_eq!(color.color.float32, [0.0, 0.1, 0.1, 1.0]);
+        assert_eq!(depth.depth_stencil.depth, 1.0);
+        assert_eq!(depth.depth_stencil.stencil, 0);
+    }
+}
+
+#[test]
+fn settings_are_changed_in_place() {
+    let mut settings = RenderSettings::default();
+    settings.set_clear_color(glm::vec4(1.0, 0.5, 0.25, 1.0));
+    settings.set_clear_depth(0.5);
+    let [color, depth] = settings.clear_values();
+    unsafe {
+        assert_eq!(color.color.float32, [1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(depth.depth_stencil.depth, 0.5);
+    }
+    // out of the depth range of the viewport
+    settings.set_clear_depth(2.0);
+    assert_eq!(settings.clear_depth(), 1.0);
+    settings.set_clear_depth(-1.0);
+    assert_eq!(settings.clear_depth(), 0.0);
+}
+
+#[test]
+fn gradients_go_from_the_top_to_the_bottom() {
+    let top = glm::vec4(0.0, 0.0, 1.0, 1.0);
+    let bottom = glm::vec4(1.0, 1.0, 1.0, 1.0);
+    let mut gradient = Gradient::new(top, bottom);
+    assert_eq!(gradient.color_at(0.0), top);
+    assert_eq!(gradient.color_at(1.0), bottom);
+    assert_eq!(gradient.color_at(0.5), glm::vec4(0.5, 0.5, 1.0, 1.0));
+    assert_eq!(gradient.color_at(3.0), bottom);
+    gradient.set_bottom(top);
+    assert_eq!(gradient.color_at(0.7), top);
+
+    let mut settings = RenderSettings::default();
+    settings.set_gradient(Some(gradient));
+    assert_eq!(settings.gradient(), Some(gradient));
+}
+
+#[test]
+fn gradient_material_matches_its_shaders() {
+    let mut reflection: ShaderReflection = reflect(&spirv_from_bytes(include_bytes!("../../shaders/gradient_vert.spv")).unwrap()).unwrap();
+    reflection.merge(&reflect(&spirv_from_bytes(include_bytes!("../../shaders/gradient_frag.spv")).unwrap()).unwrap()).unwrap();
+    // the full-screen triangle has no vertex input
+    assert!(reflection.vertex_inputs().is_empty());
+
+    let material = Material::gradient();
+    assert!(material.check(&reflection).is_ok());
+    assert!(!material.depth_test());
+    assert_eq!(material.bindings()[0].resource(), MaterialResource::Uniform(size_of::<GradientUniform>() as u64));
+    assert_eq!(reflection.bindings()[0].block_size(), Some(size_of::<GradientUniform>() as u64));
+}
+#[test]
+fn gradient_uniform_bytes_hold_the_top_then_bottom_color() {
+    let gradient = Gradient::new(glm::vec4(1.0, 0.0, 0.0, 1.0), glm::vec4(0.0, 0.0, 0.5, 1.0));
+    let bytes = gradient.uniform().bytes();
+    assert_eq!(bytes.len(), size_of::<GradientUniform>());
+    let floats = bytes.chunks_exact(4)
+        .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect::<Vec<_>>();
+    assert_eq!(floats, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.5, 1.0]);
+}
\ No newline at end of file
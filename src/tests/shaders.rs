@@ -0,0 +1,59 @@
+use {
+    std::{fs, path::Path, time::{Duration, SystemTime}},
+    crate::renderer::shader_source::{ShaderSource, ShaderStage, FileWatcher, compile_glsl, spirv_from_bytes},
+};
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+#[test]
+fn repo_shaders_compile_from_glsl() {
+    for (path, stage) in [
+        ("shaders/texture.vert", ShaderStage::Vertex),
+        ("shaders/texture.frag", ShaderStage::Fragment),
+        ("shaders/only_color.vert", ShaderStage::Vertex),
+        ("shaders/only_color.frag", ShaderStage::Fragment),
+        ("shaders/skybox.vert", ShaderStage::Vertex),
+        ("shaders/skybox.frag", ShaderStage::Fragment),
+        ("shaders/gradient.vert", ShaderStage::Vertex),
+        ("shaders/gradient.frag", ShaderStage::Fragment),
+    ] {
+        let spirv = ShaderSource::File(path.into()).spirv(stage)
+            .unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(spirv[0], SPIRV_MAGIC, "{}", path);
+    }
+}
+
+#[test]
+fn invalid_glsl_is_reported() {
+    let source = "#version 450\nvoid main() { gl_Position = undefined_value; }\n";
+    assert!(compile_glsl(source, ShaderStage::Vertex).is_err());
+}
+
+#[test]
+fn embedded_spirv_is_checked() {
+    let spirv = ShaderSource::Embedded(include_bytes!("../../shaders/texture_vert.spv"))
+        .spirv(ShaderStage::Vertex)
+        .unwrap();
+    assert_eq!(spirv[0], SPIRV_MAGIC);
+    assert!(spirv_from_bytes(&[1, 2, 3]).is_err());
+    assert!(spirv_from_bytes(&[0; 8]).is_err());
+}
+
+#[test]
+fn watcher_reports_modified_files_once() {
+    let path = std::env::temp_dir().join(format!("vulkan-rs-watcher-{}.frag", std::process::id()));
+    fs::write(&path, "old").unwrap();
+    let mut watcher = FileWatcher::new(Duration::ZERO);
+    watcher.watch(&path);
+    assert!(watcher.changed().is_empty());
+
+    fs::write(&path, "new").unwrap();
+    let file = fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+    assert_eq!(watcher.changed(), vec![path.clone()]);
+    assert!(watcher.changed().is_empty());
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(watcher.changed(), vec![path.clone()]);
+    assert!(!Path::new(&path).exists());
+}
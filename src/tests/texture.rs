@@ -0,0 +1,101 @@
+use png::{BitDepth, ColorType};
+use crate::tools::texture::{decode, decode_png, Texture, TextureFormat};
+
+/// Encodes a 2x1 PNG in memory.
+fn encode(color: ColorType, depth: BitDepth, data: &[u8], palette: Option<(Vec<u8>, Vec<u8>)>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        if let Some((palette, trns)) = palette {
+            encoder.set_palette(palette);
+            encoder.set_trns(trns);
+        }
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+    }
+    bytes
+}
+
+fn decode_2x1(bytes: Vec<u8>) -> Vec<u8> {
+    let texture = decode_png(bytes.as_slice()).unwrap();
+    assert_eq!((texture.width(), texture.height()), (2, 1));
+    texture.data().to_vec()
+}
+
+#[test]
+fn png_color_types_are_expanded_to_rgba() {
+    assert_eq!(decode_2x1(encode(ColorType::RGB, BitDepth::Eight, &[1, 2, 3, 4, 5, 6], None)),
+        [1, 2, 3, 255, 4, 5, 6, 255]);
+    assert_eq!(decode_2x1(encode(ColorType::Grayscale, BitDepth::Eight, &[10, 20], None)),
+        [10, 10, 10, 255, 20, 20, 20, 255]);
+    assert_eq!(decode_2x1(encode(ColorType::GrayscaleAlpha, BitDepth::Eight, &[10, 1, 20, 2], None)),
+        [10, 10, 10, 1, 20, 20, 20, 2]);
+    // 1 bit per pixel: 0b01 packed in the high bits
+    assert_eq!(decode_2x1(encode(ColorType::Grayscale, BitDepth::One, &[0b0100_0000], None)),
+        [0, 0, 0, 255, 255, 255, 255, 255]);
+}
+
+#[test]
+fn png_palette_and_transparency_are_expanded() {
+    let palette = (vec![255, 0, 0, 0, 0, 255], vec![128]);
+    assert_eq!(decode_2x1(encode(ColorType::Indexed, BitDepth::Eight, &[0, 1], Some(palette))),
+        [255, 0, 0, 128, 0, 0, 255, 255]);
+}
+
+#[test]
+fn png_16_bits_keep_the_high_byte() {
+    let data = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xff, 0xff, 0x00, 0x00, 0x80, 0x00];
+    assert_eq!(decode_2x1(encode(ColorType::RGB, BitDepth::Sixteen, &data, None)),
+        [0x12, 0x56, 0x9a, 255, 0xff, 0x00, 0x80, 255]);
+}
+
+#[test]
+fn invalid_png_is_an_error() {
+    assert!(decode_png(&b"not a png"[..]).is_err());
+}
+
+fn encode_with(format: image::ImageFormat) -> Vec<u8> {
+    let image = image::RgbImage::from_raw(2, 1, vec![200, 100, 50, 10, 20, 30]).unwrap();
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, format).unwrap();
+    bytes.into_inner()
+}
+
+#[test]
+fn bmp_tga_and_jpeg_are_decoded_to_rgba() {
+    let lossless = [200, 100, 50, 255, 10, 20, 30, 255];
+    assert_eq!(decode_any(&encode_with(image::ImageFormat::Bmp), "bmp").data(), lossless);
+    assert_eq!(decode_any(&encode_with(image::ImageFormat::Tga), "tga").data(), lossless);
+
+    let jpeg = decode_any(&encode_with(image::ImageFormat::Jpeg), "jpg");
+    assert_eq!(jpeg.format(), TextureFormat::Rgba8Srgb);
+    assert_eq!(jpeg.data().len(), 8);
+    // the extension does not matter for formats with magic bytes
+    assert_eq!(decode_any(&encode_with(image::ImageFormat::Png), "jpg").data(), lossless);
+}
+
+#[test]
+fn hdr_keeps_float_colors() {
+    let pixels = [image::Rgb([4.0f32, 0.5, 0.25]), image::Rgb([0.0, 1.0, 16.0])];
+    let mut bytes = Vec::new();
+    image::codecs::hdr::HdrEncoder::new(&mut bytes).encode(&pixels, 2, 1).unwrap();
+
+    let texture = decode_any(&bytes, "hdr");
+    assert_eq!(texture.format(), TextureFormat::Rgba32Float);
+    let values = texture.data()
+        .chunks_exact(4)
+        .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [4.0, 0.5, 0.25, 1.0, 0.0, 1.0, 16.0, 1.0]);
+}
+
+#[test]
+fn unknown_format_is_an_error() {
+    assert!(decode(b"GIF89a", "gif").is_err());
+}
+
+fn decode_any(bytes: &[u8], extension: &str) -> Texture {
+    decode(bytes, extension).unwrap()
+}
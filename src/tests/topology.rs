@@ -0,0 +1,58 @@
+use vulkanalia::prelude::v1_0::*;
+use nalgebra_glm as glm;
+use crate::{
+    renderer::{rasterization::Topology, vertex::Vertex},
+    object::{
+        Object, cube::Cube, polyline::Polyline, axis_gizmo::AxisGizmo, grid::Grid, point_cloud::PointCloud,
+        instanced::Instanced,
+    },
+};
+
+#[test]
+fn topologies_map_to_vulkan() {
+    assert_eq!(Topology::default(), Topology::TriangleList);
+    assert_eq!(Topology::LineStrip.vk_topology(), vk::PrimitiveTopology::LINE_STRIP);
+    assert_eq!(Topology::TriangleFan.vk_topology(), vk::PrimitiveTopology::TRIANGLE_FAN);
+    assert_eq!(Topology::PointList.vk_topology(), vk::PrimitiveTopology::POINT_LIST);
+    assert!(Topology::LineStrip.is_strip());
+    assert!(Topology::TriangleStrip.is_strip());
+    assert!(!Topology::LineList.is_strip());
+    assert!(!Topology::TriangleList.is_strip());
+}
+
+#[test]
+fn objects_declare_their_topology() {
+    let vertex = Vertex::new(glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec2(0.0, 0.0));
+    let cube = Cube::from_one(vertex, 1.0, 1.0, 1.0, None);
+    assert_eq!(cube.topology(), Topology::TriangleList);
+    assert_eq!(AxisGizmo::new(1.0).topology(), Topology::LineList);
+    assert_eq!(Grid::new(1.0, 2, glm::vec3(0.5, 0.5, 0.5)).topology(), Topology::LineList);
+    assert_eq!(PointCloud::from_points(&[], glm::vec3(1.0, 1.0, 1.0)).topology(), Topology::PointList);
+    assert_eq!(Instanced::new(AxisGizmo::new(1.0), vec![]).topology(), Topology::LineList);
+}
+
+#[test]
+fn polylines_can_be_closed() {
+    let points = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 0.0)];
+    let open = Polyline::new(&points, glm::vec3(1.0, 1.0, 1.0), false);
+    assert_eq!(open.topology(), Topology::LineStrip);
+    assert_eq!(open.indices(), &[0, 1, 2]);
+    let closed = Polyline::new(&points, glm::vec3(1.0, 1.0, 1.0), true);
+    assert_eq!(closed.indices(), &[0, 1, 2, 0]);
+}
+
+#[test]
+fn line_objects_have_one_segment_per_index_pair() {
+    let gizmo = AxisGizmo::new(2.0);
+    assert_eq!(gizmo.indices().len(), 6);
+    assert_eq!(gizmo.vertices()[1].pos(), glm::vec3(2.0, 0.0, 0.0));
+    assert_eq!(gizmo.vertices()[5].color(), glm::vec3(0.0, 0.0, 1.0));
+
+    // 3 lines along each axis, on the XY plane
+    let grid = Grid::new(4.0, 2, glm::vec3(0.5, 0.5, 0.5));
+    assert_eq!(grid.indices().len(), 2 * 2 * 3);
+    assert!(grid.vertices().iter().all(|v| v.pos().z == 0.0 && v.pos().x.abs() <= 2.0 && v.pos().y.abs() <= 2.0));
+
+    let cloud = PointCloud::from_points(&[glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 2.0, 3.0)], glm::vec3(1.0, 0.0, 0.0));
+    assert_eq!(cloud.indices(), &[0, 1]);
+}
@@ -0,0 +1,127 @@
+use {
+    std::f32::consts::PI,
+    anyhow::{Result, anyhow},
+    nalgebra_glm as glm,
+    super::{
+        loader::Loadable,
+        texture::{Texture, TextureFormat},
+    },
+};
+
+/// Six square faces of the same size and format, in the Vulkan layer order:
+/// +X, -X, +Y, -Y, +Z, -Z.
+pub struct Cubemap {
+    faces: [Texture; 6],
+}
+
+impl Cubemap {
+    pub fn from_faces(faces: [Texture; 6]) -> Result<Self> {
+        let (size, format) = (faces[0].width(), faces[0].format());
+        if size == 0 {
+            return Err(anyhow!("Cubemap faces can not be empty."));
+        }
+        if let Some(face) = faces.iter().find(|f| f.width() != size || f.height() != size || f.format() != format) {
+            return Err(anyhow!("Cubemap faces must all be {}x{} {:?} images, found a {}x{} {:?} one.",
+                size, size, format, face.width(), face.height(), face.format()));
+        }
+        Ok(Self { faces })
+    }
+
+    /// Loads the faces from six image files, given in the order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn load_faces(paths: [&str; 6]) -> Result<Self> {
+        let [px, nx, py, ny, pz, nz] = paths.map(Texture::load);
+        Self::from_faces([px?, nx?, py?, ny?, pz?, nz?])
+    }
+
+    /// Projects an equirectangular panorama on faces of `face_size` pixels. The panorama goes
+    /// around the Z axis, the default up of the camera, its top row looking up.
+    pub fn from_equirectangular(panorama: &Texture, face_size: u32) -> Result<Self> {
+        if panorama.width() == 0 || panorama.height() == 0 || face_size == 0 {
+            return Err(anyhow!("Unable to make a cubemap from an empty panorama."));
+        }
+        let faces = [0, 1, 2, 3, 4, 5].map(|face| {
+            let mut pixels = Vec::with_capacity((face_size * face_size) as usize * 4);
+            for y in 0..face_size {
+                for x in 0..face_size {
+                    let s = 2.0 * (x as f32 + 0.5) / face_size as f32 - 1.0;
+                    let t = 2.0 * (y as f32 + 0.5) / face_size as f32 - 1.0;
+                    let (u, v) = equirectangular_coordinates(&cube_direction(face, s, t));
+                    pixels.extend(sample_bilinear(panorama, u, v));
+                }
+            }
+            match panorama.format() {
+                TextureFormat::Rgba8Srgb => Texture::from_rgba(face_size, face_size,
+                    pixels.iter().map(|c| c.round().clamp(0.0, 255.0) as u8).collect()),
+                TextureFormat::Rgba32Float => Texture::from_rgba_f32(face_size, face_size, &pixels),
+            }
+        });
+        Self::from_faces(faces)
+    }
+
+    /// Loads a panorama, usually a Radiance HDR, and projects it with `from_equirectangular`.
+    pub fn load_equirectangular(path: &str, face_size: u32) -> Result<Self> {
+        Self::from_equirectangular(&Texture::load(path)?, face_size)
+    }
+
+    pub fn faces(&self) -> &[Texture; 6] { &self.faces }
+    pub fn size(&self) -> u32 { self.faces[0].width() }
+    pub fn format(&self) -> TextureFormat { self.faces[0].format() }
+
+    /// Pixels of the six faces one after the other.
+    pub fn data(&self) -> Vec<u8> {
+        self.faces.iter().flat_map(|f| f.data().iter().copied()).collect()
+    }
+}
+
+/// Direction sampled at the face coordinates `s` and `t` in [-1, 1] of a cube face,
+/// `t` going down the face, following the cube map face selection of Vulkan.
+pub fn cube_direction(face: usize, s: f32, t: f32) -> glm::Vec3 {
+    let direction = match face {
+        0 => glm::vec3(1.0, -t, -s),
+        1 => glm::vec3(-1.0, -t, s),
+        2 => glm::vec3(s, 1.0, t),
+        3 => glm::vec3(s, -1.0, -t),
+        4 => glm::vec3(s, -t, 1.0),
+        _ => glm::vec3(-s, -t, -1.0),
+    };
+    glm::normalize(&direction)
+}
+
+/// Texture coordinates of a direction in an equirectangular panorama around the Z axis.
+pub fn equirectangular_coordinates(direction: &glm::Vec3) -> (f32, f32) {
+    let u = 0.5 + direction.y.atan2(direction.x) / (2.0 * PI);
+    let v = 0.5 - direction.z.clamp(-1.0, 1.0).asin() / PI;
+    (u, v)
+}
+
+/// RGBA color at the texture coordinates, wrapping horizontally and clamped vertically.
+/// 8 bits textures give values in [0, 255].
+fn sample_bilinear(texture: &Texture, u: f32, v: f32) -> [f32; 4] {
+    let (width, height) = (texture.width() as i64, texture.height() as i64);
+    let x = u * width as f32 - 0.5;
+    let y = v * height as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |x: i64, y: i64| {
+        let index = (y.clamp(0, height - 1) * width + x.rem_euclid(width)) as usize;
+        texel(texture, index)
+    };
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let [a, b, c, d] = [pixel(x0, y0), pixel(x0 + 1, y0), pixel(x0, y0 + 1), pixel(x0 + 1, y0 + 1)];
+    std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+fn texel(texture: &Texture, index: usize) -> [f32; 4] {
+    let size = texture.format().bytes_per_pixel();
+    let bytes = &texture.data()[index * size..(index + 1) * size];
+    match texture.format() {
+        TextureFormat::Rgba8Srgb => std::array::from_fn(|i| bytes[i] as f32),
+        TextureFormat::Rgba32Float => std::array::from_fn(|i| {
+            f32::from_ne_bytes([bytes[4 * i], bytes[4 * i + 1], bytes[4 * i + 2], bytes[4 * i + 3]])
+        }),
+    }
+}
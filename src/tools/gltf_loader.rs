@@ -0,0 +1,165 @@
+use {
+    std::{sync::Arc, collections::HashMap},
+    log::*,
+    nalgebra_glm as glm,
+    anyhow::{Result, anyhow},
+    gltf::{image::Format, mesh::Mode},
+    crate::{renderer::vertex::Vertex, object::generate_normals},
+    super::{
+        material::{Material, SubMesh},
+        texture::Texture,
+    },
+};
+
+//================================================
+// load glTF
+//================================================
+
+/// Loads a `.gltf` or `.glb` file with its external or embedded buffers and images.
+///
+/// The node hierarchy of the default scene is flattened: every triangle primitive becomes a
+/// sub-mesh whose vertices are moved by the world transform of its node. Vertex colors are
+/// multiplied by the base color factor, base color textures become the diffuse images of the materials.
+pub(crate) fn load_gltf(url: &str,
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    materials: &mut Vec<Material>,
+    submeshes: &mut Vec<SubMesh>) -> Result<()>
+{
+    let (document, buffers, images) = gltf::import(url)?;
+
+    let mut textures = HashMap::<usize, Arc<Texture>>::new();
+    for material in document.materials() {
+        let pbr = material.pbr_metallic_roughness();
+        let base_color = pbr.base_color_factor();
+        let metallic = pbr.metallic_factor();
+        let roughness = pbr.roughness_factor();
+        let diffuse = glm::vec3(base_color[0], base_color[1], base_color[2]);
+        // Rough Blinn-Phong equivalent of the metallic-roughness model
+        let specular = glm::lerp(&glm::vec3(0.04, 0.04, 0.04), &diffuse, metallic);
+        let shininess = (2.0 / roughness.max(0.01).powi(4) - 2.0).clamp(1.0, 1024.0);
+
+        let mut converted = Material::new(material.name().unwrap_or(""), diffuse, None, specular, shininess, base_color[3]);
+        if let Some(info) = pbr.base_color_texture() {
+            let index = info.texture().source().index();
+            let texture = match textures.get(&index) {
+                Some(texture) => texture.clone(),
+                None => {
+                    let texture = Arc::new(convert_image(&images[index])?);
+                    textures.insert(index, texture.clone());
+                    texture
+                },
+            };
+            converted.set_diffuse_image(Some(texture));
+        }
+        materials.push(converted);
+    }
+
+    let scene = document.default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| anyhow!("`{}` has no scene.", url))?;
+    let mut nodes = scene.nodes()
+        .map(|node| (node, glm::Mat4::identity()))
+        .collect::<Vec<_>>();
+
+    while let Some((node, parent)) = nodes.pop() {
+        let transform = parent * glm::make_mat4(node.transform().matrix().as_flattened());
+        nodes.extend(node.children().map(|child| (child, transform)));
+
+        let mesh = match node.mesh() {
+            Some(mesh) => mesh,
+            None => continue,
+        };
+        let normal_matrix = glm::mat4_to_mat3(&glm::transpose(&glm::inverse(&transform)));
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                warn!("Skipping {:?} primitive of mesh `{}` in `{}`.", primitive.mode(), mesh.name().unwrap_or(""), url);
+                continue;
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions = match reader.read_positions() {
+                Some(positions) => positions.collect::<Vec<_>>(),
+                None => continue,
+            };
+
+            let normals = reader.read_normals().map(|n| n.collect::<Vec<_>>());
+            let tex_coords = reader.read_tex_coords(0).map(|t| t.into_f32().collect::<Vec<_>>());
+            let colors = reader.read_colors(0).map(|c| c.into_rgba_f32().collect::<Vec<_>>());
+            let material = primitive.material().index();
+            let base_color = material.map(|m| materials[m].diffuse()).unwrap_or_else(|| glm::vec3(1.0, 1.0, 1.0));
+
+            let first_vertex = vertices.len();
+            for (i, position) in positions.iter().enumerate() {
+                let position = transform * glm::vec4(position[0], position[1], position[2], 1.0);
+                let normal = normals.as_ref()
+                    .map(|n| glm::normalize(&(normal_matrix * glm::make_vec3(&n[i]))))
+                    .unwrap_or_else(glm::Vec3::zeros);
+                let tex_coord = tex_coords.as_ref().map(|t| glm::make_vec2(&t[i])).unwrap_or_else(glm::Vec2::zeros);
+                let color = colors.as_ref()
+                    .map(|c| glm::vec3(c[i][0], c[i][1], c[i][2]))
+                    .unwrap_or_else(|| glm::vec3(1.0, 1.0, 1.0));
+                vertices.push(Vertex::with_normal(position.xyz(), color.component_mul(&base_color), tex_coord, normal));
+            }
+
+            let primitive_indices = match reader.read_indices() {
+                Some(read) => read.into_u32().collect::<Vec<_>>(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            if normals.is_none() {
+                generate_normals(&mut vertices[first_vertex..], &primitive_indices);
+            }
+
+            let first_index = indices.len();
+            indices.extend(primitive_indices.iter().map(|i| i + first_vertex as u32));
+            submeshes.push(SubMesh::new(mesh.name().unwrap_or(""), first_index, primitive_indices.len(), material));
+        }
+    }
+
+    Ok(())
+}
+
+/// Expands a decoded glTF image to 8 bits RGBA.
+fn convert_image(image: &gltf::image::Data) -> Result<Texture> {
+    let pixels = &image.pixels;
+    let (channels, bytes): (usize, usize) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let count = (image.width * image.height) as usize;
+    if pixels.len() < count * channels * bytes {
+        return Err(anyhow!("glTF image of {}x{} pixels has only {} bytes.", image.width, image.height, pixels.len()));
+    }
+
+    let channel = |pixel: usize, c: usize| -> u8 {
+        let offset = (pixel * channels + c) * bytes;
+        match bytes {
+            1 => pixels[offset],
+            2 => (u16::from_ne_bytes([pixels[offset], pixels[offset + 1]]) >> 8) as u8,
+            _ => {
+                let value = f32::from_ne_bytes([pixels[offset], pixels[offset + 1], pixels[offset + 2], pixels[offset + 3]]);
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            },
+        }
+    };
+
+    let mut data = Vec::with_capacity(count * 4);
+    for pixel in 0..count {
+        let rgba = match channels {
+            1 => { let l = channel(pixel, 0); [l, l, l, 255] },
+            2 => { let l = channel(pixel, 0); [l, l, l, channel(pixel, 1)] },
+            3 => [channel(pixel, 0), channel(pixel, 1), channel(pixel, 2), 255],
+            _ => [channel(pixel, 0), channel(pixel, 1), channel(pixel, 2), channel(pixel, 3)],
+        };
+        data.extend_from_slice(&rgba);
+    }
+    Ok(Texture::from_rgba(image.width, image.height, data))
+}
@@ -13,7 +13,7 @@ use {
 pub trait Loadable 
 where 
     Self: Sized {
-    fn load(path: &String) -> Result<Self, Error>;
+    fn load(path: &str) -> Result<Self, Error>;
 }
 
 ///
@@ -35,13 +35,13 @@ where
     pub fn new() -> Self {
         Self::default()
     }
-    pub fn load(&mut self, path: &String) -> Result<Arc<T>, Error> {
+    pub fn load(&mut self, path: &str) -> Result<Arc<T>, Error> {
         if let Some(item) = self.item_loaded.get(path) {
             Ok(item.clone())   //case where item is already loaded
         }
         else {      //case where we need to load a new item            
             let item = Arc::new(T::load(path)?);
-            self.item_loaded.insert(path.clone(), item.clone());
+            self.item_loaded.insert(path.to_string(), item.clone());
             Ok(item.clone())
         }
     }
@@ -0,0 +1,72 @@
+use {
+    std::{path::Path, sync::Arc},
+    nalgebra_glm as glm,
+    super::texture::Texture,
+};
+
+/// Surface properties of a sub-mesh, as described by a MTL file or a glTF material.
+#[derive(Clone)]
+pub struct Material {
+    name: String,
+    diffuse: glm::Vec3,
+    /// Path of the diffuse texture, relative paths are resolved against the model directory.
+    diffuse_texture: Option<String>,
+    /// Diffuse texture already decoded, for images embedded in the model file.
+    diffuse_image: Option<Arc<Texture>>,
+    specular: glm::Vec3,
+    shininess: f32,
+    opacity: f32,
+}
+
+impl Material {
+    pub fn new(name: &str, diffuse: glm::Vec3, diffuse_texture: Option<String>, specular: glm::Vec3, shininess: f32, opacity: f32) -> Self {
+        Self { name: name.to_string(), diffuse, diffuse_texture, diffuse_image: None, specular, shininess, opacity }
+    }
+
+    pub(crate) fn from_mtl(material: &tobj::Material, directory: &Path) -> Self {
+        let diffuse_texture = if material.diffuse_texture.is_empty() {
+            None
+        } else {
+            Some(directory.join(&material.diffuse_texture).to_string_lossy().into_owned())
+        };
+        Self {
+            name: material.name.clone(),
+            diffuse: glm::make_vec3(&material.diffuse),
+            diffuse_texture,
+            diffuse_image: None,
+            specular: glm::make_vec3(&material.specular),
+            shininess: material.shininess,
+            opacity: material.dissolve,
+        }
+    }
+
+    pub fn name(&self) -> &str { self.name.as_ref() }
+    pub fn diffuse(&self) -> glm::Vec3 { self.diffuse }
+    pub fn diffuse_texture(&self) -> Option<&str> { self.diffuse_texture.as_deref() }
+    pub fn diffuse_image(&self) -> Option<Arc<Texture>> { self.diffuse_image.clone() }
+    pub fn set_diffuse_image(&mut self, image: Option<Arc<Texture>>) { self.diffuse_image = image; }
+    pub fn specular(&self) -> glm::Vec3 { self.specular }
+    pub fn shininess(&self) -> f32 { self.shininess }
+    pub fn opacity(&self) -> f32 { self.opacity }
+}
+
+/// Range of the model indices drawn with the same material.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubMesh {
+    name: String,
+    first_index: usize,
+    index_count: usize,
+    material: Option<usize>,
+}
+
+impl SubMesh {
+    pub fn new(name: &str, first_index: usize, index_count: usize, material: Option<usize>) -> Self {
+        Self { name: name.to_string(), first_index, index_count, material }
+    }
+
+    pub fn name(&self) -> &str { self.name.as_ref() }
+    pub fn first_index(&self) -> usize { self.first_index }
+    pub fn index_count(&self) -> usize { self.index_count }
+    /// Index in `Model::materials`.
+    pub fn material(&self) -> Option<usize> { self.material }
+}
@@ -1,3 +1,6 @@
 pub mod loader;
 pub mod texture;
-pub mod model;
\ No newline at end of file
+pub mod model;pub mod material;
+pub mod gltf_loader;
+
+pub mod cubemap;
\ No newline at end of file
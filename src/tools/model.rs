@@ -1,8 +1,10 @@
 use super::loader::Loadable;
 
 use {
-    std::{io::BufReader, fs::File, collections::HashMap},
-    crate::{renderer::{vertex::Vertex}},
+    std::{io::BufReader, fs::File, collections::HashMap, path::Path, ops::Range},
+    log::*,
+    crate::{renderer::{vertex::Vertex}, object::generate_normals},
+    super::{material::{Material, SubMesh}, gltf_loader::load_gltf},
     nalgebra_glm as glm,
     anyhow::{Result, anyhow},
 };
@@ -10,21 +12,34 @@ use {
 pub struct Model {
     vertices: Vec<Vertex>,
     indices: Vec<u32>,
+    materials: Vec<Material>,
+    submeshes: Vec<SubMesh>,
 }
 
 impl Model {
-    pub fn new(path: &String) -> Result<Self> {
+    /// Loads a `.gltf`/`.glb` file, or an OBJ file for any other extension.
+    pub fn new(path: &str) -> Result<Self> {
         let mut vertices = Vec::<Vertex>::new();
         let mut indices = Vec::<u32>::new();
+        let mut materials = Vec::<Material>::new();
+        let mut submeshes = Vec::<SubMesh>::new();
 
-        load_model(path, &mut vertices, &mut indices)?;
+        let extension = Path::new(path).extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "gltf" | "glb" => load_gltf(path, &mut vertices, &mut indices, &mut materials, &mut submeshes)?,
+            _ => load_model(path, &mut vertices, &mut indices, &mut materials, &mut submeshes)?,
+        }
 
         Ok(Self {
-            vertices, indices,  
+            vertices, indices, materials, submeshes,
         })
     }
+    /// Model made of a single sub-mesh without material.
     pub fn construct(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
-        Self { vertices, indices }
+        let submeshes = vec![SubMesh::new("", 0, indices.len(), None)];
+        Self { vertices, indices, materials: vec![], submeshes }
     }
     pub fn vertices(&self) -> &[Vertex] {
         self.vertices.as_ref()
@@ -33,10 +48,33 @@ impl Model {
     pub fn indices(&self) -> &[u32] {
         self.indices.as_ref()
     }
+
+    pub fn materials(&self) -> &[Material] {
+        self.materials.as_ref()
+    }
+
+    pub fn submeshes(&self) -> &[SubMesh] {
+        self.submeshes.as_ref()
+    }
+
+    /// Indices of a single sub-mesh.
+    pub fn submesh_indices(&self, submesh: usize) -> &[u32] {
+        &self.indices[self.submesh_range(submesh)]
+    }
+
+    /// Range of the model indices belonging to the sub-mesh.
+    pub fn submesh_range(&self, submesh: usize) -> Range<usize> {
+        let submesh = &self.submeshes[submesh];
+        submesh.first_index()..submesh.first_index() + submesh.index_count()
+    }
+
+    pub fn submesh_material(&self, submesh: usize) -> Option<&Material> {
+        self.submeshes[submesh].material().map(|m| &self.materials[m])
+    }
 }
 
 impl Loadable for Model {
-    fn load(path: &String) -> Result<Self, anyhow::Error> {
+    fn load(path: &str) -> Result<Self, anyhow::Error> {
         Model::new(path)
     }
 }
@@ -45,34 +83,66 @@ impl Loadable for Model {
 // load Model
 //================================================
 
-pub(crate) fn load_model(url: &String, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) -> Result<()> {
+/// Loads an OBJ file and the MTL files it refers to, a missing MTL file only loses the materials.
+/// Each OBJ object/material pair becomes a sub-mesh; the diffuse color is baked in the vertex color.
+pub(crate) fn load_model(url: &str, 
+    vertices: &mut Vec<Vertex>, 
+    indices: &mut Vec<u32>,
+    materials: &mut Vec<Material>,
+    submeshes: &mut Vec<SubMesh>) -> Result<()> 
+{
     let mut reader = BufReader::new(File::open(url)?);
+    let directory = Path::new(url).parent().unwrap_or_else(|| Path::new(""));
 
-    let (models, _) = match tobj::load_obj_buf(&mut reader, true, |_| {
-        Ok((vec![tobj::Material::empty()], HashMap::new()))
+    let (models, mtl_materials) = match tobj::load_obj_buf(&mut reader, true, |mtl_path| {
+        let path = directory.join(mtl_path);
+        tobj::load_mtl(&path).or_else(|e| {
+            warn!("Unable to load materials `{}` of `{}`: {}", path.display(), url, e);
+            Ok((vec![], HashMap::new()))
+        })
     }) {
         Ok(tuple) => tuple,
         Err(e) => return Err(anyhow!(e))
     };
 
+    materials.extend(mtl_materials.iter().map(|m| Material::from_mtl(m, directory)));
+
     let mut unique_vertices = HashMap::new();
 
+    //indices of the sub-meshes without normals in the file
+    let mut generated = vec![];
+
     for model in &models {
-        for index in &model.mesh.indices {
+        let mesh = &model.mesh;
+        let color = mesh.material_id
+            .map(|m| materials[m].diffuse())
+            .unwrap_or_else(|| glm::vec3(1.0, 1.0, 1.0));
+        let first_index = indices.len();
+        let has_normals = !mesh.normals.is_empty();
+
+        for index in &mesh.indices {
             let pos_offset = (3 * index) as usize;
             let tex_coord_offset = (2 * index) as usize;
 
-            let vertex = Vertex::new (
+            let tex_coord = if mesh.texcoords.is_empty() {
+                glm::vec2(0.0, 0.0)
+            } else {
+                glm::vec2(mesh.texcoords[tex_coord_offset], 1.0 - mesh.texcoords[tex_coord_offset + 1])
+            };
+            let normal = if has_normals {
+                glm::vec3(mesh.normals[pos_offset], mesh.normals[pos_offset + 1], mesh.normals[pos_offset + 2])
+            } else {
+                glm::Vec3::zeros()
+            };
+            let vertex = Vertex::with_normal (
                 glm::vec3 (
-                    model.mesh.positions[pos_offset],
-                    model.mesh.positions[pos_offset + 1],
-                    model.mesh.positions[pos_offset + 2],
+                    mesh.positions[pos_offset],
+                    mesh.positions[pos_offset + 1],
+                    mesh.positions[pos_offset + 2],
                 ),
-                glm::vec3(1.0, 1.0, 1.0),
-                glm::vec2 (
-                    model.mesh.texcoords[tex_coord_offset],
-                    1.0 - model.mesh.texcoords[tex_coord_offset + 1],
-                ),   
+                color,
+                tex_coord,
+                normal,
             );
 
             if let Some(index) = unique_vertices.get(&vertex) {
@@ -84,7 +154,14 @@ pub(crate) fn load_model(url: &String, vertices: &mut Vec<Vertex>, indices: &mut
                 indices.push(index as u32);
             }
         }
+
+        if !has_normals {
+            generated.extend_from_slice(&indices[first_index..]);
+        }
+        submeshes.push(SubMesh::new(&model.name, first_index, indices.len() - first_index, mesh.material_id));
     }    
 
+    generate_normals(vertices, &generated);
+
     Ok(())
-}
\ No newline at end of file
+}
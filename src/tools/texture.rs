@@ -1,43 +1,158 @@
-use std::fs::File;
+use std::{fs::{self, File}, io::{BufWriter, Read}, path::Path};
 
 use super::loader::Loadable;
-use anyhow::{Error, anyhow};
+use anyhow::{Error, Result, anyhow};
+
+/// Pixel layout of a texture, each one maps to a single GPU format.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TextureFormat {
+    /// 8 bits per channel, sRGB encoded colors.
+    Rgba8Srgb,
+    /// 32 bits float per channel, linear high dynamic range colors.
+    Rgba32Float,
+}
+
+impl TextureFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            TextureFormat::Rgba8Srgb => 4,
+            TextureFormat::Rgba32Float => 16,
+        }
+    }
+}
 
 pub struct Texture {
     data: Vec<u8>, 
-    info: png::OutputInfo,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
 }
 
 impl Texture {
-    pub(crate) fn construct(data: Vec<u8>, info: png::OutputInfo) -> Self {
-        Self { data, info }
+    /// Wraps tightly packed 8 bits RGBA pixels.
+    pub fn from_rgba(width: u32, height: u32, data: Vec<u8>) -> Self {
+        Self { data, width, height, format: TextureFormat::Rgba8Srgb }
+    }
+
+    /// Wraps tightly packed float RGBA pixels.
+    pub fn from_rgba_f32(width: u32, height: u32, data: &[f32]) -> Self {
+        let data = data.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        Self { data, width, height, format: TextureFormat::Rgba32Float }
+    }
+
+    /// Writes the pixels to a PNG file, float textures can not be saved.
+    pub fn save_png(&self, path: &str) -> Result<()> {
+        if self.format != TextureFormat::Rgba8Srgb {
+            return Err(anyhow!("Only 8 bits textures can be saved as PNG."));
+        }
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width(), self.height());
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.data)?;
+        Ok(())
     }
 
     pub fn data(&self) -> &[u8] {
         self.data.as_ref()
     }
     pub fn width(&self) -> u32 {
-        self.info.width
+        self.width
     }
     pub fn height(&self) -> u32 {
-        self.info.height
+        self.height
+    }
+    pub fn format(&self) -> TextureFormat {
+        self.format
     }
     pub fn buffer_size(&self) -> usize {
-        self.info.buffer_size()
+        self.data.len()
     }
 }
 
 impl Loadable for Texture {
-    fn load(path: &String) -> Result<Self, Error> {
-        let image = File::open(path)?;
-        let decoder = png::Decoder::new(image);
-        let (info, mut reader) = match decoder.read_info() {
-            Ok(tuple) => tuple,
-            Err(e) => return Err(anyhow!(e))
-        };
-
-        let mut data = vec![0; info.buffer_size()];
-        reader.next_frame(&mut data)?;
-        Ok(Texture::construct(data, info))
+    fn load(path: &str) -> Result<Self, Error> {
+        let bytes = fs::read(path)?;
+        let extension = Path::new(path).extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        decode(&bytes, &extension).map_err(|e| anyhow!("Unable to load texture `{}`: {}", path, e))
+    }
+}
+
+//================================================
+// Decoding
+//================================================
+
+/// Decodes PNG, JPEG, BMP and Radiance HDR images from their magic bytes,
+/// TGA having none it is recognized by its extension.
+pub fn decode(bytes: &[u8], extension: &str) -> Result<Texture> {
+    if bytes.starts_with(b"\x89PNG") {
+        decode_png(bytes)
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        decode_ldr(bytes, image::ImageFormat::Jpeg)
+    } else if bytes.starts_with(b"BM") {
+        decode_ldr(bytes, image::ImageFormat::Bmp)
+    } else if bytes.starts_with(b"#?RADIANCE") || bytes.starts_with(b"#?RGBE") {
+        decode_hdr(bytes)
+    } else if extension == "tga" {
+        decode_ldr(bytes, image::ImageFormat::Tga)
+    } else {
+        Err(anyhow!("unknown image format, expected PNG, JPEG, TGA, BMP or HDR"))
+    }
+}
+
+fn decode_ldr(bytes: &[u8], format: image::ImageFormat) -> Result<Texture> {
+    let image = image::load_from_memory_with_format(bytes, format)?.to_rgba8();
+    Ok(Texture::from_rgba(image.width(), image.height(), image.into_raw()))
+}
+
+/// Radiance HDR images keep their float colors, for environment lighting.
+fn decode_hdr(bytes: &[u8]) -> Result<Texture> {
+    let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Hdr)?.to_rgba32f();
+    Ok(Texture::from_rgba_f32(image.width(), image.height(), image.as_raw()))
+}
+
+//================================================
+// PNG
+//================================================
+
+/// Decodes a PNG image to 8 bits RGBA, the only layout uploaded by the renderer.
+///
+/// Palettes and transparency chunks are expanded, bit depths below 8 are scaled up
+/// and 16 bits channels keep their most significant byte.
+pub(crate) fn decode_png<R: Read>(image: R) -> Result<Texture> {
+    let mut decoder = png::Decoder::new(image);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info()?;
+
+    let mut data = vec![0; info.buffer_size()];
+    reader.next_frame(&mut data)?;
+
+    if info.bit_depth != png::BitDepth::Eight {
+        return Err(anyhow!("unsupported PNG bit depth {:?} for {:?} pixels", info.bit_depth, info.color_type));
+    }
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::RGB => 3,
+        png::ColorType::RGBA => 4,
+        png::ColorType::Indexed => return Err(anyhow!("unexpanded PNG palette")),
+    };
+
+    let mut rgba = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+    for row in data.chunks_exact(info.line_size) {
+        for pixel in row[..info.width as usize * channels].chunks_exact(channels) {
+            let pixel = match *pixel {
+                [l] => [l, l, l, 255],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!(),
+            };
+            rgba.extend_from_slice(&pixel);
+        }
     }
+    Ok(Texture::from_rgba(info.width, info.height, rgba))
 }
\ No newline at end of file